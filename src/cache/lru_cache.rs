@@ -0,0 +1,480 @@
+use crate::hash::flat_hash_map::FlatHashMap;
+use alloc::vec::Vec;
+use core::{borrow::Borrow, hash::Hash};
+
+/// Marks the absence of a neighbour in the recency list.
+const NIL: usize = usize::MAX;
+
+/// A map with a fixed capacity that evicts its least-recently-used entry to make room for new
+/// ones.
+///
+/// The entries live in a vector and are threaded onto a doubly-linked list by index, from the
+/// most to the least recently used, so refreshing or evicting an entry only rewires a few
+/// indices. A [`FlatHashMap`] maps each key to the position of its entry, so looking up,
+/// refreshing and evicting an entry all take expected `O(1)` time. Keys are stored both in the
+/// map and next to their value, so they need to be [`Clone`] as well as [`Hash`] and [`Eq`].
+///
+/// Inserting, [`get`](LruCache::get) and [`get_mut`](LruCache::get_mut) mark an entry as the most
+/// recently used; [`peek`](LruCache::peek) and iteration do not.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::cache::lru_cache::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("apple", 3);
+/// cache.put("pear", 5);
+///
+/// // Looking up "apple" makes "pear" the least recently used entry.
+/// assert_eq!(cache.get("apple"), Some(&3));
+/// cache.put("fig", 7);
+///
+/// assert_eq!(cache.peek("pear"), None);
+/// assert!(cache.iter().map(|(key, _)| *key).eq(["fig", "apple"]));
+/// ```
+pub struct LruCache<K, V> {
+    entries: Vec<Entry<K, V>>,
+    index: FlatHashMap<K, usize>,
+    capacity: usize,
+    /// The most recently used entry.
+    head: usize,
+    /// The least recently used entry.
+    tail: usize,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    /// The next more recently used entry.
+    prev: usize,
+    /// The next less recently used entry.
+    next: usize,
+}
+
+impl<K, V> LruCache<K, V> {
+    /// Returns the number of entries the cache holds before it starts evicting.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the entries, from the most to the least recently used.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            entries: &self.entries,
+            front: self.head,
+            back: self.tail,
+            len: self.entries.len(),
+        }
+    }
+
+    /// Takes the entry at `slot` out of the recency list.
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = (self.entries[slot].prev, self.entries[slot].next);
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next].prev = prev,
+        }
+    }
+
+    /// Puts the unlinked entry at `slot` at the front of the recency list.
+    fn push_front(&mut self, slot: usize) {
+        self.entries[slot].prev = NIL;
+        self.entries[slot].next = self.head;
+        match self.head {
+            NIL => self.tail = slot,
+            head => self.entries[head].prev = slot,
+        }
+        self.head = slot;
+    }
+
+    fn touch(&mut self, slot: usize) {
+        if self.head != slot {
+            self.unlink(slot);
+            self.push_front(slot);
+        }
+    }
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Constructs an empty cache that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be non-zero");
+        Self {
+            entries: Vec::new(),
+            index: FlatHashMap::new(),
+            capacity,
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    /// Inserts a key-value pair and marks it as the most recently used entry.
+    ///
+    /// If the key is already present, its value is replaced and the old value is returned.
+    /// Otherwise, if the cache is full, the least recently used entry is evicted first.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&slot) = self.index.get(&key) {
+            self.touch(slot);
+            return Some(core::mem::replace(&mut self.entries[slot].value, value));
+        }
+
+        if self.entries.len() == self.capacity {
+            // Reuse the slot of the evicted entry.
+            let slot = self.tail;
+            self.index.remove(&self.entries[slot].key);
+            self.index.insert(key.clone(), slot);
+            self.entries[slot].key = key;
+            self.entries[slot].value = value;
+            self.touch(slot);
+        } else {
+            let slot = self.entries.len();
+            self.index.insert(key.clone(), slot);
+            self.entries.push(Entry {
+                key,
+                value,
+                prev: NIL,
+                next: NIL,
+            });
+            self.push_front(slot);
+        }
+        None
+    }
+
+    /// Returns a reference to the value corresponding to the key and marks it as the most
+    /// recently used entry.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key and marks it as the
+    /// most recently used entry.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        self.touch(slot);
+        Some(&mut self.entries[slot].value)
+    }
+
+    /// Returns a reference to the value corresponding to the key without marking it as used.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        Some(&self.entries[slot].value)
+    }
+
+    /// Returns `true` if the cache contains a value for the key, without marking it as used.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).is_some()
+    }
+
+    /// Removes a key from the cache, returning its value if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.index.remove(key)?;
+        Some(self.remove_slot(slot).1)
+    }
+
+    /// Removes and returns the least recently used entry, or [`None`] if the cache is empty.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        if self.tail == NIL {
+            return None;
+        }
+        let slot = self.tail;
+        self.index.remove(&self.entries[slot].key);
+        Some(self.remove_slot(slot))
+    }
+
+    /// Removes every entry from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    /// Removes the entry at `slot`, whose key has already been taken out of the index, and moves
+    /// the last entry into its place to keep the vector dense.
+    fn remove_slot(&mut self, slot: usize) -> (K, V) {
+        self.unlink(slot);
+        let last = self.entries.len() - 1;
+        if slot != last {
+            let (prev, next) = (self.entries[last].prev, self.entries[last].next);
+            match prev {
+                NIL => self.head = slot,
+                prev => self.entries[prev].next = slot,
+            }
+            match next {
+                NIL => self.tail = slot,
+                next => self.entries[next].prev = slot,
+            }
+            if let Some(index) = self.index.get_mut(&self.entries[last].key) {
+                *index = slot;
+            }
+        }
+        let entry = self.entries.swap_remove(slot);
+        (entry.key, entry.value)
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for LruCache<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| Entry {
+                    key: entry.key.clone(),
+                    value: entry.value.clone(),
+                    prev: entry.prev,
+                    next: entry.next,
+                })
+                .collect(),
+            index: self.index.clone(),
+            capacity: self.capacity,
+            head: self.head,
+            tail: self.tail,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for LruCache<K, V> {
+    /// Formats the entries as a map, from the most to the least recently used.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`LruCache`], from the most to the least recently used.
+///
+/// This `struct` is created by [`LruCache::iter`].
+pub struct Iter<'a, K, V> {
+    entries: &'a [Entry<K, V>],
+    front: usize,
+    back: usize,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let entry = &self.entries[self.front];
+        self.front = entry.next;
+        self.len -= 1;
+        Some((&entry.key, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let entry = &self.entries[self.back];
+        self.back = entry.prev;
+        self.len -= 1;
+        Some((&entry.key, &entry.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries,
+            front: self.front,
+            back: self.back,
+            len: self.len,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::VecDeque, format};
+
+    fn keys<K: Copy, V>(cache: &LruCache<K, V>) -> Vec<K> {
+        cache.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(3);
+        for key in 1..=3 {
+            assert_eq!(cache.put(key, key * 10), None);
+        }
+        assert_eq!(keys(&cache), [3, 2, 1]);
+
+        cache.put(4, 40);
+        assert_eq!(keys(&cache), [4, 3, 2]);
+        assert!(!cache.contains_key(&1));
+
+        assert_eq!(cache.get(&2), Some(&20));
+        cache.put(5, 50);
+        assert_eq!(keys(&cache), [5, 2, 4]);
+
+        // Peeking does not save an entry from eviction.
+        assert_eq!(cache.peek(&4), Some(&40));
+        cache.put(6, 60);
+        assert_eq!(keys(&cache), [6, 5, 2]);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn reinserting_refreshes_without_growing() {
+        let mut cache = LruCache::new(3);
+        cache.put('a', 1);
+        cache.put('b', 2);
+        cache.put('c', 3);
+
+        assert_eq!(cache.put('a', 10), Some(1));
+        assert_eq!(cache.len(), 3);
+        assert_eq!(keys(&cache), ['a', 'c', 'b']);
+
+        cache.put('d', 4);
+        assert_eq!(keys(&cache), ['d', 'a', 'c']);
+        assert_eq!(cache.peek(&'a'), Some(&10));
+    }
+
+    #[test]
+    fn capacity_one() {
+        let mut cache = LruCache::new(1);
+        cache.put(1, 'x');
+        assert_eq!(cache.put(1, 'y'), Some('x'));
+        assert_eq!(cache.put(2, 'z'), None);
+        assert_eq!(keys(&cache), [2]);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.pop_lru(), Some((2, 'z')));
+        assert!(cache.is_empty());
+        assert_eq!(cache.pop_lru(), None);
+    }
+
+    #[test]
+    fn pop_lru_and_remove() {
+        let mut cache: LruCache<u32, u32> = LruCache::new(4);
+        for key in 0..4 {
+            cache.put(key, key);
+        }
+        assert_eq!(cache.remove(&1), Some(1));
+        assert_eq!(cache.remove(&1), None);
+        assert_eq!(cache.pop_lru(), Some((0, 0)));
+        assert_eq!(keys(&cache), [3, 2]);
+        assert!(cache.iter().rev().map(|(key, _)| *key).eq([2, 3]));
+
+        cache.put(7, 7);
+        *cache.get_mut(&2).expect("Key should be present") += 100;
+        assert_eq!(format!("{:?}", cache), "{2: 102, 7: 7, 3: 3}");
+    }
+
+    #[test]
+    #[should_panic(expected = "LruCache capacity must be non-zero")]
+    fn zero_capacity() {
+        let _: LruCache<u8, u8> = LruCache::new(0);
+    }
+
+    #[test]
+    fn randomized_against_vec_deque() {
+        let mut rng = Rng::new(445);
+        let mut cache = LruCache::new(16);
+        // The reference keeps entries from the most to the least recently used.
+        let mut reference: VecDeque<(u64, u64)> = VecDeque::new();
+
+        for _ in 0..10_000 {
+            let key = rng.below(40);
+            let found = reference.iter().position(|(k, _)| *k == key);
+            match rng.below(5) {
+                0 => {
+                    let expected = found.and_then(|i| reference.remove(i)).map(|(_, v)| v);
+                    assert_eq!(cache.remove(&key), expected);
+                }
+                1 => {
+                    let expected = found.and_then(|i| reference.remove(i));
+                    if let Some(entry) = expected {
+                        reference.push_front(entry);
+                    }
+                    assert_eq!(cache.get(&key), expected.map(|(_, v)| v).as_ref());
+                }
+                2 => assert_eq!(cache.pop_lru(), reference.pop_back()),
+                _ => {
+                    let value = rng.next_u64();
+                    let old = found.and_then(|i| reference.remove(i)).map(|(_, v)| v);
+                    if old.is_none() && reference.len() == 16 {
+                        reference.pop_back();
+                    }
+                    reference.push_front((key, value));
+                    assert_eq!(cache.put(key, value), old);
+                }
+            }
+
+            assert_eq!(cache.len(), reference.len());
+            assert!(cache
+                .iter()
+                .map(|(k, v)| (*k, *v))
+                .eq(reference.iter().copied()));
+        }
+    }
+}
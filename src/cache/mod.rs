@@ -0,0 +1,2 @@
+/// Least-recently-used caches
+pub mod lru_cache;
@@ -0,0 +1,150 @@
+//! # Collections
+//!
+//! [`Collection`](crate::Collection) is implemented by every data-structure in the crate that
+//! holds a variable number of elements, for code that only needs to measure or empty whatever it
+//! is given.
+//!
+//! # Examples
+//!
+//! ```
+//! use collect_me::{tree::binary_tree::BinaryTree, vec::sorted_vec::SortedVec, Collection};
+//!
+//! /// Empties `buffer` once it holds `limit` elements, returning how many were dropped.
+//! fn flush(buffer: &mut impl Collection, limit: usize) -> usize {
+//!     let len = buffer.len();
+//!     if len < limit {
+//!         return 0;
+//!     }
+//!     buffer.clear();
+//!     len
+//! }
+//!
+//! let mut tree = BinaryTree::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+//! let mut sorted: SortedVec<u32> = [5, 1].into_iter().collect();
+//!
+//! assert_eq!(flush(&mut tree, 3), 3);
+//! assert_eq!(flush(&mut sorted, 3), 0);
+//! assert!(tree.is_empty());
+//! assert_eq!(sorted.len(), 2);
+//! ```
+
+use crate::{
+    cache::lru_cache::LruCache,
+    hash::flat_hash_map::FlatHashMap,
+    list::{doubly_linked_list::DoublyLinkedList, unrolled_list::UnrolledList},
+    monoid::Monoid,
+    set::union_find::{UnionFind, UnionFindMap},
+    tree::{
+        b_tree::BTree, binary_tree::BinaryTree, counter::Counter, fenwick_tree::FenwickTree,
+        interval_tree::IntervalTree, kd_tree::KdTree, pairing_heap::PairingHeap,
+        radix_tree::RadixTree, range_map::RangeMap, red_black_tree::RedBlackTree,
+        segment_tree::SegmentTree, splay_tree::SplayTree, treap::Treap, trie::Trie,
+    },
+    vec::{
+        bit_vec::BitVec, fixed_bit_set::FixedBitSet, slot_map::SlotMap, sorted_map::SortedMap,
+        sorted_vec::SortedVec, sparse_set::SparseSet, stable_vec::StableVec,
+        stack_deque::StackDeque,
+    },
+};
+use core::hash::Hash;
+
+/// A data-structure holding a number of elements that can be counted and removed all at once.
+pub trait Collection {
+    /// Returns the number of elements in the collection.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the collection has no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every element from the collection.
+    fn clear(&mut self);
+}
+
+/// Implements [`Collection`] by forwarding to the inherent methods of each type.
+macro_rules! collections {
+    ($(impl[$($generics:tt)*] $collection:ty;)*) => {
+        $(
+            impl<$($generics)*> Collection for $collection {
+                fn len(&self) -> usize {
+                    self.len()
+                }
+
+                fn is_empty(&self) -> bool {
+                    self.is_empty()
+                }
+
+                fn clear(&mut self) {
+                    self.clear()
+                }
+            }
+        )*
+    };
+}
+
+collections! {
+    impl[K: Hash + Eq + Clone, V] LruCache<K, V>;
+    impl[K, V, S] FlatHashMap<K, V, S>;
+    impl[T] DoublyLinkedList<T>;
+    impl[T, const C: usize] UnrolledList<T, C>;
+    impl[] UnionFind;
+    impl[K] UnionFindMap<K>;
+    impl[K, V, const B: usize] BTree<K, V, B>;
+    impl[K, V] BinaryTree<K, V>;
+    impl[T] Counter<T>;
+    impl[T, M: Monoid<T>] FenwickTree<T, M>;
+    impl[K, V] IntervalTree<K, V>;
+    impl[const D: usize, T] KdTree<D, T>;
+    impl[T] PairingHeap<T>;
+    impl[K, V] RadixTree<K, V>;
+    impl[K, V] RangeMap<K, V>;
+    impl[K, V] RedBlackTree<K, V>;
+    impl[T, M: Monoid<T>] SegmentTree<T, M>;
+    impl[K, V] SplayTree<K, V>;
+    impl[K, V] Treap<K, V>;
+    impl[V] Trie<V>;
+    impl[] BitVec;
+    impl[const BITS: usize, const WORDS: usize] FixedBitSet<BITS, WORDS>;
+    impl[V] SlotMap<V>;
+    impl[K, V] SortedMap<K, V>;
+    impl[T] SortedVec<T>;
+    impl[] SparseSet;
+    impl[T] StableVec<T>;
+    impl[T, const N: usize] StackDeque<T, N>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{monoid::Sum, test_util::assert_clears};
+
+    #[test]
+    fn clear_without_map_tests() {
+        let mut splay = SplayTree::new();
+        splay.insert(1, 'a');
+        assert_clears(&mut splay);
+
+        let mut intervals = IntervalTree::new();
+        intervals.insert(0..4, 'a');
+        assert_clears(&mut intervals);
+
+        assert_clears(&mut KdTree::from_points([
+            ([0.0, 1.0], 'a'),
+            ([1.0, 0.0], 'b'),
+        ]));
+        assert_clears(&mut FenwickTree::<u32, Sum>::from_slice(&[1, 2, 3]));
+        assert_clears(&mut SegmentTree::<u32, Sum>::from_slice(&[1, 2, 3]));
+
+        let mut sets = UnionFind::new(3);
+        sets.union(0, 2);
+        assert_clears(&mut sets);
+        assert_eq!(sets.set_count(), 0);
+
+        let mut keyed = UnionFindMap::new();
+        keyed.union("a", "b");
+        assert_clears(&mut keyed);
+        assert!(!keyed.same_set(&"a", &"b"));
+        assert_eq!(keyed.set_count(), 0);
+    }
+}
@@ -0,0 +1,656 @@
+use super::fnv::FnvBuildHasher;
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash, Hasher},
+    mem, slice,
+};
+
+/// A hash map that stores its entries directly in one array, probing linearly on collisions.
+///
+/// Every entry sits in the first free slot at or after the slot its hash maps to. Insertion uses
+/// Robin Hood hashing: an entry that has been displaced further from its slot than the one in
+/// its way takes over that slot, and the other entry moves on instead. This keeps probe
+/// sequences short and lets lookups stop early. Removal shifts the entries after the removed
+/// one back into place, so there are no tombstones, and heavy churn does not slow the map down.
+/// The array doubles in size once it would be more than seven eighths full.
+///
+/// Hashing defaults to [`FnvHasher`](crate::hash::fnv::FnvHasher), which works without the
+/// standard library but is not resistant to deliberately colliding keys. Any other
+/// [`BuildHasher`] can be plugged in through `S`.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::hash::flat_hash_map::FlatHashMap;
+///
+/// let mut ages: FlatHashMap<&str, u32> = FlatHashMap::new();
+/// ages.insert("ada", 36);
+/// ages.insert("alan", 41);
+/// *ages.get_or_insert_with("grace", || 0) += 85;
+///
+/// assert_eq!(ages.get("alan"), Some(&41));
+/// assert_eq!(ages.remove("ada"), Some(36));
+/// assert_eq!(ages["grace"], 85);
+/// assert_eq!(ages.len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct FlatHashMap<K, V, S = FnvBuildHasher> {
+    /// Either empty or a power of two long.
+    slots: Vec<Option<Slot<K, V>>>,
+    len: usize,
+    hasher: S,
+}
+
+#[derive(Clone)]
+struct Slot<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// The number of slots allocated for the first entry.
+const MIN_SLOTS: usize = 8;
+
+impl<K, V, S: Default> FlatHashMap<K, V, S> {
+    /// Constructs an empty map. This does not allocate.
+    pub fn new() -> Self {
+        Self::with_hasher(S::default())
+    }
+
+    /// Constructs an empty map with room for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, S::default())
+    }
+}
+
+impl<K, V, S> FlatHashMap<K, V, S> {
+    /// Constructs an empty map that hashes keys with `hasher`. This does not allocate.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            hasher,
+        }
+    }
+
+    /// Constructs an empty map with room for at least `capacity` entries that hashes keys with
+    /// `hasher`.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let mut map = Self::with_hasher(hasher);
+        if capacity > 0 {
+            map.slots = empty_slots(slots_for(capacity));
+        }
+        map
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries the map can hold before it needs to grow.
+    pub fn capacity(&self) -> usize {
+        self.slots.len() / 8 * 7
+    }
+
+    /// Returns the hasher the map hashes keys with.
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// Removes every entry from the map, keeping the allocated slots for reuse.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the entries of the map, in no particular order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries of the map with mutable references to the values,
+    /// in no particular order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            slots: self.slots.iter_mut(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys of the map, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values of the map, in no particular order.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Returns the slot that entries with `hash` belong in.
+    fn home(&self, hash: u64) -> usize {
+        // Fibonacci hashing spreads weak hashes over the high bits, which are the ones kept.
+        let bits = self.slots.len().trailing_zeros();
+        (hash.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> (64 - bits)) as usize
+    }
+
+    /// Returns how far the entry with `hash` stored at `index` is from its home slot.
+    fn distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(self.home(hash)) & (self.slots.len() - 1)
+    }
+}
+
+impl<K, V, S> FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Reserves room for at least `additional` more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = slots_for(self.len + additional);
+        if required > self.slots.len() {
+            let old = mem::replace(&mut self.slots, empty_slots(required));
+            // Reinserting counts every entry again.
+            self.len = 0;
+            for slot in old.into_iter().flatten() {
+                self.insert_new(slot);
+            }
+        }
+    }
+
+    /// Inserts a key-value pair into the map. If the key was already present, its value is
+    /// replaced and the old value returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash(&key);
+        if let Some(index) = self.find(hash, &key) {
+            let slot = self.slots[index]
+                .as_mut()
+                .expect("found slots are occupied");
+            return Some(mem::replace(&mut slot.value, value));
+        }
+        self.reserve(1);
+        self.insert_new(Slot { hash, key, value });
+        None
+    }
+
+    /// Returns a reference to the value of `key`, or [`None`] if it is not present.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        self.slots[index].as_ref().map(|slot| &slot.value)
+    }
+
+    /// Returns a mutable reference to the value of `key`, or [`None`] if it is not present.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        self.slots[index].as_mut().map(|slot| &mut slot.value)
+    }
+
+    /// Returns a mutable reference to the value of `key`, inserting the value returned by
+    /// `default` first if the key is not present.
+    pub fn get_or_insert_with<F>(&mut self, key: K, default: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        let hash = self.hash(&key);
+        let index = match self.find(hash, &key) {
+            Some(index) => index,
+            None => {
+                self.reserve(1);
+                self.insert_new(Slot {
+                    hash,
+                    key,
+                    value: default(),
+                })
+            }
+        };
+        let slot = self.slots[index]
+            .as_mut()
+            .expect("found slots are occupied");
+        &mut slot.value
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash(key), key).is_some()
+    }
+
+    /// Removes `key` from the map and returns its value, or [`None`] if it was not present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes `key` from the map and returns the stored key and its value, or [`None`] if it
+    /// was not present.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut hole = self.find(self.hash(key), key)?;
+        let removed = self.slots[hole].take().expect("found slots are occupied");
+        self.len -= 1;
+
+        // Shift the rest of the cluster back by one until an entry that is already in its home
+        // slot, or the end of the cluster. Robin Hood insertion keeps every cluster ordered by
+        // home slot, so nothing further along can need the hole.
+        let mask = self.slots.len() - 1;
+        loop {
+            let next = (hole + 1) & mask;
+            match &self.slots[next] {
+                Some(slot) if self.distance(slot.hash, next) > 0 => {
+                    self.slots.swap(hole, next);
+                    hole = next;
+                }
+                _ => break,
+            }
+        }
+        Some((removed.key, removed.value))
+    }
+
+    // `BuildHasher::hash_one` needs a newer compiler than the crate supports.
+    #[allow(clippy::manual_hash_one)]
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the index of the slot holding `key`, whose hash is `hash`.
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut index = self.home(hash);
+        let mut distance = 0;
+        // The map is never full, so the probe always ends at an empty slot or earlier.
+        while let Some(slot) = &self.slots[index] {
+            if self.distance(slot.hash, index) < distance {
+                // The key would have displaced this entry if it were present.
+                return None;
+            }
+            if slot.hash == hash && slot.key.borrow() == key {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+        None
+    }
+
+    /// Inserts an entry whose key is not present and returns the index it ended up at. There
+    /// must be room for it.
+    fn insert_new(&mut self, slot: Slot<K, V>) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut carried = slot;
+        let mut index = self.home(carried.hash);
+        let mut distance = 0;
+        let mut placed = None;
+        self.len += 1;
+        loop {
+            let resident_distance = match &self.slots[index] {
+                Some(resident) => self.distance(resident.hash, index),
+                None => {
+                    self.slots[index] = Some(carried);
+                    return placed.unwrap_or(index);
+                }
+            };
+            if resident_distance < distance {
+                // Rob the richer entry of its slot, and carry it along instead.
+                let resident = self.slots[index].as_mut().expect("the slot is occupied");
+                mem::swap(resident, &mut carried);
+                placed.get_or_insert(index);
+                distance = resident_distance;
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+    }
+}
+
+/// Returns the number of slots needed to hold `len` entries without exceeding the load factor.
+fn slots_for(len: usize) -> usize {
+    let mut slots = MIN_SLOTS;
+    while slots / 8 * 7 < len {
+        slots *= 2;
+    }
+    slots
+}
+
+fn empty_slots<K, V>(len: usize) -> Vec<Option<Slot<K, V>>> {
+    let mut slots = Vec::with_capacity(len);
+    slots.resize_with(len, || None);
+    slots
+}
+
+impl<K, V, S: Default> Default for FlatHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> PartialEq for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, S> Eq for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug, S> core::fmt::Debug for FlatHashMap<K, V, S> {
+    /// Formats the entries of the map, in no particular order.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S, Q> core::ops::Index<&Q> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value of `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not present in the map.
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key)
+            .expect("Key is not present in the FlatHashMap")
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a FlatHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut FlatHashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the entries of a [`FlatHashMap`], in no particular order.
+///
+/// This `struct` is created by [`FlatHashMap::iter`].
+pub struct Iter<'a, K, V> {
+    slots: slice::Iter<'a, Option<Slot<K, V>>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.slots.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((&slot.key, &slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`FlatHashMap`] with mutable references to the values, in
+/// no particular order.
+///
+/// This `struct` is created by [`FlatHashMap::iter_mut`].
+pub struct IterMut<'a, K, V> {
+    slots: slice::IterMut<'a, Option<Slot<K, V>>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.slots.by_ref().flatten().next()?;
+        self.len -= 1;
+        Some((&slot.key, &mut slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for IterMut<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IterMut").field("len", &self.len).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeMap, format, string::String};
+    use core::hash::BuildHasherDefault;
+
+    /// A hasher that sends every key to the same slot, so that everything collides.
+    #[derive(Default)]
+    struct Colliding;
+
+    impl Hasher for Colliding {
+        fn write(&mut self, _bytes: &[u8]) {}
+
+        fn finish(&self) -> u64 {
+            0
+        }
+    }
+
+    /// Checks that every entry can be found and sits where Robin Hood hashing puts it.
+    fn check<K: Hash + Eq + Ord + core::fmt::Debug, V: PartialEq + core::fmt::Debug, S>(
+        map: &FlatHashMap<K, V, S>,
+        reference: &BTreeMap<K, V>,
+    ) where
+        S: BuildHasher,
+    {
+        assert_eq!(map.len(), reference.len());
+        assert_eq!(map.iter().len(), reference.len());
+        assert!(map.len() <= map.capacity());
+        for (key, value) in reference {
+            assert_eq!(map.get(key), Some(value));
+        }
+        let mask = map.slots.len().wrapping_sub(1);
+        for (index, slot) in map.slots.iter().enumerate() {
+            if let Some(slot) = slot {
+                assert_eq!(slot.hash, map.hash(&slot.key));
+                // No entry may be further from home than its predecessor plus one.
+                let distance = map.distance(slot.hash, index);
+                let prev = (index + mask) & mask;
+                let prev_distance = map.slots[prev]
+                    .as_ref()
+                    .map_or(0, |slot| map.distance(slot.hash, prev) + 1);
+                assert!(distance <= prev_distance);
+            }
+        }
+    }
+
+    fn randomized<S: BuildHasher + Default>(seed: u64, keys: u64, steps: usize) {
+        let mut rng = Rng::new(seed);
+        let mut map: FlatHashMap<u64, u64, S> = FlatHashMap::new();
+        let mut reference = BTreeMap::new();
+
+        for step in 0..steps {
+            let key = rng.below(keys);
+            let value = rng.next_u64();
+            match rng.below(6) {
+                0 | 1 => assert_eq!(map.insert(key, value), reference.insert(key, value)),
+                2 | 3 => assert_eq!(map.remove(&key), reference.remove(&key)),
+                4 => {
+                    *map.get_or_insert_with(key, || value) ^= 1;
+                    *reference.entry(key).or_insert(value) ^= 1;
+                }
+                _ => {
+                    assert_eq!(map.get_mut(&key), reference.get_mut(&key));
+                    assert_eq!(map.contains_key(&key), reference.contains_key(&key));
+                }
+            }
+            if step % 64 == 0 {
+                check(&map, &reference);
+            }
+        }
+        check(&map, &reference);
+        let mut entries: Vec<(u64, u64)> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        entries.sort_unstable();
+        assert!(entries.into_iter().eq(reference.into_iter()));
+    }
+
+    #[test]
+    fn randomized_against_btree_map() {
+        randomized::<FnvBuildHasher>(451, 50, 5_000);
+        randomized::<FnvBuildHasher>(4510, 5_000, 50_000);
+        randomized::<BuildHasherDefault<Colliding>>(4511, 100, 3_000);
+    }
+
+    #[test]
+    fn churn_does_not_grow() {
+        let mut map: FlatHashMap<u64, u64> = (0..1_000).map(|key| (key, key)).collect();
+        let capacity = map.capacity();
+        let mut rng = Rng::new(4512);
+        for round in 0..50_000u64 {
+            let key = rng.below(1_000);
+            assert_eq!(map.remove(&key), Some(key));
+            assert_eq!(map.insert(key, key), None);
+            if round % 1_000 == 0 {
+                assert!((0..1_000).all(|key| map.get(&key) == Some(&key)));
+            }
+        }
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.len(), 1_000);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), capacity);
+        assert_eq!(map.get(&1), None);
+    }
+
+    #[test]
+    fn borrowed_keys_and_traits() {
+        let mut map: FlatHashMap<String, usize> = FlatHashMap::with_capacity(2);
+        assert!(map.capacity() >= 2);
+        for word in ["alpha", "bravo", "charlie"] {
+            map.insert(word.into(), word.len());
+        }
+        assert_eq!(map["charlie"], 7);
+        assert_eq!(map.remove_entry("alpha"), Some(("alpha".into(), 5)));
+        for (_, value) in &mut map {
+            *value *= 2;
+        }
+        let clone = map.clone();
+        assert_eq!(clone, map);
+        assert_eq!(map.get("bravo"), Some(&10));
+        assert_eq!(format!("{:?}", FlatHashMap::<u8, u8>::new()), "{}");
+        assert_eq!(map.keys().count(), 2);
+        assert_eq!(map.values().sum::<usize>(), 24);
+    }
+
+    #[test]
+    #[should_panic(expected = "Key is not present in the FlatHashMap")]
+    fn index_missing_key() {
+        let map: FlatHashMap<i32, i32> = FlatHashMap::new();
+        let _ = map[&1];
+    }
+}
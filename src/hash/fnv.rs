@@ -0,0 +1,81 @@
+use core::hash::{BuildHasherDefault, Hasher};
+
+/// The 64-bit Fowler–Noll–Vo hash function, in its FNV-1a variant.
+///
+/// FNV is tiny and fast for short keys, and needs neither the standard library nor a source of
+/// randomness, which makes it the default hasher of
+/// [`FlatHashMap`](crate::hash::flat_hash_map::FlatHashMap). It is not keyed, though, so anyone
+/// who controls the keys can make them collide on purpose. Maps holding untrusted keys should
+/// use a keyed hasher instead.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::hash::fnv::FnvHasher;
+/// use core::hash::Hasher;
+///
+/// let mut hasher = FnvHasher::default();
+/// hasher.write(b"a");
+/// assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FnvHasher(u64);
+
+/// Builds an [`FnvHasher`] for every key.
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl FnvHasher {
+    /// Constructs a hasher whose initial state is `key` rather than the standard offset basis.
+    pub fn with_key(key: u64) -> Self {
+        Self(key)
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fnv(bytes: &[u8]) -> u64 {
+        let mut hasher = FnvHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn reference_vectors() {
+        assert_eq!(fnv(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn writes_are_concatenated() {
+        let mut hasher = FnvHasher::default();
+        hasher.write(b"foo");
+        hasher.write(b"bar");
+        assert_eq!(hasher.finish(), fnv(b"foobar"));
+        assert_ne!(FnvHasher::with_key(1).finish(), fnv(b""));
+    }
+}
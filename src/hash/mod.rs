@@ -0,0 +1,4 @@
+/// Open-addressing hash maps
+pub mod flat_hash_map;
+/// The Fowler–Noll–Vo hash function
+pub mod fnv;
@@ -0,0 +1,519 @@
+//! Lazy set operations over sorted iterators.
+//!
+//! Every adapter in this module takes two iterators that yield their items in ascending order
+//! and produces a new iterator that is itself sorted. The inputs may come from anywhere, e.g. a
+//! sorted slice or the keys of a [`BinaryTree`](crate::tree::binary_tree::BinaryTree). Sortedness
+//! of the inputs is checked with debug assertions only.
+//!
+//! # Duplicates
+//!
+//! The inputs are treated as multisets. If an item occurs `m` times in `a` and `n` times in `b`,
+//! it occurs
+//!
+//! | Adapter                                                   | times in the output |
+//! |-----------------------------------------------------------|---------------------|
+//! | [`merge_sorted`](crate::iter::merge_sorted)               | `m + n`             |
+//! | [`union_sorted`](crate::iter::union_sorted)               | `max(m, n)`         |
+//! | [`intersection_sorted`](crate::iter::intersection_sorted) | `min(m, n)`         |
+//! | [`difference_sorted`](crate::iter::difference_sorted)     | `m - n` if `m > n`  |
+//!
+//! Whenever equal items from both inputs are matched up, the item from `a` is the one yielded.
+//! For deduplicated inputs this coincides with the set operations on
+//! [`BTreeSet`](alloc::collections::BTreeSet).
+//!
+//! The adapters without a `_by` suffix order items by [`PartialOrd`], treating incomparable
+//! items as equal.
+//!
+//! # Examples
+//!
+//! ```
+//! use collect_me::iter::{intersection_sorted, union_sorted};
+//!
+//! let a = [1, 3, 5, 7];
+//! let b = [3, 4, 5];
+//!
+//! assert!(union_sorted(a, b).eq([1, 3, 4, 5, 7]));
+//! assert!(intersection_sorted(a, b).eq([3, 5]));
+//! ```
+//!
+//! The keys of a [`BinaryTree`](crate::tree::binary_tree::BinaryTree) come out sorted, so two
+//! trees can be combined without collecting their keys first:
+//!
+//! ```
+//! use collect_me::iter::{difference_sorted, union_sorted};
+//! use collect_me::tree::binary_tree::BinaryTree;
+//!
+//! let online = BinaryTree::from([("alice", 3), ("bob", 1), ("dave", 4)]);
+//! let admins = BinaryTree::from([("bob", true), ("carol", false)]);
+//!
+//! assert!(union_sorted(online.keys(), admins.keys()).eq(&["alice", "bob", "carol", "dave"]));
+//! assert!(difference_sorted(online.keys(), admins.keys()).eq(&["alice", "dave"]));
+//! ```
+
+use core::{cmp::Ordering, fmt, iter::Peekable};
+
+type PartialCmp<T> = fn(&T, &T) -> Ordering;
+
+fn partial_cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+/// Pulls the next item out of `iter`, asserting that it does not exceed the item after it.
+fn next_sorted<I, F>(iter: &mut Peekable<I>, cmp: &mut F) -> Option<I::Item>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    let item = iter.next()?;
+    if cfg!(debug_assertions) {
+        if let Some(next) = iter.peek() {
+            assert!(
+                cmp(&item, next) != Ordering::Greater,
+                "input iterator is not sorted"
+            );
+        }
+    }
+    Some(item)
+}
+
+macro_rules! sorted_adapter {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        pub struct $name<A, B, F>
+        where
+            A: Iterator,
+            B: Iterator<Item = A::Item>,
+        {
+            a: Peekable<A>,
+            b: Peekable<B>,
+            cmp: F,
+        }
+
+        impl<A, B, F> $name<A, B, F>
+        where
+            A: Iterator,
+            B: Iterator<Item = A::Item>,
+            F: FnMut(&A::Item, &A::Item) -> Ordering,
+        {
+            fn new(a: A, b: B, cmp: F) -> Self {
+                Self {
+                    a: a.peekable(),
+                    b: b.peekable(),
+                    cmp,
+                }
+            }
+
+            /// Compares the heads of both inputs, treating an exhausted input as larger.
+            fn compare_heads(&mut self) -> Option<Ordering> {
+                match (self.a.peek(), self.b.peek()) {
+                    (Some(a), Some(b)) => Some((self.cmp)(a, b)),
+                    (Some(_), None) => Some(Ordering::Less),
+                    (None, Some(_)) => Some(Ordering::Greater),
+                    (None, None) => None,
+                }
+            }
+
+            fn next_a(&mut self) -> Option<A::Item> {
+                next_sorted(&mut self.a, &mut self.cmp)
+            }
+
+            fn next_b(&mut self) -> Option<A::Item> {
+                next_sorted(&mut self.b, &mut self.cmp)
+            }
+        }
+
+        impl<A, B, F> fmt::Debug for $name<A, B, F>
+        where
+            A: Iterator,
+            B: Iterator<Item = A::Item>,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name)).finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+sorted_adapter!(
+    /// An iterator over every item of two sorted iterators, created by [`merge_sorted`].
+    MergeSorted
+);
+sorted_adapter!(
+    /// An iterator over the sorted union of two sorted iterators, created by [`union_sorted`].
+    UnionSorted
+);
+sorted_adapter!(
+    /// An iterator over the sorted intersection of two sorted iterators, created by
+    /// [`intersection_sorted`].
+    IntersectionSorted
+);
+sorted_adapter!(
+    /// An iterator over the items of one sorted iterator that are missing from another, created
+    /// by [`difference_sorted`].
+    DifferenceSorted
+);
+
+impl<A, B, F> Iterator for MergeSorted<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.compare_heads()? {
+            Ordering::Greater => self.next_b(),
+            _ => self.next_a(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_lower, a_upper) = self.a.size_hint();
+        let (b_lower, b_upper) = self.b.size_hint();
+        let upper = a_upper.zip(b_upper).and_then(|(a, b)| a.checked_add(b));
+        (a_lower.saturating_add(b_lower), upper)
+    }
+}
+
+impl<A, B, F> Iterator for UnionSorted<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.compare_heads()? {
+            Ordering::Less => self.next_a(),
+            Ordering::Greater => self.next_b(),
+            Ordering::Equal => {
+                self.next_b();
+                self.next_a()
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_lower, a_upper) = self.a.size_hint();
+        let (b_lower, b_upper) = self.b.size_hint();
+        let upper = a_upper.zip(b_upper).and_then(|(a, b)| a.checked_add(b));
+        (a_lower.max(b_lower), upper)
+    }
+}
+
+impl<A, B, F> Iterator for IntersectionSorted<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.a.peek().is_some() && self.b.peek().is_some() {
+            match self.compare_heads()? {
+                Ordering::Less => {
+                    self.next_a();
+                }
+                Ordering::Greater => {
+                    self.next_b();
+                }
+                Ordering::Equal => {
+                    self.next_b();
+                    return self.next_a();
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = match (self.a.size_hint().1, self.b.size_hint().1) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        (0, upper)
+    }
+}
+
+impl<A, B, F> Iterator for DifferenceSorted<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.a.peek().is_some() {
+            match self.compare_heads()? {
+                Ordering::Less => return self.next_a(),
+                Ordering::Greater => {
+                    self.next_b();
+                }
+                Ordering::Equal => {
+                    self.next_a();
+                    self.next_b();
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.a.size_hint().1)
+    }
+}
+
+/// Merges two sorted iterators into one sorted iterator, keeping every item of both.
+///
+/// Equal items are yielded from `a` before `b`, so the merge is stable.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::iter::merge_sorted;
+///
+/// assert!(merge_sorted([1, 2, 4], [2, 3]).eq([1, 2, 2, 3, 4]));
+/// ```
+pub fn merge_sorted<T, A, B>(a: A, b: B) -> MergeSorted<A::IntoIter, B::IntoIter, PartialCmp<T>>
+where
+    T: PartialOrd,
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+{
+    merge_sorted_by(a, b, partial_cmp)
+}
+
+/// Like [`merge_sorted`], but with a custom comparison function.
+pub fn merge_sorted_by<T, A, B, F>(a: A, b: B, cmp: F) -> MergeSorted<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    MergeSorted::new(a.into_iter(), b.into_iter(), cmp)
+}
+
+/// Yields the sorted union of two sorted iterators.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::iter::union_sorted;
+///
+/// assert!(union_sorted([1, 2, 2, 4], [2, 3]).eq([1, 2, 2, 3, 4]));
+/// ```
+///
+/// The keys of two trees can be combined directly:
+///
+/// ```
+/// use collect_me::iter::union_sorted;
+/// use collect_me::tree::binary_tree::BinaryTree;
+///
+/// let a = BinaryTree::from([(1, 'a'), (4, 'd')]);
+/// let b = BinaryTree::from([(2, 'b'), (4, 'D')]);
+///
+/// assert!(union_sorted(a.keys(), b.keys()).copied().eq([1, 2, 4]));
+/// ```
+pub fn union_sorted<T, A, B>(a: A, b: B) -> UnionSorted<A::IntoIter, B::IntoIter, PartialCmp<T>>
+where
+    T: PartialOrd,
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+{
+    union_sorted_by(a, b, partial_cmp)
+}
+
+/// Like [`union_sorted`], but with a custom comparison function.
+pub fn union_sorted_by<T, A, B, F>(a: A, b: B, cmp: F) -> UnionSorted<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    UnionSorted::new(a.into_iter(), b.into_iter(), cmp)
+}
+
+/// Yields the sorted intersection of two sorted iterators.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::iter::intersection_sorted;
+///
+/// assert!(intersection_sorted([1, 2, 2, 4], [2, 2, 2, 3, 4]).eq([2, 2, 4]));
+/// ```
+pub fn intersection_sorted<T, A, B>(
+    a: A,
+    b: B,
+) -> IntersectionSorted<A::IntoIter, B::IntoIter, PartialCmp<T>>
+where
+    T: PartialOrd,
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+{
+    intersection_sorted_by(a, b, partial_cmp)
+}
+
+/// Like [`intersection_sorted`], but with a custom comparison function.
+pub fn intersection_sorted_by<T, A, B, F>(
+    a: A,
+    b: B,
+    cmp: F,
+) -> IntersectionSorted<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    IntersectionSorted::new(a.into_iter(), b.into_iter(), cmp)
+}
+
+/// Yields the items of the sorted iterator `a` that do not appear in the sorted iterator `b`.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::iter::difference_sorted;
+///
+/// assert!(difference_sorted([1, 2, 2, 4], [2, 3]).eq([1, 2, 4]));
+/// ```
+pub fn difference_sorted<T, A, B>(
+    a: A,
+    b: B,
+) -> DifferenceSorted<A::IntoIter, B::IntoIter, PartialCmp<T>>
+where
+    T: PartialOrd,
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+{
+    difference_sorted_by(a, b, partial_cmp)
+}
+
+/// Like [`difference_sorted`], but with a custom comparison function.
+pub fn difference_sorted_by<T, A, B, F>(
+    a: A,
+    b: B,
+    cmp: F,
+) -> DifferenceSorted<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator<Item = T>,
+    B: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    DifferenceSorted::new(a.into_iter(), b.into_iter(), cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeSet, vec::Vec};
+
+    fn random_set(rng: &mut Rng) -> BTreeSet<u64> {
+        let len = rng.below(40);
+        (0..len).map(|_| rng.below(60)).collect()
+    }
+
+    fn random_multiset(rng: &mut Rng) -> Vec<u64> {
+        let len = rng.below(40);
+        let mut items: Vec<u64> = (0..len).map(|_| rng.below(10)).collect();
+        items.sort_unstable();
+        items
+    }
+
+    fn count(items: &[u64], item: u64) -> usize {
+        items.iter().filter(|&&i| i == item).count()
+    }
+
+    #[test]
+    fn set_operations() {
+        let mut rng = Rng::new(0x5EED);
+        for _ in 0..200 {
+            let a = random_set(&mut rng);
+            let b = random_set(&mut rng);
+
+            assert!(union_sorted(&a, &b).eq(a.union(&b)));
+            assert!(intersection_sorted(&a, &b).eq(a.intersection(&b)));
+            assert!(difference_sorted(&a, &b).eq(a.difference(&b)));
+            assert!(difference_sorted(&b, &a).eq(b.difference(&a)));
+
+            let mut merged: Vec<u64> = a.iter().chain(&b).copied().collect();
+            merged.sort_unstable();
+            assert!(merge_sorted(&a, &b).copied().eq(merged));
+        }
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut rng = Rng::new(42);
+        for _ in 0..200 {
+            let a = random_multiset(&mut rng);
+            let b = random_multiset(&mut rng);
+
+            let merged: Vec<u64> = merge_sorted(&a, &b).copied().collect();
+            let union: Vec<u64> = union_sorted(&a, &b).copied().collect();
+            let intersection: Vec<u64> = intersection_sorted(&a, &b).copied().collect();
+            let difference: Vec<u64> = difference_sorted(&a, &b).copied().collect();
+
+            for output in [&merged, &union, &intersection, &difference] {
+                assert!(output.windows(2).all(|w| w[0] <= w[1]));
+            }
+
+            for item in 0..10 {
+                let (m, n) = (count(&a, item), count(&b, item));
+                assert_eq!(count(&merged, item), m + n);
+                assert_eq!(count(&union, item), m.max(n));
+                assert_eq!(count(&intersection, item), m.min(n));
+                assert_eq!(count(&difference, item), m.saturating_sub(n));
+            }
+        }
+    }
+
+    #[test]
+    fn equal_items_come_from_a() {
+        let a = [(1, 'a'), (2, 'a')];
+        let b = [(1, 'b'), (2, 'b'), (3, 'b')];
+        let by_key = |x: &(i32, char), y: &(i32, char)| x.0.cmp(&y.0);
+
+        assert!(union_sorted_by(a, b, by_key).eq([(1, 'a'), (2, 'a'), (3, 'b')]));
+        assert!(intersection_sorted_by(a, b, by_key).eq([(1, 'a'), (2, 'a')]));
+        assert!(merge_sorted_by(a, b, by_key).eq([
+            (1, 'a'),
+            (1, 'b'),
+            (2, 'a'),
+            (2, 'b'),
+            (3, 'b')
+        ]));
+        assert_eq!(
+            difference_sorted_by(b, a, by_key).collect::<Vec<_>>(),
+            [(3, 'b')]
+        );
+    }
+
+    #[test]
+    fn reverse_comparator() {
+        let a = [5, 3, 1];
+        let b = [4, 3];
+        assert!(union_sorted_by(a, b, |x, y| y.cmp(x)).eq([5, 4, 3, 1]));
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let empty: [i32; 0] = [];
+        assert!(union_sorted(empty, [1, 2]).eq([1, 2]));
+        assert!(intersection_sorted([1, 2], empty).eq(empty));
+        assert!(difference_sorted([1, 2], empty).eq([1, 2]));
+        assert!(merge_sorted(empty, empty).eq(empty));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "not sorted")]
+    fn unsorted_input() {
+        let _ = union_sorted([2, 1], [3]).count();
+    }
+}
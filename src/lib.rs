@@ -9,5 +9,34 @@
 //! Extra data-structures relating to data-lookup not defined in the standard library
 
 extern crate alloc;
+/// Caches with eviction policies
+pub mod cache;
+/// A trait shared by every data-structure with a variable number of elements
+pub mod collection;
+/// Hash-based data-structures
+pub mod hash;
+/// Adapters over sorted iterators
+pub mod iter;
+/// List-like data-structures
+pub mod list;
+/// A trait shared by the map-like data-structures
+pub mod map;
+/// Associative operations for aggregating collections
+pub mod monoid;
+/// Totally ordered wrappers for floating-point keys
+pub mod ord;
+/// Set-like data-structures
+pub mod set;
+/// Conformance tests for implementations of the crate's traits
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 /// Tree-like data-structures
 pub mod tree;
+/// Vector-like data-structures
+pub mod vec;
+
+pub use collection::Collection;
+pub use map::Map;
+
+#[cfg(any(test, feature = "testing"))]
+mod test_util;
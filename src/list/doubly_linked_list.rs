@@ -0,0 +1,922 @@
+use alloc::boxed::Box;
+use core::{marker::PhantomData, mem, ptr::NonNull};
+
+/// A doubly-linked list that owns its elements, with a cursor for editing it in place.
+///
+/// Every element lives in a node of its own, which never moves once allocated. Besides the
+/// usual operations on both ends, a [`CursorMut`] walks the list and inserts, removes, splices
+/// or splits it at its position in constant time.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::list::doubly_linked_list::DoublyLinkedList;
+///
+/// let mut list: DoublyLinkedList<i32> = (1..=5).collect();
+/// let mut cursor = list.cursor_front_mut();
+/// cursor.move_next();
+/// assert_eq!(cursor.remove_current(), Some(2));
+/// cursor.insert_after(10);
+///
+/// let tail = cursor.split_after();
+/// assert!(list.iter().eq(&[1, 3]));
+/// assert!(tail.iter().eq(&[10, 4, 5]));
+/// ```
+pub struct DoublyLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    /// The list owns its nodes, as far as drop checking and variance are concerned.
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+// SAFETY: The list owns its elements like a `Box` would, and the raw pointers are never shared
+// with anything outside the list and the borrows handed out from it.
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+
+// SAFETY: As above. A shared list only ever hands out shared references to its elements.
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+impl<T> DoublyLinkedList<T> {
+    /// Constructs an empty list.
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the first element, or [`None`] if the list is empty.
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: Every link of the list points at a live node it owns, and the returned
+        // reference borrows the list.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// Returns a mutable reference to the first element, or [`None`] if the list is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: As in `front`, and the returned reference borrows the list mutably.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Returns a reference to the last element, or [`None`] if the list is empty.
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: As in `front`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// Returns a mutable reference to the last element, or [`None`] if the list is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: As in `front_mut`.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Prepends `value` to the list.
+    pub fn push_front(&mut self, value: T) {
+        let node = Node::allocate(value);
+        // SAFETY: The node was just allocated and belongs to no list yet.
+        unsafe { self.link_chain(None, self.head, node, node, 1) };
+    }
+
+    /// Appends `value` to the list.
+    pub fn push_back(&mut self, value: T) {
+        let node = Node::allocate(value);
+        // SAFETY: As in `push_front`.
+        unsafe { self.link_chain(self.tail, None, node, node, 1) };
+    }
+
+    /// Removes the first element and returns it, or [`None`] if the list is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: The head is a node of this list.
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the list is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: The tail is a node of this list.
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// Moves every element of `other` to the end of the list, leaving `other` empty.
+    ///
+    /// This takes constant time.
+    pub fn append(&mut self, other: &mut Self) {
+        let other = mem::take(other);
+        // SAFETY: The tail is the last node of this list.
+        unsafe { self.splice(self.tail, None, other) };
+    }
+
+    /// Removes every element from the list.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns a front-to-back iterator over the elements of the list.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a front-to-back iterator over mutable references to the elements of the list.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns a cursor pointing at the first element, or at the ghost position if the list is
+    /// empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// Returns a cursor pointing at the last element, or at the ghost position if the list is
+    /// empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    /// Links the chain of `len` nodes from `first` to `last` in between `prev` and `next`.
+    ///
+    /// # Safety
+    ///
+    /// `prev` must be [`None`] or a node of this list, and `next` must be its successor in this
+    /// list. The chain must be properly linked, owned by no list and exactly `len` nodes long.
+    unsafe fn link_chain(
+        &mut self,
+        prev: Link<T>,
+        next: Link<T>,
+        first: NonNull<Node<T>>,
+        last: NonNull<Node<T>>,
+        len: usize,
+    ) {
+        // SAFETY: The caller guarantees that every pointer refers to a live node, and no
+        // references to any of them are held while the links are rewritten.
+        unsafe {
+            (*first.as_ptr()).prev = prev;
+            (*last.as_ptr()).next = next;
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(first),
+                None => self.head = Some(first),
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(last),
+                None => self.tail = Some(last),
+            }
+        }
+        self.len += len;
+    }
+
+    /// Moves every node of `other` in between `prev` and `next`.
+    ///
+    /// # Safety
+    ///
+    /// `prev` and `next` must be as for `link_chain`.
+    unsafe fn splice(&mut self, prev: Link<T>, next: Link<T>, other: Self) {
+        let mut other = other;
+        let len = mem::take(&mut other.len);
+        if let (Some(first), Some(last)) = (other.head.take(), other.tail.take()) {
+            // SAFETY: `other` was a valid list and has given up its nodes, which no list owns
+            // anymore. The caller guarantees the rest.
+            unsafe { self.link_chain(prev, next, first, last, len) };
+        }
+    }
+
+    /// Detaches `node` from the list, frees it and returns its value.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a node of this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        // SAFETY: The caller guarantees that `node` belongs to this list, which allocated it
+        // with `Node::allocate`. Unlinking it first removes every pointer to it from the list.
+        unsafe {
+            let Node { value, prev, next } = *Box::from_raw(node.as_ptr());
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.head = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.tail = prev,
+            }
+            self.len -= 1;
+            value
+        }
+    }
+}
+
+impl<T> Node<T> {
+    /// Moves `value` into a fresh, unlinked node on the heap.
+    fn allocate(value: T) -> NonNull<Self> {
+        let node = Box::new(Self {
+            value,
+            prev: None,
+            next: None,
+        });
+        NonNull::from(Box::leak(node))
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+/// A front-to-back iterator over the elements of a [`DoublyLinkedList`].
+///
+/// This `struct` is created by [`DoublyLinkedList::iter`].
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+// SAFETY: The iterator only hands out shared references, like `&DoublyLinkedList<T>` would.
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+
+// SAFETY: As above.
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: The iterator borrows the list, so the `len` nodes between `head` and
+            // `tail` stay alive and unchanged for `'a`.
+            let node = unsafe { &*node.as_ptr() };
+            self.len -= 1;
+            self.head = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: As in `next`.
+            let node = unsafe { &*node.as_ptr() };
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self { ..*self }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A front-to-back iterator over mutable references to the elements of a [`DoublyLinkedList`].
+///
+/// This `struct` is created by [`DoublyLinkedList::iter_mut`].
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+// SAFETY: The iterator hands out mutable references, like `&mut DoublyLinkedList<T>` would.
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+
+// SAFETY: A shared `IterMut` gives access to nothing.
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: The iterator borrows the list mutably, and every node is yielded at most
+            // once since `len` counts the nodes left between `head` and `tail`.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.len -= 1;
+            self.head = node.next;
+            &mut node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: As in `next`.
+            let node = unsafe { &mut *node.as_ptr() };
+            self.len -= 1;
+            self.tail = node.prev;
+            &mut node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for IterMut<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let remaining = Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        };
+        f.debug_list().entries(remaining).finish()
+    }
+}
+
+/// An owning front-to-back iterator over the elements of a [`DoublyLinkedList`].
+///
+/// This `struct` is created by the [`IntoIterator`] implementation of [`DoublyLinkedList`].
+/// Dropping it drops the elements that were not yielded.
+#[derive(Clone, Debug)]
+pub struct IntoIter<T> {
+    list: DoublyLinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+/// A cursor over a [`DoublyLinkedList`] that can edit the list at its position.
+///
+/// The cursor points either at an element or at the "ghost" position, which sits past the back
+/// and before the front of the list, as if the list were a ring. Moving past either end lands on
+/// the ghost, and moving on from the ghost wraps around to the other end.
+///
+/// This `struct` is created by [`DoublyLinkedList::cursor_front_mut`] and
+/// [`DoublyLinkedList::cursor_back_mut`].
+pub struct CursorMut<'a, T> {
+    current: Link<T>,
+    /// The index of the current element, or the length of the list at the ghost position.
+    index: usize,
+    list: &'a mut DoublyLinkedList<T>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Returns the index of the current element, or [`None`] at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// Returns a mutable reference to the current element, or [`None`] at the ghost position.
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: The current node belongs to the list the cursor borrows mutably, and the
+        // returned reference borrows the cursor.
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Returns a mutable reference to the element after the current one, or [`None`] if there
+    /// is none. At the ghost position this is the first element.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        // SAFETY: As in `current`.
+        self.next_link()
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Returns a mutable reference to the element before the current one, or [`None`] if there
+    /// is none. At the ghost position this is the last element.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        // SAFETY: As in `current`.
+        self.prev_link()
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Moves the cursor to the next element, or from the last element to the ghost position.
+    pub fn move_next(&mut self) {
+        self.index = match self.current {
+            Some(_) => self.index + 1,
+            None => 0,
+        };
+        self.current = self.next_link();
+    }
+
+    /// Moves the cursor to the previous element, or from the first element to the ghost
+    /// position.
+    pub fn move_prev(&mut self) {
+        self.index = match (self.current, self.index) {
+            (Some(_), 0) => self.list.len,
+            (Some(_), index) => index - 1,
+            (None, _) => self.list.len.saturating_sub(1),
+        };
+        self.current = self.prev_link();
+    }
+
+    /// Inserts `value` before the current element, or at the back of the list at the ghost
+    /// position. The cursor stays where it is.
+    pub fn insert_before(&mut self, value: T) {
+        let node = Node::allocate(value);
+        let prev = self.prev_link();
+        // SAFETY: `prev` and the current node are neighbours in the list, or the ends of the
+        // list at the ghost position, and the node was just allocated.
+        unsafe { self.list.link_chain(prev, self.current, node, node, 1) };
+        self.index += 1;
+    }
+
+    /// Inserts `value` after the current element, or at the front of the list at the ghost
+    /// position. The cursor stays where it is.
+    pub fn insert_after(&mut self, value: T) {
+        let node = Node::allocate(value);
+        let next = self.next_link();
+        // SAFETY: As in `insert_before`.
+        unsafe { self.list.link_chain(self.current, next, node, node, 1) };
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    /// Removes the current element and returns it, or returns [`None`] at the ghost position.
+    /// The cursor moves on to the next element.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        self.current = self.next_link();
+        // SAFETY: `node` was the current node, which belongs to the list.
+        Some(unsafe { self.list.unlink(node) })
+    }
+
+    /// Inserts every element of `other` before the current element, or at the back of the list
+    /// at the ghost position, in constant time. The cursor stays where it is.
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        self.index += other.len;
+        let prev = self.prev_link();
+        // SAFETY: As in `insert_before`.
+        unsafe { self.list.splice(prev, self.current, other) };
+    }
+
+    /// Inserts every element of `other` after the current element, or at the front of the list
+    /// at the ghost position, in constant time. The cursor stays where it is.
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        if self.current.is_none() {
+            self.index += other.len;
+        }
+        let next = self.next_link();
+        // SAFETY: As in `insert_before`.
+        unsafe { self.list.splice(self.current, next, other) };
+    }
+
+    /// Splits the list after the current element and returns everything after it, in constant
+    /// time. At the ghost position the whole list is returned.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let node = match self.current {
+            Some(node) => node,
+            None => {
+                self.index = 0;
+                return mem::take(self.list);
+            }
+        };
+
+        let mut after = DoublyLinkedList::new();
+        // SAFETY: `node` belongs to the list, and so does its successor if there is one. Cutting
+        // the link between them leaves two properly linked chains, each owned by one list.
+        unsafe {
+            if let Some(next) = (*node.as_ptr()).next.take() {
+                (*next.as_ptr()).prev = None;
+                after.head = Some(next);
+                after.tail = self.list.tail;
+                after.len = self.list.len - self.index - 1;
+                self.list.tail = Some(node);
+                self.list.len = self.index + 1;
+            }
+        }
+        after
+    }
+
+    fn next_link(&self) -> Link<T> {
+        match self.current {
+            // SAFETY: The current node belongs to the list the cursor borrows.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        }
+    }
+
+    fn prev_link(&self) -> Link<T> {
+        match self.current {
+            // SAFETY: As in `next_link`.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for CursorMut<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CursorMut")
+            .field("index", &self.index())
+            .field("list", &self.list)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::VecDeque, format, rc::Rc, vec::Vec};
+
+    fn check<T: PartialEq + core::fmt::Debug>(list: &DoublyLinkedList<T>, reference: &[T]) {
+        assert_eq!(list.len(), reference.len());
+        assert!(list.iter().eq(reference));
+        assert!(list.iter().rev().eq(reference.iter().rev()));
+        assert_eq!(list.iter().len(), reference.len());
+        assert_eq!(list.front(), reference.first());
+        assert_eq!(list.back(), reference.last());
+    }
+
+    #[test]
+    fn ends_against_vec_deque() {
+        let mut rng = Rng::new(447);
+        let mut list = DoublyLinkedList::new();
+        let mut reference = VecDeque::new();
+
+        for _ in 0..1_000 {
+            let value = rng.next_u64();
+            match rng.below(5) {
+                0 => {
+                    list.push_front(value);
+                    reference.push_front(value);
+                }
+                1 => {
+                    list.push_back(value);
+                    reference.push_back(value);
+                }
+                2 => assert_eq!(list.pop_front(), reference.pop_front()),
+                3 => assert_eq!(list.pop_back(), reference.pop_back()),
+                _ => {
+                    if let Some(front) = list.front_mut() {
+                        *front ^= 1;
+                        reference[0] ^= 1;
+                    }
+                }
+            }
+            check(&list, reference.make_contiguous());
+        }
+    }
+
+    #[test]
+    fn cursor_against_vec() {
+        let mut rng = Rng::new(4470);
+        let mut list = DoublyLinkedList::new();
+        let mut reference: Vec<u64> = Vec::new();
+        // The position of the cursor in `reference`, where `reference.len()` is the ghost.
+        let mut position = 0;
+
+        for _ in 0..1_000 {
+            let mut cursor = list.cursor_front_mut();
+            for _ in 0..position {
+                cursor.move_next();
+            }
+            let value = rng.below(1_000);
+            match rng.below(8) {
+                0 => {
+                    cursor.move_next();
+                    position = if position == reference.len() {
+                        0
+                    } else {
+                        position + 1
+                    };
+                }
+                1 => {
+                    cursor.move_prev();
+                    position = if position == 0 {
+                        reference.len()
+                    } else {
+                        position - 1
+                    };
+                }
+                2 => {
+                    cursor.insert_before(value);
+                    reference.insert(position, value);
+                    position += 1;
+                }
+                3 => {
+                    cursor.insert_after(value);
+                    if position == reference.len() {
+                        reference.insert(0, value);
+                        position += 1;
+                    } else {
+                        reference.insert(position + 1, value);
+                    }
+                }
+                4 => {
+                    let expected = (position < reference.len()).then(|| reference.remove(position));
+                    assert_eq!(cursor.remove_current(), expected);
+                }
+                5 => {
+                    let other: DoublyLinkedList<u64> = (0..rng.below(3)).collect();
+                    if rng.below(2) == 0 {
+                        cursor.splice_before(other.clone());
+                        let tail = reference.split_off(position);
+                        position += other.len();
+                        reference.extend(other);
+                        reference.extend(tail);
+                    } else {
+                        cursor.splice_after(other.clone());
+                        let at = if position == reference.len() {
+                            position += other.len();
+                            0
+                        } else {
+                            position + 1
+                        };
+                        let tail = reference.split_off(at);
+                        reference.extend(other);
+                        reference.extend(tail);
+                    }
+                }
+                6 => {
+                    let after = cursor.split_after();
+                    let at = if position == reference.len() {
+                        position = 0;
+                        0
+                    } else {
+                        position + 1
+                    };
+                    let expected = reference.split_off(at);
+                    check(&after, &expected);
+                    if rng.below(2) == 0 {
+                        reference.extend(expected);
+                        let mut after = after;
+                        list.append(&mut after);
+                        assert!(after.is_empty());
+                    }
+                }
+                _ => {
+                    if let Some(current) = cursor.current() {
+                        *current += 1;
+                        reference[position] += 1;
+                    }
+                    assert_eq!(
+                        cursor.peek_next().copied(),
+                        reference.get(position + 1).copied().or_else(|| {
+                            (position == reference.len())
+                                .then(|| reference.first().copied())
+                                .flatten()
+                        })
+                    );
+                }
+            }
+
+            let mut cursor = list.cursor_front_mut();
+            for _ in 0..position {
+                cursor.move_next();
+            }
+            let expected = (position < reference.len()).then_some(position);
+            assert_eq!(cursor.index(), expected);
+            assert_eq!(cursor.current().copied(), reference.get(position).copied());
+            check(&list, &reference);
+        }
+    }
+
+    #[test]
+    fn cursor_wraps_around_the_ghost() {
+        let mut list: DoublyLinkedList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.index(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 3));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.index(), Some(2));
+
+        let mut empty: DoublyLinkedList<i32> = DoublyLinkedList::new();
+        let mut cursor = empty.cursor_front_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(1);
+        cursor.insert_before(2);
+        assert_eq!(cursor.remove_current(), None);
+        assert!(cursor.split_after().iter().eq(&[1, 2]));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn iter_mut_from_both_ends() {
+        let mut list: DoublyLinkedList<i32> = (0..6).collect();
+        let mut iter = list.iter_mut();
+        *iter.next().expect("6 elements") *= 10;
+        *iter.next_back().expect("5 elements") *= 10;
+        assert_eq!(iter.len(), 4);
+        for value in iter {
+            *value += 1;
+        }
+        assert!(list.iter().eq(&[0, 2, 3, 4, 5, 50]));
+        assert_eq!(format!("{:?}", list), "[0, 2, 3, 4, 5, 50]");
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&50));
+        assert_eq!(format!("{:?}", iter), "[2, 3, 4, 5]");
+        assert!(list.clone().into_iter().rev().eq([50, 5, 4, 3, 2, 0]));
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let counter = Rc::new(());
+        let counted =
+            |len| -> DoublyLinkedList<Rc<()>> { (0..len).map(|_| Rc::clone(&counter)).collect() };
+
+        {
+            let mut list = counted(5);
+            drop(list.pop_front());
+            drop(list.pop_back());
+            list.append(&mut counted(4));
+            let clone = list.clone();
+            assert_eq!(Rc::strong_count(&counter), 15);
+            drop(clone);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        {
+            let mut iter = counted(6).into_iter();
+            drop(iter.next());
+            drop(iter.next_back());
+            assert_eq!(Rc::strong_count(&counter), 5);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        {
+            let mut list = counted(6);
+            let mut cursor = list.cursor_front_mut();
+            cursor.move_next();
+            drop(cursor.remove_current());
+            cursor.splice_after(counted(2));
+            let after = cursor.split_after();
+            assert_eq!(after.len(), 5);
+            assert_eq!(Rc::strong_count(&counter), 8);
+            drop(after);
+            assert_eq!(Rc::strong_count(&counter), 3);
+            list.clear();
+            assert_eq!(Rc::strong_count(&counter), 1);
+        }
+    }
+
+    #[test]
+    fn equality() {
+        let list: DoublyLinkedList<i32> = (0..4).collect();
+        let mut other = list.clone();
+        assert_eq!(list, other);
+        other.push_back(4);
+        assert_ne!(list, other);
+        assert_eq!(other.pop_back(), Some(4));
+        *other.back_mut().expect("non-empty") = 7;
+        assert_ne!(list, other);
+    }
+}
@@ -0,0 +1,4 @@
+/// Doubly-linked lists with cursors
+pub mod doubly_linked_list;
+/// Unrolled linked lists of inline chunks
+pub mod unrolled_list;
@@ -0,0 +1,557 @@
+use super::doubly_linked_list::{self, CursorMut, DoublyLinkedList};
+use core::{mem::MaybeUninit, ptr, slice};
+
+/// A list that stores up to `C` elements per node, in order.
+///
+/// Chunking the elements keeps the cheap insertion and removal in the middle of a linked list
+/// while scanning through memory mostly sequentially. Positions are found by skipping whole
+/// chunks, so indexing takes `O(n / C)` time, and inserting or removing one element shifts at
+/// most `C` others. A full chunk is split in two when inserting into it, and a chunk that drops
+/// below half full after a removal is merged with a neighbour that is below half full too. This
+/// keeps every other chunk at least half full.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::list::unrolled_list::UnrolledList;
+///
+/// let mut list: UnrolledList<char, 4> = "helo".chars().collect();
+/// list.insert_at(3, 'l');
+/// list.push_back('!');
+/// assert_eq!(list.remove_at(0), 'h');
+///
+/// assert_eq!(list.get(2), Some(&'l'));
+/// assert!(list.iter().copied().eq("ello!".chars()));
+/// ```
+pub struct UnrolledList<T, const C: usize = 32> {
+    chunks: DoublyLinkedList<Chunk<T, C>>,
+    len: usize,
+}
+
+impl<T, const C: usize> UnrolledList<T, C> {
+    /// Constructs an empty list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk size `C` is zero.
+    pub fn new() -> Self {
+        assert!(C > 0, "the chunk size of an UnrolledList must be non-zero");
+        Self {
+            chunks: DoublyLinkedList::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the element at `index`, or [`None`] if it is out of bounds.
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        for chunk in &self.chunks {
+            if index < chunk.len {
+                return chunk.as_slice().get(index);
+            }
+            index -= chunk.len;
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the element at `index`, or [`None`] if it is out of
+    /// bounds.
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        for chunk in &mut self.chunks {
+            if index < chunk.len {
+                return chunk.as_mut_slice().get_mut(index);
+            }
+            index -= chunk.len;
+        }
+        None
+    }
+
+    /// Appends `value` to the list.
+    pub fn push_back(&mut self, value: T) {
+        match self.chunks.back_mut() {
+            Some(chunk) if chunk.len < C => chunk.insert(chunk.len, value),
+            _ => {
+                let mut chunk = Chunk::new();
+                chunk.insert(0, value);
+                self.chunks.push_back(chunk);
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Inserts `value` at `index`, shifting every element after it back by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the list.
+    pub fn insert_at(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "Index out of bounds in UnrolledList");
+        if self.chunks.is_empty() {
+            return self.push_back(value);
+        }
+        self.len += 1;
+
+        let (mut cursor, offset) = seek(&mut self.chunks, index, true);
+        let chunk = cursor.current().expect("the index lies within a chunk");
+        if chunk.len < C {
+            return chunk.insert(offset, value);
+        }
+
+        // Split the full chunk so that the `C + 1` elements end up evenly divided, which leaves
+        // both halves at least half full.
+        let half = C - C / 2;
+        let right = if offset < half {
+            let right = chunk.split_off(half - 1);
+            chunk.insert(offset, value);
+            right
+        } else {
+            let mut right = chunk.split_off(half);
+            right.insert(offset - half, value);
+            right
+        };
+        cursor.insert_after(right);
+    }
+
+    /// Removes the element at `index` and returns it, shifting every element after it forward by
+    /// one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_at(&mut self, index: usize) -> T {
+        assert!(index < self.len, "Index out of bounds in UnrolledList");
+        self.len -= 1;
+
+        let (mut cursor, offset) = seek(&mut self.chunks, index, false);
+        let chunk = cursor.current().expect("the index lies within a chunk");
+        let value = chunk.remove(offset);
+        if chunk.len == 0 {
+            // A chunk of one element is only below half full if the neighbours are not, so
+            // they do not need to be merged.
+            cursor.remove_current();
+            return value;
+        }
+
+        if chunk.is_sparse() && matches!(cursor.peek_prev(), Some(prev) if prev.is_sparse()) {
+            let mut chunk = cursor.remove_current().expect("the cursor is at a chunk");
+            cursor.move_prev();
+            let prev = cursor.current().expect("the chunk had a predecessor");
+            prev.append(&mut chunk);
+        }
+        // After merging with the predecessor, the successor may still be below half full too.
+        let sparse = matches!(cursor.current(), Some(chunk) if chunk.is_sparse());
+        if sparse && matches!(cursor.peek_next(), Some(next) if next.is_sparse()) {
+            cursor.move_next();
+            let mut next = cursor.remove_current().expect("the chunk had a successor");
+            cursor.move_prev();
+            let chunk = cursor.current().expect("the cursor is back at the chunk");
+            chunk.append(&mut next);
+        }
+        value
+    }
+
+    /// Removes every element from the list.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.len = 0;
+    }
+
+    /// Returns a front-to-back iterator over the elements of the list.
+    pub fn iter(&self) -> Iter<'_, T, C> {
+        Iter {
+            chunks: self.chunks.iter(),
+            front: [].iter(),
+            back: [].iter(),
+            len: self.len,
+        }
+    }
+}
+
+/// Returns a cursor at the chunk holding position `index`, along with the position within that
+/// chunk. With `at_end`, a position just past the end of a chunk belongs to that chunk.
+fn seek<T, const C: usize>(
+    chunks: &mut DoublyLinkedList<Chunk<T, C>>,
+    mut index: usize,
+    at_end: bool,
+) -> (CursorMut<'_, Chunk<T, C>>, usize) {
+    let mut cursor = chunks.cursor_front_mut();
+    while let Some(chunk) = cursor.current() {
+        if index < chunk.len || (at_end && index == chunk.len) {
+            break;
+        }
+        index -= chunk.len;
+        cursor.move_next();
+    }
+    (cursor, index)
+}
+
+impl<T, const C: usize> Default for UnrolledList<T, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const C: usize> Clone for UnrolledList<T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: core::fmt::Debug, const C: usize> core::fmt::Debug for UnrolledList<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const C: usize> PartialEq for UnrolledList<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const C: usize> Eq for UnrolledList<T, C> {}
+
+impl<T, const C: usize> Extend<T> for UnrolledList<T, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T, const C: usize> FromIterator<T> for UnrolledList<T, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T, const C: usize> IntoIterator for &'a UnrolledList<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A front-to-back iterator over the elements of an [`UnrolledList`].
+///
+/// This `struct` is created by [`UnrolledList::iter`].
+pub struct Iter<'a, T, const C: usize> {
+    chunks: doubly_linked_list::Iter<'a, Chunk<T, C>>,
+    /// The rest of the chunk taken from the front of `chunks`.
+    front: slice::Iter<'a, T>,
+    /// The rest of the chunk taken from the back of `chunks`.
+    back: slice::Iter<'a, T>,
+    len: usize,
+}
+
+impl<'a, T, const C: usize> Iterator for Iter<'a, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = loop {
+            if let Some(value) = self.front.next() {
+                break Some(value);
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.front = chunk.as_slice().iter(),
+                None => break self.back.next(),
+            }
+        };
+        if value.is_some() {
+            self.len -= 1;
+        }
+        value
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const C: usize> DoubleEndedIterator for Iter<'_, T, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = loop {
+            if let Some(value) = self.back.next_back() {
+                break Some(value);
+            }
+            match self.chunks.next_back() {
+                Some(chunk) => self.back = chunk.as_slice().iter(),
+                None => break self.front.next_back(),
+            }
+        };
+        if value.is_some() {
+            self.len -= 1;
+        }
+        value
+    }
+}
+
+impl<T, const C: usize> ExactSizeIterator for Iter<'_, T, C> {}
+
+impl<T, const C: usize> Clone for Iter<'_, T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            front: self.front.clone(),
+            back: self.back.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: core::fmt::Debug, const C: usize> core::fmt::Debug for Iter<'_, T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// Up to `C` elements stored inline, in order.
+struct Chunk<T, const C: usize> {
+    /// The first `len` slots are initialised.
+    buf: [MaybeUninit<T>; C],
+    len: usize,
+}
+
+impl<T, const C: usize> Chunk<T, C> {
+    const UNINIT: MaybeUninit<T> = MaybeUninit::uninit();
+
+    fn new() -> Self {
+        Self {
+            buf: [Self::UNINIT; C],
+            len: 0,
+        }
+    }
+
+    /// Returns `true` if the chunk is less than half full.
+    fn is_sparse(&self) -> bool {
+        2 * self.len < C
+    }
+
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` slots are initialised, and `MaybeUninit<T>` has the same
+        // layout as `T`.
+        unsafe { slice::from_raw_parts(self.buf.as_ptr().cast(), self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: As in `as_slice`.
+        unsafe { slice::from_raw_parts_mut(self.buf.as_mut_ptr().cast(), self.len) }
+    }
+
+    /// Inserts `value` at `index`. The chunk must not be full.
+    fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len && self.len < C);
+        // SAFETY: The slots from `index` up to `len` are initialised and there is room for one
+        // more, so shifting them back by one stays within the buffer. The slot at `index` is
+        // overwritten without being dropped, since its value now lives one slot further back.
+        unsafe {
+            let slot = self.buf.as_mut_ptr().add(index);
+            ptr::copy(slot, slot.add(1), self.len - index);
+            slot.write(MaybeUninit::new(value));
+        }
+        self.len += 1;
+    }
+
+    /// Removes the value at `index` and returns it.
+    fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len);
+        self.len -= 1;
+        // SAFETY: The slot at `index` is initialised. Its value is moved out before the slots
+        // after it are shifted over it, and decrementing `len` first marks the last slot as
+        // vacant.
+        unsafe {
+            let slot = self.buf.as_mut_ptr().add(index);
+            let value = slot.read().assume_init();
+            ptr::copy(slot.add(1), slot, self.len - index);
+            value
+        }
+    }
+
+    /// Moves the values from `at` onwards into a new chunk.
+    fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len);
+        let mut other = Self::new();
+        other.len = self.len - at;
+        self.len = at;
+        // SAFETY: The `other.len` slots from `at` were initialised, and are marked as vacant in
+        // this chunk now that `len` is `at`. The buffers of two chunks never overlap.
+        unsafe {
+            ptr::copy_nonoverlapping(self.buf.as_ptr().add(at), other.buf.as_mut_ptr(), other.len);
+        }
+        other
+    }
+
+    /// Moves every value of `other` to the end of this chunk. Both must fit into one chunk.
+    fn append(&mut self, other: &mut Self) {
+        assert!(self.len + other.len <= C);
+        // SAFETY: The first `other.len` slots of `other` are initialised, there is room for them
+        // after the `len` initialised slots of this chunk, and the buffers never overlap. Setting
+        // `other.len` to zero marks the moved slots as vacant.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                other.buf.as_ptr(),
+                self.buf.as_mut_ptr().add(self.len),
+                other.len,
+            );
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+}
+
+impl<T, const C: usize> Drop for Chunk<T, C> {
+    fn drop(&mut self) {
+        // SAFETY: The slice covers exactly the initialised slots, which are never touched again.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T: Clone, const C: usize> Clone for Chunk<T, C> {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new();
+        for value in self.as_slice() {
+            clone.insert(clone.len, value.clone());
+        }
+        clone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{format, rc::Rc, vec::Vec};
+
+    fn check<const C: usize>(list: &UnrolledList<u64, C>, reference: &[u64]) {
+        assert_eq!(list.len(), reference.len());
+        assert!(list.iter().eq(reference));
+        assert!(list.iter().rev().eq(reference.iter().rev()));
+        assert_eq!(list.iter().len(), reference.len());
+
+        let lens: Vec<usize> = list.chunks.iter().map(|chunk| chunk.len).collect();
+        assert!(lens.iter().all(|&len| len > 0 && len <= C));
+        assert_eq!(lens.iter().sum::<usize>(), reference.len());
+        assert!(lens
+            .windows(2)
+            .all(|pair| 2 * pair[0] >= C || 2 * pair[1] >= C));
+    }
+
+    fn randomized<const C: usize>(seed: u64, steps: usize) {
+        let mut rng = Rng::new(seed);
+        let mut list: UnrolledList<u64, C> = UnrolledList::new();
+        let mut reference = Vec::new();
+
+        for step in 0..steps {
+            let value = rng.next_u64();
+            // Grow for the first half and shrink for the second, so that chunks are both split
+            // and merged a lot.
+            let grow = rng.below(10) < if step < steps / 2 { 6 } else { 4 };
+            if grow {
+                if rng.below(4) == 0 {
+                    list.push_back(value);
+                    reference.push(value);
+                } else {
+                    let index = rng.below(reference.len() as u64 + 1) as usize;
+                    list.insert_at(index, value);
+                    reference.insert(index, value);
+                }
+            } else if !reference.is_empty() {
+                let index = rng.below(reference.len() as u64) as usize;
+                assert_eq!(list.remove_at(index), reference.remove(index));
+            }
+
+            let index = rng.below(reference.len() as u64 + 1) as usize;
+            if let Some(value) = list.get_mut(index) {
+                *value ^= 1;
+                reference[index] ^= 1;
+            }
+            assert_eq!(list.get(index), reference.get(index));
+            check(&list, &reference);
+        }
+    }
+
+    #[test]
+    fn randomized_against_vec() {
+        randomized::<1>(448, 1_000);
+        randomized::<2>(4480, 1_000);
+        randomized::<3>(4481, 1_000);
+        randomized::<4>(4482, 2_000);
+        randomized::<7>(4483, 2_000);
+        randomized::<32>(4484, 4_000);
+    }
+
+    #[test]
+    fn splits_full_chunks_evenly() {
+        let mut list: UnrolledList<u64, 4> = (0..4).collect();
+        list.insert_at(0, 10);
+        let lens: Vec<usize> = list.chunks.iter().map(|chunk| chunk.len).collect();
+        assert_eq!(lens, [2, 3]);
+
+        let mut list: UnrolledList<u64, 5> = (0..5).collect();
+        list.insert_at(5, 10);
+        let lens: Vec<usize> = list.chunks.iter().map(|chunk| chunk.len).collect();
+        assert_eq!(lens, [3, 3]);
+        check(&list, &[0, 1, 2, 3, 4, 10]);
+    }
+
+    #[test]
+    fn iterates_from_both_ends() {
+        let list: UnrolledList<u64, 3> = (0..10).collect();
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&9));
+        assert_eq!(iter.len(), 8);
+        assert_eq!(format!("{:?}", iter), "[1, 2, 3, 4, 5, 6, 7, 8]");
+        assert!(iter.rev().eq(&[8, 7, 6, 5, 4, 3, 2, 1]));
+        assert_eq!(list, list.clone());
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let counter = Rc::new(());
+        {
+            let mut list: UnrolledList<Rc<()>, 4> = UnrolledList::new();
+            for index in 0..20 {
+                list.insert_at(index / 2, Rc::clone(&counter));
+            }
+            for index in (0..10).rev() {
+                drop(list.remove_at(index));
+            }
+            let clone = list.clone();
+            assert_eq!(Rc::strong_count(&counter), 21);
+            drop(clone);
+            assert_eq!(Rc::strong_count(&counter), 11);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds in UnrolledList")]
+    fn insert_out_of_bounds() {
+        let mut list: UnrolledList<u64> = UnrolledList::new();
+        list.insert_at(1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds in UnrolledList")]
+    fn remove_out_of_bounds() {
+        let mut list: UnrolledList<u64> = (0..3).collect();
+        list.remove_at(3);
+    }
+}
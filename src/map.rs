@@ -0,0 +1,291 @@
+//! # Maps
+//!
+//! [`Map`](crate::Map) is the interface shared by the crate's map-like data-structures, so that
+//! code can be written once and run against whichever of them suits the workload. It is
+//! implemented by [`BinaryTree`](crate::tree::binary_tree::BinaryTree),
+//! [`RedBlackTree`](crate::tree::red_black_tree::RedBlackTree),
+//! [`BTree`](crate::tree::b_tree::BTree), [`Treap`](crate::tree::treap::Treap),
+//! [`SplayTree`](crate::tree::splay_tree::SplayTree),
+//! [`SortedMap`](crate::vec::sorted_map::SortedMap) and
+//! [`FlatHashMap`](crate::hash::flat_hash_map::FlatHashMap). Since the trait looks keys up
+//! through `&self`, [`SplayTree`](crate::tree::splay_tree::SplayTree) answers
+//! [`Map::get`](crate::Map::get) with `peek`, which does not splay.
+//!
+//! The remaining map-like types cannot offer the same signatures, and are left out:
+//!
+//! - [`Trie`](crate::tree::trie::Trie) is keyed by `&str` paths rather than by an owned key type.
+//! - [`RadixTree`](crate::tree::radix_tree::RadixTree) takes its integer keys by value.
+//! - [`LruCache`](crate::cache::lru_cache::LruCache) looks keys up through `&mut self` to record
+//!   the access, and evicts entries on insertion.
+//! - [`SlotMap`](crate::vec::slot_map::SlotMap) hands out its own keys rather than accepting
+//!   them.
+//! - [`RangeMap`](crate::tree::range_map::RangeMap) inserts ranges of keys but looks up single
+//!   points.
+//! - [`Counter`](crate::tree::counter::Counter) adjusts counts rather than storing arbitrary
+//!   values.
+//!
+//! # Examples
+//!
+//! ```
+//! use collect_me::{hash::flat_hash_map::FlatHashMap, tree::binary_tree::BinaryTree, Map};
+//!
+//! fn word_lengths<M: Map<String, usize>>(map: &mut M, text: &str) {
+//!     for word in text.split_whitespace() {
+//!         if !map.contains_key(word) {
+//!             map.insert(word.to_string(), word.len());
+//!         }
+//!     }
+//! }
+//!
+//! let mut tree = BinaryTree::new();
+//! let mut hashed: FlatHashMap<String, usize> = FlatHashMap::new();
+//! word_lengths(&mut tree, "the quick brown fox");
+//! word_lengths(&mut hashed, "the quick brown fox");
+//!
+//! assert_eq!(tree.len(), 4);
+//! assert_eq!(Map::get(&hashed, "quick"), Some(&5));
+//! ```
+
+use crate::{
+    hash::flat_hash_map::FlatHashMap,
+    tree::{
+        b_tree::BTree, binary_tree::BinaryTree, red_black_tree::RedBlackTree,
+        splay_tree::SplayTree, treap::Treap,
+    },
+    vec::sorted_map::SortedMap,
+};
+use core::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+};
+
+/// A collection of values looked up by unique keys.
+///
+/// Inserting a key that is already present replaces its value and keeps the original key, like
+/// the maps of the standard library do.
+///
+/// # Borrowed keys
+///
+/// Lookups accept any borrowed form `Q` of the key that is both [`Ord`] and [`Hash`]. Each
+/// implementation only relies on one of the two, but a generic caller cannot know which, so the
+/// trait has to ask for both; [`Ord`] alone would shut the hashed maps out, and [`Hash`] alone the
+/// ordered ones. The ordered maps never hash their keys, so the bound costs nothing at runtime,
+/// and keys that cannot be hashed can still be used through the inherent methods of each map.
+pub trait Map<K, V> {
+    /// Returns the number of entries in the map.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the map has no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized;
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized;
+
+    /// Returns `true` if the map contains the key.
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a key-value pair into the map. If the key was already present, its value is
+    /// replaced and the old value returned.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    /// Removes a key from the map, returning its value if it was present.
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized;
+
+    /// Builds a map holding `entries` through [`Extend`], for generic code that cannot name
+    /// [`FromIterator`] for the map it is given. Later entries replace earlier ones with the same
+    /// key.
+    fn from_entries<I>(entries: I) -> Self
+    where
+        Self: Sized + Default + Extend<(K, V)>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        map.extend(entries);
+        map
+    }
+}
+
+/// Implements [`Map`] for ordered maps by forwarding to their inherent methods.
+macro_rules! ordered_maps {
+    ($(impl[$($generics:tt)*] $map:ty;)*) => {
+        $(
+            impl<$($generics)*> Map<K, V> for $map {
+                fn len(&self) -> usize {
+                    self.len()
+                }
+
+                fn get<Q>(&self, key: &Q) -> Option<&V>
+                where
+                    K: Borrow<Q>,
+                    Q: Ord + Hash + ?Sized,
+                {
+                    self.get(key)
+                }
+
+                fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+                where
+                    K: Borrow<Q>,
+                    Q: Ord + Hash + ?Sized,
+                {
+                    self.get_mut(key)
+                }
+
+                fn insert(&mut self, key: K, value: V) -> Option<V> {
+                    self.insert(key, value)
+                }
+
+                fn remove<Q>(&mut self, key: &Q) -> Option<V>
+                where
+                    K: Borrow<Q>,
+                    Q: Ord + Hash + ?Sized,
+                {
+                    self.remove(key)
+                }
+            }
+        )*
+    };
+}
+
+ordered_maps! {
+    impl[K: Ord, V] BinaryTree<K, V>;
+    impl[K: Ord, V] RedBlackTree<K, V>;
+    impl[K: Ord, V, const B: usize] BTree<K, V, B>;
+    impl[K: Ord, V] Treap<K, V>;
+    impl[K: Ord, V] SortedMap<K, V>;
+}
+
+impl<K: Ord, V> Map<K, V> for SplayTree<K, V> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.peek(key)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.get_mut(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.remove(key)
+    }
+}
+
+impl<K, V, S> Map<K, V> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.get(key)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.get_mut(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.remove(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use alloc::string::String;
+
+    fn borrowed_keys<M: Map<String, u32> + Default + Extend<(String, u32)>>() {
+        let entries = [("b", 1), ("a", 2), ("b", 3)];
+        let mut map = M::from_entries(entries.iter().map(|&(key, value)| (key.into(), value)));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("b"), Some(&3));
+        assert!(map.contains_key("a"));
+        assert_eq!(map.get("c"), None);
+
+        *map.get_mut("a").expect("Key should be present") += 10;
+        assert_eq!(map.remove("a"), Some(12));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    macro_rules! conformance_tests {
+        ($($name:ident: $map:ident $(+ $iteration:ident)?;)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    testing::check_map($map::<_, _>::new);
+                    $(testing::$iteration($map::<_, _>::new);)?
+                    borrowed_keys::<$map<String, u32>>();
+                }
+            )*
+        };
+    }
+
+    conformance_tests! {
+        binary_tree: BinaryTree + check_iteration;
+        red_black_tree: RedBlackTree;
+        b_tree: BTree + check_iteration;
+        treap: Treap + check_iteration;
+        splay_tree: SplayTree + check_iteration;
+        sorted_map: SortedMap;
+        flat_hash_map: FlatHashMap + check_iteration;
+    }
+}
@@ -0,0 +1,126 @@
+//! # Monoids
+//!
+//! Aggregating collections such as [`FenwickTree`](crate::tree::fenwick_tree::FenwickTree) and
+//! [`SegmentTree`](crate::tree::segment_tree::SegmentTree) combine their elements with an
+//! associative operation that has an identity element, also known as a monoid. The operation is
+//! picked with a marker type implementing [`Monoid`](crate::monoid::Monoid), so that the same
+//! element type can be summed in one collection and minimised in another.
+//!
+//! [`Sum`](crate::monoid::Sum), [`Min`](crate::monoid::Min) and [`Max`](crate::monoid::Max) are
+//! provided for every primitive integer and floating-point type. [`Sum`](crate::monoid::Sum) also
+//! implements [`Group`](crate::monoid::Group), since addition can be undone by subtraction.
+//! Integer sums overflow like ordinary `+`.
+//!
+//! # Examples
+//!
+//! ```
+//! use collect_me::monoid::{Max, Monoid, Sum};
+//!
+//! let values = [3, 1, 4, 1, 5];
+//! let total = values.iter().fold(Sum::identity(), |a, b| Sum::combine(&a, b));
+//! let largest = values.iter().fold(Max::identity(), |a, b| Max::combine(&a, b));
+//!
+//! assert_eq!(total, 14);
+//! assert_eq!(largest, 5);
+//! ```
+
+/// An associative operation on `T` with an identity element.
+///
+/// Implementations must satisfy, for all `a`, `b` and `c`:
+///
+/// - `combine(&identity(), &a) == a` and `combine(&a, &identity()) == a`
+/// - `combine(&combine(&a, &b), &c) == combine(&a, &combine(&b, &c))`
+pub trait Monoid<T> {
+    /// Returns the identity element.
+    fn identity() -> T;
+
+    /// Combines two values.
+    fn combine(a: &T, b: &T) -> T;
+}
+
+/// A [`Monoid`] whose operation can be undone.
+///
+/// Implementations must satisfy `combine(&b, &difference(&a, &b)) == a` for all `a` and `b`.
+pub trait Group<T>: Monoid<T> {
+    /// Returns the value that turns `b` into `a` when combined with it.
+    fn difference(a: &T, b: &T) -> T;
+}
+
+/// Combines values by adding them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sum;
+
+/// Combines values by keeping the smaller one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Min;
+
+/// Combines values by keeping the larger one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Max;
+
+macro_rules! primitive_monoids {
+    ($($ty:ty: $zero:expr, $lowest:expr, $highest:expr;)*) => {
+        $(
+            impl Monoid<$ty> for Sum {
+                fn identity() -> $ty {
+                    $zero
+                }
+
+                fn combine(a: &$ty, b: &$ty) -> $ty {
+                    *a + *b
+                }
+            }
+
+            impl Group<$ty> for Sum {
+                fn difference(a: &$ty, b: &$ty) -> $ty {
+                    *a - *b
+                }
+            }
+
+            impl Monoid<$ty> for Min {
+                fn identity() -> $ty {
+                    $highest
+                }
+
+                fn combine(a: &$ty, b: &$ty) -> $ty {
+                    if *b < *a {
+                        *b
+                    } else {
+                        *a
+                    }
+                }
+            }
+
+            impl Monoid<$ty> for Max {
+                fn identity() -> $ty {
+                    $lowest
+                }
+
+                fn combine(a: &$ty, b: &$ty) -> $ty {
+                    if *b > *a {
+                        *b
+                    } else {
+                        *a
+                    }
+                }
+            }
+        )*
+    };
+}
+
+primitive_monoids! {
+    u8: 0, u8::MIN, u8::MAX;
+    u16: 0, u16::MIN, u16::MAX;
+    u32: 0, u32::MIN, u32::MAX;
+    u64: 0, u64::MIN, u64::MAX;
+    u128: 0, u128::MIN, u128::MAX;
+    usize: 0, usize::MIN, usize::MAX;
+    i8: 0, i8::MIN, i8::MAX;
+    i16: 0, i16::MIN, i16::MAX;
+    i32: 0, i32::MIN, i32::MAX;
+    i64: 0, i64::MIN, i64::MAX;
+    i128: 0, i128::MIN, i128::MAX;
+    isize: 0, isize::MIN, isize::MAX;
+    f32: 0.0, f32::NEG_INFINITY, f32::INFINITY;
+    f64: 0.0, f64::NEG_INFINITY, f64::INFINITY;
+}
@@ -0,0 +1,203 @@
+//! # Float keys
+//!
+//! [`f32`] and [`f64`] only implement [`PartialOrd`], since `NaN` is not comparable to anything
+//! (including itself). Storing such keys in an ordered collection silently breaks its invariants,
+//! so this module provides [`TotalF32`](crate::ord::TotalF32) and
+//! [`TotalF64`](crate::ord::TotalF64), thin wrappers that implement [`Ord`], [`Eq`] and
+//! [`Hash`](core::hash::Hash) with the following rules:
+//!
+//! - All `NaN`s (regardless of sign or payload) are equal to each other and greater than every
+//!   other value, including positive infinity.
+//! - `-0.0` and `0.0` are equal.
+//! - Every other value is ordered as usual.
+//!
+//! Hashing is consistent with equality: `NaN`s and zeroes are canonicalised before their bit
+//! pattern is hashed.
+//!
+//! # Examples
+//!
+//! ```
+//! use collect_me::ord::TotalF64;
+//! use collect_me::tree::binary_tree::BinaryTree;
+//!
+//! let mut tree = BinaryTree::new();
+//! tree.insert(TotalF64(f64::NAN), "not a number");
+//! tree.insert(TotalF64(-0.0), "zero");
+//!
+//! assert_eq!(tree.get(&TotalF64(f64::NAN)), Some(&"not a number"));
+//! assert_eq!(tree.get(&TotalF64(0.0)), Some(&"zero"));
+//! ```
+
+macro_rules! total_float {
+    ($name:ident, $float:ty, $signed:ty, $unsigned:ty) => {
+        #[doc = concat!("A totally ordered [`", stringify!($float), "`].")]
+        ///
+        /// See the [module-level documentation](self) for the exact ordering rules.
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name(pub $float);
+
+        impl $name {
+            /// Maps every `NaN` onto a single `NaN` and `-0.0` onto `0.0`.
+            fn canonical(self) -> $float {
+                if self.0.is_nan() {
+                    <$float>::NAN
+                } else if self.0 == 0.0 {
+                    0.0
+                } else {
+                    self.0
+                }
+            }
+
+            /// Maps the canonical value onto an integer with the same order. Reinterpreted as
+            /// signed integers, the bit patterns of positive floats already sort correctly, and
+            /// those of negative floats sort in reverse, so every bit but the sign is flipped for
+            /// them.
+            fn key(self) -> $signed {
+                let bits = self.canonical().to_bits() as $signed;
+                bits ^ (((bits >> (<$signed>::BITS - 1)) as $unsigned) >> 1) as $signed
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == core::cmp::Ordering::Equal
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                self.key().cmp(&other.key())
+            }
+        }
+
+        impl core::hash::Hash for $name {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                self.canonical().to_bits().hash(state);
+            }
+        }
+
+        impl From<$float> for $name {
+            fn from(value: $float) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $float {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+total_float!(TotalF32, f32, i32, u32);
+total_float!(TotalF64, f64, i64, u64);
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::tree::binary_tree::BinaryTree;
+    use alloc::{format, vec::Vec};
+    use core::hash::{Hash, Hasher};
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: Hash>(value: T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn nan_ordering() {
+        let negative_nan = TotalF64(-f64::NAN);
+        let payload_nan = TotalF64(f64::from_bits(f64::NAN.to_bits() | 1));
+
+        assert_eq!(TotalF64(f64::NAN), negative_nan);
+        assert_eq!(TotalF64(f64::NAN), payload_nan);
+        assert!(TotalF64(f64::NAN) > TotalF64(f64::INFINITY));
+        assert!(negative_nan > TotalF64(f64::NEG_INFINITY));
+        assert_eq!(hash(negative_nan), hash(payload_nan));
+
+        let mut values: Vec<TotalF32> = [f32::NAN, 1.0, -f32::NAN, f32::INFINITY, -3.5]
+            .into_iter()
+            .map(TotalF32)
+            .collect();
+        values.sort();
+
+        let values: Vec<f32> = values.into_iter().map(f32::from).collect();
+        assert_eq!(&values[..3], &[-3.5, 1.0, f32::INFINITY]);
+        assert!(values[3].is_nan() && values[4].is_nan());
+    }
+
+    #[test]
+    fn matches_partial_order() {
+        let values = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1.5,
+            -f64::MIN_POSITIVE,
+            -f64::from_bits(1),
+            0.0,
+            f64::from_bits(1),
+            f64::MIN_POSITIVE,
+            1.0,
+            f64::MAX,
+            f64::INFINITY,
+        ];
+        for a in values {
+            for b in values {
+                assert_eq!(TotalF64(a).partial_cmp(&TotalF64(b)), a.partial_cmp(&b));
+                let (a, b) = (a as f32, b as f32);
+                assert_eq!(TotalF32(a).partial_cmp(&TotalF32(b)), a.partial_cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn signed_zero() {
+        assert_eq!(TotalF64(0.0), TotalF64(-0.0));
+        assert_eq!(TotalF32(0.0), TotalF32(-0.0));
+        assert_eq!(hash(TotalF64(0.0)), hash(TotalF64(-0.0)));
+        assert!(TotalF64(-0.0) > TotalF64(-f64::MIN_POSITIVE));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", TotalF64(1.5)), "1.5");
+        assert_eq!(format!("{:?}", TotalF32(-2.0)), "TotalF32(-2.0)");
+    }
+
+    #[test]
+    fn tree_keys() {
+        let mut tree = BinaryTree::new();
+        tree.insert(TotalF64(1.0), 'A');
+        tree.insert(TotalF64(f64::NAN), 'B');
+        tree.insert(TotalF64(-1.0), 'C');
+        tree.insert(TotalF64(0.0), 'D');
+
+        assert_eq!(tree.insert(TotalF64(-f64::NAN), 'E'), Some('B'));
+        assert_eq!(tree.insert(TotalF64(-0.0), 'F'), Some('D'));
+        assert_eq!(tree.len(), 4);
+
+        assert_eq!(tree.get(&TotalF64(f64::NAN)), Some(&'E'));
+        assert_eq!(tree.get(&TotalF64(0.0)), Some(&'F'));
+        assert_eq!(tree.get(&TotalF64(1.0)), Some(&'A'));
+        assert_eq!(tree.get(&TotalF64(-1.0)), Some(&'C'));
+    }
+}
@@ -0,0 +1,2 @@
+/// Disjoint sets with union-find
+pub mod union_find;
@@ -0,0 +1,370 @@
+use crate::tree::binary_tree::BinaryTree;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+/// A partition of the elements `0..n` into disjoint sets, supporting near-constant time unions
+/// and membership queries.
+///
+/// Every set is a tree of parent links whose root is the representative of the set. Lookups
+/// compress the path they walk so that every visited element points straight at the root, and
+/// unions hang the shallower tree below the deeper one. Together these keep the amortized cost of
+/// every operation at the inverse Ackermann function of `n`, which is below five for any
+/// practical input.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::set::union_find::UnionFind;
+///
+/// let mut groups = UnionFind::new(5);
+/// assert!(groups.union(0, 1));
+/// assert!(groups.union(3, 4));
+/// assert!(!groups.union(1, 0));
+///
+/// assert!(groups.same_set(0, 1));
+/// assert!(!groups.same_set(1, 3));
+/// assert_eq!(groups.set_count(), 3);
+/// assert_eq!(groups.set_size(4), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    /// An upper bound on the height of the tree below each root.
+    ranks: Vec<u8>,
+    /// The number of elements in the set of each root.
+    sizes: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Constructs a partition of `0..len` into singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            ranks: alloc::vec![0; len],
+            sizes: alloc::vec![1; len],
+            sets: len,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Removes every element, leaving an empty partition.
+    pub fn clear(&mut self) {
+        self.parents.clear();
+        self.ranks.clear();
+        self.sizes.clear();
+        self.sets = 0;
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Adds a new element in a set of its own and returns it.
+    pub fn make_set(&mut self) -> usize {
+        let element = self.parents.len();
+        self.parents.push(element);
+        self.ranks.push(0);
+        self.sizes.push(1);
+        self.sets += 1;
+        element
+    }
+
+    /// Returns the representative of the set containing `element`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` is out of bounds.
+    pub fn find(&mut self, element: usize) -> usize {
+        assert!(element < self.len(), "Element out of bounds in UnionFind");
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut current = element;
+        while current != root {
+            current = core::mem::replace(&mut self.parents[current], root);
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns `false` if they already were the same set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is out of bounds.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.ranks[a] < self.ranks[b] {
+            core::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        if self.ranks[a] == self.ranks[b] {
+            self.ranks[a] += 1;
+        }
+        self.sets -= 1;
+        true
+    }
+
+    /// Returns `true` if `a` and `b` are in the same set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is out of bounds.
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Returns the number of elements in the set containing `element`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` is out of bounds.
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+}
+
+/// A [`UnionFind`] over arbitrary ordered keys instead of indices.
+///
+/// Keys are mapped to indices with a [`BinaryTree`], which adds `O(log(n))` to every operation.
+/// They are stored both in the tree and in a vector to look representatives up again, so they
+/// need to be [`Clone`].
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::set::union_find::UnionFindMap;
+///
+/// let mut friends = UnionFindMap::new();
+/// friends.union("alice", "bob");
+/// friends.union("carol", "dave");
+/// friends.union("bob", "erin");
+///
+/// assert!(friends.same_set("alice", "erin"));
+/// assert!(!friends.same_set("alice", "carol"));
+/// assert_eq!(friends.set_size("erin"), Some(3));
+/// assert_eq!(friends.set_count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnionFindMap<K> {
+    indices: BinaryTree<K, usize>,
+    keys: Vec<K>,
+    sets: UnionFind,
+}
+
+impl<K> UnionFindMap<K> {
+    /// Constructs an empty partition.
+    pub fn new() -> Self {
+        Self {
+            indices: BinaryTree::new(),
+            keys: Vec::new(),
+            sets: UnionFind::new(0),
+        }
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if there are no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes every key, leaving an empty partition.
+    pub fn clear(&mut self) {
+        self.indices.clear();
+        self.keys.clear();
+        self.sets.clear();
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn set_count(&self) -> usize {
+        self.sets.set_count()
+    }
+}
+
+impl<K> UnionFindMap<K>
+where
+    K: Ord + Clone,
+{
+    /// Adds `key` in a set of its own. Returns `false` if it was already present, in which case
+    /// its set is left alone.
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.indices.get(&key).is_some() {
+            return false;
+        }
+        let index = self.sets.make_set();
+        self.indices.insert(key.clone(), index);
+        self.keys.push(key);
+        true
+    }
+
+    /// Returns `true` if `key` is present.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.indices.get(key).is_some()
+    }
+
+    /// Returns the representative of the set containing `key`, or [`None`] if it is not present.
+    pub fn find<Q>(&mut self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = *self.indices.get(key)?;
+        let root = self.sets.find(index);
+        Some(&self.keys[root])
+    }
+
+    /// Merges the sets containing `a` and `b`, adding either key first if it is not present.
+    /// Returns `false` if they already were the same set.
+    pub fn union(&mut self, a: K, b: K) -> bool {
+        let a = self.index_or_insert(a);
+        let b = self.index_or_insert(b);
+        self.sets.union(a, b)
+    }
+
+    /// Returns `true` if `a` and `b` are both present and in the same set.
+    pub fn same_set<Q>(&mut self, a: &Q, b: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match (self.indices.get(a), self.indices.get(b)) {
+            (Some(&a), Some(&b)) => self.sets.same_set(a, b),
+            _ => false,
+        }
+    }
+
+    /// Returns the number of keys in the set containing `key`, or [`None`] if it is not present.
+    pub fn set_size<Q>(&mut self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = *self.indices.get(key)?;
+        Some(self.sets.set_size(index))
+    }
+
+    fn index_or_insert(&mut self, key: K) -> usize {
+        match self.indices.get(&key) {
+            Some(&index) => index,
+            None => {
+                let index = self.sets.make_set();
+                self.indices.insert(key.clone(), index);
+                self.keys.push(key);
+                index
+            }
+        }
+    }
+}
+
+impl<K> Default for UnionFindMap<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::string::String;
+
+    #[test]
+    fn randomized_against_labels() {
+        let mut rng = Rng::new(446);
+        let mut sets = UnionFind::new(50);
+        // Every element is labelled with its set, and a union relabels one side entirely.
+        let mut labels: Vec<usize> = (0..50).collect();
+
+        for _ in 0..2_000 {
+            if rng.below(20) == 0 {
+                assert_eq!(sets.make_set(), labels.len());
+                labels.push(labels.len());
+            }
+
+            let len = labels.len() as u64;
+            let (a, b) = (rng.below(len) as usize, rng.below(len) as usize);
+            let (label_a, label_b) = (labels[a], labels[b]);
+            assert_eq!(sets.union(a, b), label_a != label_b);
+            for label in &mut labels {
+                if *label == label_b {
+                    *label = label_a;
+                }
+            }
+
+            let mut distinct = labels.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(sets.set_count(), distinct.len());
+
+            let (c, d) = (rng.below(len) as usize, rng.below(len) as usize);
+            assert_eq!(sets.same_set(c, d), labels[c] == labels[d]);
+            let expected = labels.iter().filter(|label| **label == labels[c]).count();
+            assert_eq!(sets.set_size(c), expected);
+        }
+    }
+
+    #[test]
+    fn find_compresses_paths() {
+        let mut sets = UnionFind::new(8);
+        for element in 1..8 {
+            sets.union(0, element);
+        }
+        let root = sets.find(7);
+        assert!((0..8).all(|element| sets.parents[element] == root));
+        assert_eq!(sets.set_size(3), 8);
+        assert_eq!(sets.set_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Element out of bounds in UnionFind")]
+    fn find_out_of_bounds() {
+        let mut sets = UnionFind::new(3);
+        sets.find(3);
+    }
+
+    #[test]
+    fn keyed() {
+        let mut sets: UnionFindMap<String> = UnionFindMap::new();
+        assert!(sets.insert("a".into()));
+        assert!(!sets.insert("a".into()));
+        assert_eq!(sets.find("a").map(String::as_str), Some("a"));
+        assert_eq!(sets.find("b"), None);
+
+        assert!(sets.union("a".into(), "b".into()));
+        assert!(sets.union("c".into(), "b".into()));
+        assert!(!sets.union("a".into(), "c".into()));
+        assert_eq!(sets.len(), 3);
+        assert_eq!(sets.set_count(), 1);
+
+        let root = sets.find("c").cloned();
+        assert_eq!(sets.find("a").cloned(), root);
+        assert!(!sets.same_set("a", "missing"));
+        assert_eq!(sets.set_size("missing"), None);
+        assert!(sets.contains("b"));
+    }
+}
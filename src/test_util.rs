@@ -0,0 +1,42 @@
+//! Helpers shared by the crate's unit tests.
+
+#[cfg(test)]
+use crate::Collection;
+
+/// A tiny xorshift pseudo-random number generator for deterministic randomized tests.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..bound`.
+    pub(crate) fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/// Asserts that `collection` reports being empty through both accessors of [`Collection`].
+#[cfg(test)]
+pub(crate) fn assert_empty<C: Collection>(collection: &C) {
+    assert_eq!(collection.len(), 0);
+    assert!(collection.is_empty());
+}
+
+/// Asserts that `collection` holds elements, then that clearing it leaves it empty.
+#[cfg(test)]
+pub(crate) fn assert_clears<C: Collection>(collection: &mut C) {
+    assert_ne!(collection.len(), 0);
+    assert!(!collection.is_empty());
+    collection.clear();
+    assert_empty(collection);
+}
@@ -0,0 +1,394 @@
+//! # Testing
+//!
+//! A conformance suite for implementations of [`Map`](crate::Map), which holds the maps of this
+//! crate and downstream ones to the same behaviour. Each check builds fresh maps with the factory
+//! closure it is given, runs a sequence of operations against them and against a
+//! [`BTreeMap`](alloc::collections::BTreeMap) oracle, and panics at the first difference.
+//!
+//! The maps are keyed by [`Key`](crate::testing::Key) and hold [`Value`](crate::testing::Value)s.
+//! Keys borrow as `u64`, so every lookup also exercises the borrowed form of the key, and can be
+//! rigged to panic part-way through an operation. Values record when they are dropped, so that
+//! entries which are leaked or dropped twice are caught.
+//!
+//! The module is compiled for the crate's own tests, and for everyone else behind the `testing`
+//! feature. It links `std`, which it needs to catch the panics it provokes.
+//!
+//! # Examples
+//!
+//! Checking a new map takes a single test:
+//!
+//! ```
+//! use collect_me::{testing, tree::binary_tree::BinaryTree};
+//!
+//! testing::check_map(BinaryTree::new);
+//! testing::check_iteration(BinaryTree::new);
+//! ```
+
+extern crate std;
+
+use crate::{test_util::Rng, Map};
+use alloc::{collections::BTreeMap, collections::BTreeSet, rc::Rc, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// A key that compares and hashes exactly like the `u64` it wraps.
+///
+/// The suite also builds keys that panic after a set number of comparisons and hashes, to check
+/// that a map survives a panic in the middle of an operation.
+#[derive(Debug, Clone)]
+pub struct Key {
+    id: u64,
+    /// The number of comparisons and hashes left before the key panics.
+    fuse: Option<Rc<Cell<usize>>>,
+}
+
+impl Key {
+    /// Constructs a key that never panics.
+    pub fn new(id: u64) -> Self {
+        Self { id, fuse: None }
+    }
+
+    /// Returns the number the key wraps.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn burn(&self) {
+        if let Some(fuse) = &self.fuse {
+            let left = fuse.get();
+            assert!(left > 0, "Key {} panicked on purpose", self.id);
+            fuse.set(left - 1);
+        }
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.burn();
+        other.burn();
+        self.id.cmp(&other.id)
+    }
+}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.burn();
+        self.id.hash(state);
+    }
+}
+
+impl core::borrow::Borrow<u64> for Key {
+    fn borrow(&self) -> &u64 {
+        &self.id
+    }
+}
+
+/// A value that reports to the check that created it when it is dropped.
+#[derive(Debug)]
+pub struct Value {
+    id: u64,
+    serial: usize,
+    live: Rc<RefCell<BTreeSet<usize>>>,
+}
+
+impl Value {
+    /// Returns the number the value was created with.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        let dropped = self.live.borrow_mut().remove(&self.serial);
+        // Panicking while the map unwinds from another panic would abort the whole test run.
+        if !std::thread::panicking() {
+            assert!(dropped, "Value {} was dropped twice", self.id);
+        }
+    }
+}
+
+/// Hands out [`Value`]s and keeps track of which of them are still alive.
+#[derive(Debug, Default)]
+struct Tracker {
+    live: Rc<RefCell<BTreeSet<usize>>>,
+    created: Cell<usize>,
+}
+
+impl Tracker {
+    fn value(&self, id: u64) -> Value {
+        let serial = self.created.get();
+        self.created.set(serial + 1);
+        self.live.borrow_mut().insert(serial);
+        Value {
+            id,
+            serial,
+            live: Rc::clone(&self.live),
+        }
+    }
+
+    fn live(&self) -> usize {
+        self.live.borrow().len()
+    }
+}
+
+/// Asserts that `map` holds exactly the entries of `oracle`, none of them more than once.
+fn assert_matches<M: Map<Key, Value>>(map: &M, oracle: &BTreeMap<u64, u64>, tracker: &Tracker) {
+    assert_eq!(map.len(), oracle.len());
+    assert_eq!(map.is_empty(), oracle.is_empty());
+    for (key, value) in oracle {
+        assert_eq!(map.get(key).map(Value::id), Some(*value), "key {}", key);
+        assert!(map.contains_key(key));
+    }
+    let end = oracle.keys().next_back().map_or(0, |last| last + 1);
+    for absent in (0..=end).filter(|key| !oracle.contains_key(key)) {
+        assert!(map.get(&absent).is_none(), "key {}", absent);
+    }
+    assert_eq!(tracker.live(), map.len(), "the map leaked or lost values");
+}
+
+/// Runs every check that only needs the [`Map`] trait against the maps built by `new`.
+pub fn check_map<M, F>(mut new: F)
+where
+    M: Map<Key, Value>,
+    F: FnMut() -> M,
+{
+    scripted(&mut new);
+    randomized(&mut new);
+    drops(&mut new);
+    panic_safety(&mut new);
+}
+
+/// Runs a fixed sequence of operations covering the edge cases of every method.
+pub fn scripted<M, F>(mut new: F)
+where
+    M: Map<Key, Value>,
+    F: FnMut() -> M,
+{
+    let tracker = Tracker::default();
+    let mut map = new();
+    assert!(map.is_empty());
+    assert_eq!(map.len(), 0);
+    assert!(map.get(&0).is_none());
+    assert!(map.get_mut(&0).is_none());
+    assert!(map.remove(&0).is_none());
+
+    for id in [5, 2, 8, 1, 9, 3] {
+        assert!(map.insert(Key::new(id), tracker.value(id * 10)).is_none());
+    }
+    assert_eq!(map.len(), 6);
+
+    // Replacing a value keeps a single entry and hands back the old value.
+    let old = map
+        .insert(Key::new(2), tracker.value(200))
+        .map(|old| old.id());
+    assert_eq!(old, Some(20));
+    assert_eq!(map.get(&2).map(Value::id), Some(200));
+
+    *map.get_mut(&8).expect("Key 8 should be present") = tracker.value(800);
+    assert!(map.contains_key(&8));
+    assert!(!map.contains_key(&4));
+
+    assert_eq!(map.remove(&5).map(|value| value.id()), Some(50));
+    assert!(map.remove(&5).is_none());
+    assert!(map.get(&5).is_none());
+
+    let oracle = BTreeMap::from([(1, 10), (2, 200), (3, 30), (8, 800), (9, 90)]);
+    assert_matches(&map, &oracle, &tracker);
+
+    drop(map);
+    assert_eq!(tracker.live(), 0, "dropping the map should drop its values");
+}
+
+/// Runs a long pseudo-random sequence of operations, comparing every result with the oracle.
+pub fn randomized<M, F>(mut new: F)
+where
+    M: Map<Key, Value>,
+    F: FnMut() -> M,
+{
+    let tracker = Tracker::default();
+    let mut rng = Rng::new(249);
+    let mut map = new();
+    let mut oracle = BTreeMap::new();
+
+    for _ in 0..2_000 {
+        let id = rng.below(200);
+        let value = rng.next_u64();
+        match rng.below(5) {
+            0 | 1 => {
+                let old = map.insert(Key::new(id), tracker.value(value));
+                assert_eq!(old.map(|old| old.id()), oracle.insert(id, value));
+            }
+            2 => {
+                let removed = map.remove(&id).map(|removed| removed.id());
+                assert_eq!(removed, oracle.remove(&id));
+            }
+            3 => match (map.get_mut(&id), oracle.get_mut(&id)) {
+                (Some(found), Some(expected)) => {
+                    *found = tracker.value(value);
+                    *expected = value;
+                }
+                (None, None) => {}
+                (found, _) => panic!("get_mut({}) returned {:?}", id, found),
+            },
+            _ => assert_eq!(map.get(&id).map(Value::id), oracle.get(&id).copied()),
+        }
+        assert_eq!(map.len(), oracle.len());
+        assert_eq!(map.contains_key(&id), oracle.contains_key(&id));
+        assert_eq!(tracker.live(), oracle.len());
+    }
+
+    assert_matches(&map, &oracle, &tracker);
+    drop(map);
+    assert_eq!(tracker.live(), 0, "dropping the map should drop its values");
+}
+
+/// Checks that every value is dropped exactly once, whether it is replaced, removed, or still in
+/// the map when the map is dropped.
+pub fn drops<M, F>(mut new: F)
+where
+    M: Map<Key, Value>,
+    F: FnMut() -> M,
+{
+    let tracker = Tracker::default();
+    drop(new());
+    assert_eq!(tracker.live(), 0);
+
+    let mut map = new();
+    for id in 0..100 {
+        map.insert(Key::new(id), tracker.value(id));
+    }
+    for id in 0..50 {
+        map.insert(Key::new(id), tracker.value(id + 100));
+    }
+    assert_eq!(tracker.live(), 100, "replaced values should be dropped");
+
+    for id in (0..100).step_by(3) {
+        map.remove(&id);
+    }
+    assert_eq!(
+        tracker.live(),
+        map.len(),
+        "removed values should be dropped"
+    );
+
+    drop(map);
+    assert_eq!(tracker.live(), 0, "dropping the map should drop its values");
+}
+
+/// Makes a key panic after each possible number of comparisons during an insertion, a lookup
+/// and a removal, and checks that the map is left holding every entry it had, is still usable,
+/// and drops its values correctly.
+///
+/// The key being inserted may or may not have made it into the map when the panic struck.
+pub fn panic_safety<M, F>(mut new: F)
+where
+    M: Map<Key, Value>,
+    F: FnMut() -> M,
+{
+    for after in 0..12_usize {
+        for operation in 0..3 {
+            let tracker = Tracker::default();
+            let mut map = new();
+            let mut oracle = BTreeMap::new();
+            for id in (0..128).step_by(2) {
+                map.insert(Key::new(id), tracker.value(id));
+                oracle.insert(id, id);
+            }
+
+            // Inserted keys are new, and removed keys are present, so both reach a leaf.
+            let id = 10 * after as u64 + (operation == 0) as u64;
+            let fuse = Rc::new(Cell::new(after));
+            let key = Key {
+                id,
+                fuse: Some(Rc::clone(&fuse)),
+            };
+            let outcome = catch_unwind(AssertUnwindSafe(|| match operation {
+                0 => {
+                    map.insert(key, tracker.value(id));
+                }
+                1 => {
+                    map.get(&key);
+                }
+                _ => {
+                    map.remove(&key);
+                }
+            }));
+            // Defuse the key in case the map kept it.
+            fuse.set(usize::MAX);
+
+            if outcome.is_ok() {
+                // The operation needed fewer comparisons than the fuse allowed.
+                match operation {
+                    0 => {
+                        oracle.insert(id, id);
+                    }
+                    2 => {
+                        oracle.remove(&id);
+                    }
+                    _ => {}
+                }
+            } else if operation == 0 && map.contains_key(&id) {
+                oracle.insert(id, id);
+            }
+            assert_matches(&map, &oracle, &tracker);
+
+            map.insert(Key::new(1_000), tracker.value(1_000));
+            assert_eq!(map.remove(&1_000).map(|value| value.id()), Some(1_000));
+            drop(map);
+            assert_eq!(tracker.live(), 0, "dropping the map should drop its values");
+        }
+    }
+}
+
+/// Checks that iterating over the maps built by `new` yields every entry exactly once, in any
+/// order.
+pub fn check_iteration<M, F>(mut new: F)
+where
+    M: Map<Key, Value>,
+    for<'a> &'a M: IntoIterator<Item = (&'a Key, &'a Value)>,
+    F: FnMut() -> M,
+{
+    let tracker = Tracker::default();
+    let mut rng = Rng::new(2_490);
+    let mut map = new();
+    assert!((&map).into_iter().next().is_none());
+
+    let mut oracle = BTreeMap::new();
+    for round in 0..20 {
+        for _ in 0..50 {
+            let id = rng.below(300);
+            if rng.below(3) == 0 {
+                assert_eq!(map.remove(&id).is_some(), oracle.remove(&id).is_some());
+            } else {
+                map.insert(Key::new(id), tracker.value(id + round));
+                oracle.insert(id, id + round);
+            }
+        }
+
+        let mut entries: Vec<(u64, u64)> = (&map)
+            .into_iter()
+            .map(|(key, value)| (key.id(), value.id()))
+            .collect();
+        entries.sort_unstable();
+        assert!(entries.iter().copied().eq(oracle.clone()));
+    }
+}
@@ -0,0 +1,769 @@
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+/// A B-tree containing key-value pairs where the keys can be ordered.
+///
+/// Every node stores between `B - 1` and `2 * B - 1` entries (only the root may hold fewer) in
+/// contiguous arrays, and every leaf sits at the same depth. Packing many keys into a node keeps
+/// the tree shallow and lets a lookup scan a few cache lines per level instead of chasing a
+/// pointer per comparison, which pays off for large maps. `B` defaults to `6` and must be at
+/// least `2`.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::b_tree::BTree;
+///
+/// let mut tree: BTree<_, _> = BTree::new();
+/// tree.insert(0, "John");
+/// tree.insert(42, "Neo");
+/// tree.insert(2, "Alice");
+///
+/// assert_eq!(tree.get(&0), Some(&"John"));
+/// assert_eq!(tree.get(&42), Some(&"Neo"));
+/// assert_eq!(tree.get(&2), Some(&"Alice"));
+///
+/// let mut narrow: BTree<u32, (), 2> = BTree::new();
+/// narrow.extend((0..100).map(|key| (key, ())));
+/// assert!(narrow.range(10..13).map(|(key, _)| *key).eq(10..13));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BTree<K, V, const B: usize = 6> {
+    root: BTreeNode<K, V>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct BTreeNode<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    /// Empty for leaves, otherwise one more child than there are keys. The keys of
+    /// `children[i]` lie between `keys[i - 1]` and `keys[i]`.
+    children: Vec<BTreeNode<K, V>>,
+}
+
+impl<K, V> BTreeNode<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            keys: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.keys.binary_search_by(|probe| probe.borrow().cmp(key))
+    }
+
+    /// Splits the full child at `index` in two, moving its median entry up into this node.
+    fn split_child<const B: usize>(&mut self, index: usize) {
+        let child = &mut self.children[index];
+        let mut right = BTreeNode::new(2 * B - 1);
+        right.keys.extend(child.keys.drain(B..));
+        right.values.extend(child.values.drain(B..));
+        if !child.is_leaf() {
+            right.children.extend(child.children.drain(B..));
+        }
+        let key = child.keys.pop().expect("A full child holds a median key");
+        let value = child
+            .values
+            .pop()
+            .expect("A full child holds a median value");
+
+        self.keys.insert(index, key);
+        self.values.insert(index, value);
+        self.children.insert(index + 1, right);
+    }
+
+    /// Makes sure that the child at `index` holds at least `B` entries before removing from it,
+    /// borrowing from a sibling or merging with one. Returns the index of the child to descend
+    /// into, which changes if it was merged into its left sibling.
+    fn fill_child<const B: usize>(&mut self, index: usize) -> usize {
+        if self.children[index].keys.len() >= B {
+            return index;
+        }
+
+        if index > 0 && self.children[index - 1].keys.len() >= B {
+            let (left, right) = self.children.split_at_mut(index);
+            let (left, child) = (&mut left[index - 1], &mut right[0]);
+            let key = left
+                .keys
+                .pop()
+                .expect("A sibling with B entries has a last key");
+            let value = left
+                .values
+                .pop()
+                .expect("A sibling with B entries has a last value");
+            let key = core::mem::replace(&mut self.keys[index - 1], key);
+            let value = core::mem::replace(&mut self.values[index - 1], value);
+            child.keys.insert(0, key);
+            child.values.insert(0, value);
+            if !left.is_leaf() {
+                let grandchild = left.children.pop().expect("An inner node has children");
+                child.children.insert(0, grandchild);
+            }
+            index
+        } else if index + 1 < self.children.len() && self.children[index + 1].keys.len() >= B {
+            let (left, right) = self.children.split_at_mut(index + 1);
+            let (child, right) = (&mut left[index], &mut right[0]);
+            let key = core::mem::replace(&mut self.keys[index], right.keys.remove(0));
+            let value = core::mem::replace(&mut self.values[index], right.values.remove(0));
+            child.keys.push(key);
+            child.values.push(value);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+            index
+        } else if index > 0 {
+            self.merge_children(index - 1);
+            index - 1
+        } else {
+            self.merge_children(index);
+            index
+        }
+    }
+
+    /// Merges the child at `index + 1` and the entry separating it from the child at `index` into
+    /// the latter.
+    fn merge_children(&mut self, index: usize) {
+        let right = self.children.remove(index + 1);
+        let (key, value) = (self.keys.remove(index), self.values.remove(index));
+        let BTreeNode {
+            keys,
+            values,
+            children,
+        } = right;
+
+        let left = &mut self.children[index];
+        left.keys.push(key);
+        left.values.push(value);
+        left.keys.extend(keys);
+        left.values.extend(values);
+        left.children.extend(children);
+    }
+
+    /// Removes the entry with the smallest (`last == false`) or largest (`last == true`) key from
+    /// the subtree rooted at this node, which must hold at least `B` entries unless it is the root.
+    fn remove_extreme<const B: usize>(&mut self, last: bool) -> Option<(K, V)> {
+        let mut node = self;
+        while !node.is_leaf() {
+            let index = if last { node.children.len() - 1 } else { 0 };
+            let index = node.fill_child::<B>(index);
+            node = &mut node.children[index];
+        }
+
+        if last {
+            Some((node.keys.pop()?, node.values.pop()?))
+        } else if node.keys.is_empty() {
+            None
+        } else {
+            Some((node.keys.remove(0), node.values.remove(0)))
+        }
+    }
+}
+
+impl<K, V, const B: usize> BTree<K, V, B> {
+    /// Constructs an empty tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `B` is less than `2`.
+    pub fn new() -> Self {
+        assert!(B >= 2, "the branching factor of a BTree must be at least 2");
+        Self {
+            root: BTreeNode::new(0),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of entries in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every entry from the tree.
+    pub fn clear(&mut self) {
+        self.root = BTreeNode::new(0);
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the entries of the tree, sorted by key.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut stack = Vec::new();
+        if self.len > 0 {
+            descend(&mut stack, &self.root);
+        }
+        Iter {
+            stack,
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V, const B: usize> BTree<K, V, B>
+where
+    K: Ord,
+{
+    /// Inserts a key-value pair into the [`BTree`].
+    ///
+    /// Returns [`None`] if the key did not exist, otherwise updates
+    /// the value and returns [`Some`] with the old value.
+    ///
+    /// # Note
+    ///
+    /// Like with `HashMap` the key does not get updated.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.root.keys.len() == 2 * B - 1 {
+            let old_root = core::mem::replace(&mut self.root, BTreeNode::new(2 * B - 1));
+            self.root.children.push(old_root);
+            self.root.split_child::<B>(0);
+        }
+
+        // Full nodes are split on the way down, so that there always is room for the entry a
+        // split moves up into the parent.
+        let mut node = &mut self.root;
+        loop {
+            let index = match node.search(&key) {
+                Ok(index) => return Some(core::mem::replace(&mut node.values[index], value)),
+                Err(index) => index,
+            };
+            if node.is_leaf() {
+                node.keys.insert(index, key);
+                node.values.insert(index, value);
+                self.len += 1;
+                return None;
+            }
+
+            let mut index = index;
+            if node.children[index].keys.len() == 2 * B - 1 {
+                node.split_child::<B>(index);
+                match key.cmp(&node.keys[index]) {
+                    Ordering::Equal => {
+                        return Some(core::mem::replace(&mut node.values[index], value))
+                    }
+                    Ordering::Greater => index += 1,
+                    Ordering::Less => {}
+                }
+            }
+            node = &mut node.children[index];
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &self.root;
+        loop {
+            match node.search(key) {
+                Ok(index) => return Some(&node.values[index]),
+                Err(index) => node = node.children.get(index)?,
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &mut self.root;
+        loop {
+            match node.search(key) {
+                Ok(index) => return Some(&mut node.values[index]),
+                Err(index) => node = node.children.get_mut(index)?,
+            }
+        }
+    }
+
+    /// Returns `true` if the tree contains a value for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes a key from the tree, returning its value if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the tree, returning the stored key and value if it was present.
+    ///
+    /// Nodes on the way down are topped up to at least `B` entries first, borrowing from or
+    /// merging with a sibling, so that removing from a leaf never leaves it underfull.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = Self::remove_from(&mut self.root, key);
+        if self.root.keys.is_empty() {
+            if let Some(child) = self.root.children.pop() {
+                self.root = child;
+            }
+        }
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_from<Q>(mut node: &mut BTreeNode<K, V>, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            match node.search(key) {
+                Ok(index) if node.is_leaf() => {
+                    return Some((node.keys.remove(index), node.values.remove(index)));
+                }
+                Ok(index) => {
+                    // Replace the entry with its neighbour from a child that can spare one, or
+                    // merge both children around it and keep looking in the merged node.
+                    let replacement = if node.children[index].keys.len() >= B {
+                        node.children[index].remove_extreme::<B>(true)
+                    } else if node.children[index + 1].keys.len() >= B {
+                        node.children[index + 1].remove_extreme::<B>(false)
+                    } else {
+                        node.merge_children(index);
+                        node = &mut node.children[index];
+                        continue;
+                    };
+
+                    let (key, value) = replacement?;
+                    let key = core::mem::replace(&mut node.keys[index], key);
+                    let value = core::mem::replace(&mut node.values[index], value);
+                    return Some((key, value));
+                }
+                Err(_) if node.is_leaf() => return None,
+                Err(index) => {
+                    let index = node.fill_child::<B>(index);
+                    node = &mut node.children[index];
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over the entries of the tree whose keys lie within `range`, sorted by
+    /// key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both ends are equal and
+    /// excluded.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        match (start, end) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+                panic!("range start and end are equal and excluded in BTree")
+            }
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if s > e =>
+            {
+                panic!("range start is greater than range end in BTree")
+            }
+            _ => {}
+        }
+
+        // Walk down towards the first key in the range, remembering where to resume in every
+        // node on the way, just like `Iter` does after descending.
+        let mut stack = Vec::new();
+        let mut node = &self.root;
+        loop {
+            let index = node.keys.partition_point(|key| match start {
+                Bound::Included(start) => key.borrow() < start,
+                Bound::Excluded(start) => key.borrow() <= start,
+                Bound::Unbounded => false,
+            });
+            stack.push((node, index));
+            match node.children.get(index) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        // Find the last key in the range, so that the iterator knows when to stop without having
+        // to hold on to the bound.
+        let mut last = None;
+        let mut node = &self.root;
+        loop {
+            let index = node.keys.partition_point(|key| match end {
+                Bound::Included(end) => key.borrow() <= end,
+                Bound::Excluded(end) => key.borrow() < end,
+                Bound::Unbounded => true,
+            });
+            if index > 0 {
+                last = Some(&node.keys[index - 1]);
+            }
+            match node.children.get(index) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+
+        let mut range = Range {
+            inner: Iter { stack, len: 0 },
+            last,
+        };
+        let first = range.inner.clone().next().map(|(key, _)| key);
+        if let (Some(first), Some(last)) = (first, last) {
+            if first > last {
+                range.last = None;
+            }
+        }
+        if range.last.is_none() {
+            range.inner.stack.clear();
+        }
+        range
+    }
+}
+
+/// Pushes `node` and the leftmost path below it onto `stack`, each with the index of the next
+/// entry to visit.
+fn descend<'a, K, V>(stack: &mut Vec<(&'a BTreeNode<K, V>, usize)>, mut node: &'a BTreeNode<K, V>) {
+    loop {
+        stack.push((node, 0));
+        match node.children.first() {
+            Some(child) => node = child,
+            None => return,
+        }
+    }
+}
+
+impl<K, V, const B: usize> Default for BTree<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, const B: usize> Extend<(K, V)> for BTree<K, V, B> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V, const B: usize> FromIterator<(K, V)> for BTree<K, V, B> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, const B: usize> PartialEq for BTree<K, V, B> {
+    /// Compares the entries of both trees in ascending key order, regardless of their layout.
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, const B: usize> Eq for BTree<K, V, B> {}
+
+impl<K, V, Q, const B: usize> core::ops::Index<&Q> for BTree<K, V, B>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the B-tree.
+    fn index(&self, index: &Q) -> &Self::Output {
+        self.get(index).expect("Key is not present in the B-tree")
+    }
+}
+
+impl<'a, K, V, const B: usize> IntoIterator for &'a BTree<K, V, B> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`BTree`], sorted by key.
+///
+/// This `struct` is created by [`BTree::iter`].
+pub struct Iter<'a, K, V> {
+    /// The nodes on the path to the next entry, each with the index of the next entry to visit
+    /// in it. Every child before that index has been visited already.
+    stack: Vec<(&'a BTreeNode<K, V>, usize)>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, index)) = self.stack.pop() {
+            if index < node.keys.len() {
+                self.stack.push((node, index + 1));
+                if let Some(child) = node.children.get(index + 1) {
+                    descend(&mut self.stack, child);
+                }
+                self.len = self.len.saturating_sub(1);
+                return Some((&node.keys[index], &node.values[index]));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`BTree`] whose keys lie within a range, sorted by key.
+///
+/// This `struct` is created by [`BTree::range`].
+pub struct Range<'a, K, V> {
+    inner: Iter<'a, K, V>,
+    /// The last key in the range, or [`None`] once it has been yielded.
+    last: Option<&'a K>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.last?;
+        let (key, value) = self.inner.next()?;
+        if core::ptr::eq(key, last) {
+            self.last = None;
+        }
+        Some((key, value))
+    }
+}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            last: self.last,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Range<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::collections::BTreeMap;
+
+    /// Checks that the keys are sorted, that every node is filled within bounds and that every
+    /// leaf sits at the same depth.
+    fn assert_b_tree<K: Ord, V, const B: usize>(tree: &BTree<K, V, B>) {
+        let mut leaf_depth = None;
+        let mut count = 0;
+        let mut pending = alloc::vec![(&tree.root, 0, None::<&K>, None::<&K>)];
+        while let Some((node, depth, low, high)) = pending.pop() {
+            count += node.keys.len();
+            assert_eq!(node.keys.len(), node.values.len());
+            assert!(node.keys.len() < 2 * B);
+            if depth > 0 {
+                assert!(node.keys.len() >= B - 1);
+            }
+            assert!(node.keys.windows(2).all(|pair| pair[0] < pair[1]));
+            if let (Some(low), Some(first)) = (low, node.keys.first()) {
+                assert!(low < first);
+            }
+            if let (Some(high), Some(last)) = (high, node.keys.last()) {
+                assert!(last < high);
+            }
+
+            if node.is_leaf() {
+                assert_eq!(*leaf_depth.get_or_insert(depth), depth);
+            } else {
+                assert_eq!(node.children.len(), node.keys.len() + 1);
+                for (index, child) in node.children.iter().enumerate() {
+                    let low = index.checked_sub(1).map(|index| &node.keys[index]).or(low);
+                    let high = node.keys.get(index).or(high);
+                    pending.push((child, depth + 1, low, high));
+                }
+            }
+        }
+        assert_eq!(count, tree.len());
+    }
+
+    /// Pins `B` to its default, which is not inferred for `BTree::new()` in expressions.
+    type DefaultBTree<K, V> = BTree<K, V>;
+
+    crate::tree::map_tests!(DefaultBTree, assert_b_tree);
+
+    fn randomized_against_btree_map<const B: usize>(seed: u64) {
+        let mut rng = Rng::new(seed);
+        let mut tree: BTree<u64, u64, B> = BTree::new();
+        let mut reference = BTreeMap::new();
+
+        for step in 0..20_000 {
+            let key = rng.below(2_000);
+            // Mostly insert at first and mostly remove later, so the tree grows and shrinks by
+            // several levels.
+            if rng.below(10) < if step < 10_000 { 3 } else { 7 } {
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+            } else {
+                let value = rng.next_u64();
+                assert_eq!(tree.insert(key, value), reference.insert(key, value));
+            }
+            assert_eq!(tree.len(), reference.len());
+            if step % 500 == 0 {
+                assert_b_tree(&tree);
+            }
+
+            let probe = rng.below(2_000);
+            assert_eq!(tree.get(&probe), reference.get(&probe));
+        }
+
+        assert_b_tree(&tree);
+        assert!(tree.iter().eq(reference.iter()));
+        for key in reference.keys() {
+            assert!(tree.remove(key).is_some());
+        }
+        assert!(tree.is_empty());
+        assert!(tree.root.is_leaf());
+    }
+
+    #[test]
+    fn randomized() {
+        randomized_against_btree_map::<2>(437);
+        randomized_against_btree_map::<3>(438);
+        randomized_against_btree_map::<6>(439);
+        randomized_against_btree_map::<16>(440);
+    }
+
+    #[test]
+    fn sequential_inserts_and_removes() {
+        let mut tree: BTree<u32, u32, 3> = BTree::new();
+        for key in 0..10_000 {
+            tree.insert(key, key * 2);
+        }
+        assert_b_tree(&tree);
+        assert_eq!(tree.iter().len(), 10_000);
+        assert!(tree.iter().map(|(key, _)| *key).eq(0..10_000));
+
+        for key in (0..10_000).rev().step_by(2) {
+            assert_eq!(tree.remove(&key), Some(key * 2));
+        }
+        assert_b_tree(&tree);
+        assert!(tree.iter().map(|(key, _)| *key).eq((0..10_000).step_by(2)));
+    }
+
+    #[test]
+    fn range() {
+        let tree: BTree<u32, (), 2> = (0..200).map(|key| (key * 2, ())).collect();
+        let keys = |range: Range<'_, u32, ()>| range.map(|(key, _)| *key).collect::<Vec<_>>();
+
+        assert_eq!(keys(tree.range(10..17)), [10, 12, 14, 16]);
+        assert_eq!(keys(tree.range(11..=16)), [12, 14, 16]);
+        assert_eq!(
+            keys(tree.range((Bound::Excluded(10), Bound::Included(14)))),
+            [12, 14]
+        );
+        assert_eq!(keys(tree.range(395..)), [396, 398]);
+        assert_eq!(keys(tree.range(..3)), [0, 2]);
+        assert_eq!(tree.range(..).count(), 200);
+        assert_eq!(tree.range(11..12).count(), 0);
+        assert_eq!(tree.range(500..).count(), 0);
+        assert_eq!(tree.range(..0).count(), 0);
+
+        let mut rng = Rng::new(4370);
+        let reference: BTreeMap<u32, ()> = tree.iter().map(|(key, _)| (*key, ())).collect();
+        for _ in 0..1_000 {
+            let (a, b) = (rng.below(420) as u32, rng.below(420) as u32);
+            let (low, high) = (a.min(b), a.max(b));
+            assert!(tree.range(low..high).eq(reference.range(low..high)));
+            assert!(tree.range(low..=high).eq(reference.range(low..=high)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end in BTree")]
+    fn range_inverted() {
+        let tree: BTree<u32, ()> = BTree::new();
+        let _ = tree.range((Bound::Included(2), Bound::Included(1)));
+    }
+
+    #[test]
+    fn borrowed_keys() {
+        let mut tree: BTree<alloc::string::String, u32> = BTree::new();
+        for (value, key) in ["pear", "apple", "fig"].into_iter().enumerate() {
+            tree.insert(key.into(), value as u32);
+        }
+        assert_eq!(tree.get("fig"), Some(&2));
+        assert_eq!(tree["apple"], 1);
+        assert!(tree
+            .range::<str, _>((Bound::Included("b"), Bound::Excluded("g")))
+            .map(|(key, _)| key.as_str())
+            .eq(["fig"]));
+        assert_eq!(tree.remove("pear"), Some(0));
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "the branching factor of a BTree must be at least 2")]
+    fn branching_factor_too_small() {
+        let _: BTree<u32, (), 1> = BTree::new();
+    }
+}
@@ -1,15 +1,36 @@
-use alloc::boxed::Box;
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    format,
+    string::String,
+    vec::Vec,
+};
+use core::{
+    alloc::Layout,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+    ptr,
+};
 
 /// A binary tree containing key-value pairs where the keys can be ordered.
 ///
 /// It should be noted that for most applications, a `HashMap` will offer
 /// superior performance to that of a binary tree, since each node in the tree requires a heap
-/// allocation (apart from the root). Hash maps also provided amortized-constant lookup times where
+/// allocation. Hash maps also provided amortized-constant lookup times where
 /// a binary tree gives `O(log(n))`.
 ///
+/// The tree is an AVL tree: every insertion and removal rebalances the nodes along the affected
+/// path, so that the heights of the two subtrees of any node differ by at most one. This keeps
+/// lookups, insertions and removals at `O(log(n))` regardless of the order in which keys are
+/// inserted.
+///
 /// For efficiency, the tree maintains a count of the number of elements inserted so that the
 /// `len` and `is_empty` methods are constant-time complexity.
 ///
+/// Keys must implement [`Ord`]. Floating-point keys can be wrapped in
+/// [`TotalF32`](crate::ord::TotalF32) or [`TotalF64`](crate::ord::TotalF64) to get a total
+/// order.
+///
 /// # Examples
 ///
 /// This example shows how the binary tree functions much like a `HashMap`, but
@@ -26,19 +47,26 @@ use alloc::boxed::Box;
 /// assert_eq!(tree.get(&42), Some(&"Neo"));
 /// assert_eq!(tree.get(&2), Some(&"Alice"));
 /// ```
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default)]
 pub struct BinaryTree<K, V> {
-    root: Option<BinaryTreeNode<K, V>>,
+    root: NodeChild<K, V>,
     len: usize,
 }
 
 type NodeChild<K, V> = Option<Box<BinaryTreeNode<K, V>>>;
 
+/// The outcome of inserting into a tree without replacing an existing value.
+type EntryResult<'a, K, V> = Result<&'a mut V, OccupiedError<'a, K, V>>;
+
 #[doc(hidden)]
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default)]
 struct BinaryTreeNode<K, V> {
     key: K,
     value: V,
+    /// The number of nodes on the longest path from this node down to a leaf, including itself.
+    height: usize,
+    /// The number of nodes in the subtree rooted at this node, including itself.
+    size: usize,
     children: (NodeChild<K, V>, NodeChild<K, V>),
 }
 
@@ -47,12 +75,496 @@ impl<K, V> BinaryTree<K, V> {
     pub fn new() -> Self {
         Self { root: None, len: 0 }
     }
+
+    /// Returns the number of elements in the tree with constant-time complexity.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every element from the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::from([(1, 'a'), (2, 'b')]);
+    /// tree.clear();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        BinaryTreeNode::drop_subtree(self.root.take());
+        self.len = 0;
+    }
+
+    /// Returns the entry with the smallest key, or [`None`] if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert_eq!(tree.first_key_value(), None);
+    ///
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.first_key_value(), Some((&1, &'a')));
+    /// ```
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.children.0.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Returns the entry with the largest key, or [`None`] if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert_eq!(tree.last_key_value(), None);
+    ///
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.last_key_value(), Some((&2, &'b')));
+    /// ```
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.children.1.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// Removes and returns the entry with the smallest key, or [`None`] if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    ///
+    /// assert_eq!(tree.pop_first(), Some((1, 'a')));
+    /// assert_eq!(tree.pop_first(), Some((2, 'b')));
+    /// assert_eq!(tree.pop_first(), None);
+    /// ```
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let node = BinaryTreeNode::unlink_min(&mut self.root)?;
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    /// Removes and returns the entry with the largest key, or [`None`] if the tree is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    ///
+    /// assert_eq!(tree.pop_last(), Some((2, 'b')));
+    /// assert_eq!(tree.pop_last(), Some((1, 'a')));
+    /// assert_eq!(tree.pop_last(), None);
+    /// ```
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let node = BinaryTreeNode::unlink_max(&mut self.root)?;
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    /// Returns an iterator over the entries of the tree, sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    ///
+    /// assert!(tree.iter().eq([(&1, &'a'), (&2, &'b')]));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut pieces = VecDeque::new();
+        if let Some(root) = self.root.as_deref() {
+            pieces.push_back(Piece::Subtree(root));
+        }
+
+        Iter {
+            pieces,
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries of the tree in pre-order, visiting every node before
+    /// its left and then its right subtree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [2, 1, 3] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert!(tree.iter_preorder().map(|(key, _)| *key).eq([2, 1, 3]));
+    /// ```
+    pub fn iter_preorder(&self) -> PreOrder<'_, K, V> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries of the tree in post-order, visiting the left and then
+    /// the right subtree of every node before the node itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [2, 1, 3] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert!(tree.iter_postorder().map(|(key, _)| *key).eq([1, 3, 2]));
+    /// ```
+    pub fn iter_postorder(&self) -> PostOrder<'_, K, V> {
+        PostOrder {
+            stack: self
+                .root
+                .as_deref()
+                .map(|root| (root, false))
+                .into_iter()
+                .collect(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries of the tree in level-order, visiting the nodes one
+    /// depth at a time from left to right.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [2, 1, 3, 4] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert!(tree.iter_levelorder().map(|(key, _)| *key).eq([2, 1, 3, 4]));
+    /// ```
+    pub fn iter_levelorder(&self) -> LevelOrder<'_, K, V> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+            len: self.len,
+        }
+    }
+
+    /// Returns a value that displays the shape of the tree, with one entry per line.
+    ///
+    /// Every child is drawn below its parent and marked with `L` or `R` depending on which side
+    /// of its parent it is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [2, 1, 3, 4] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// let expected = "\
+    /// 2: ()
+    /// ├── L 1: ()
+    /// └── R 3: ()
+    ///     └── R 4: ()";
+    /// assert_eq!(tree.display_structure().to_string(), expected);
+    /// ```
+    pub fn display_structure(&self) -> DisplayStructure<'_, K, V> {
+        DisplayStructure { tree: self }
+    }
+
+    /// Returns an iterator over the keys of the tree in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [42, 7, 13, 1] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// let keys: Vec<_> = tree.keys().copied().collect();
+    /// assert_eq!(keys, [1, 7, 13, 42]);
+    /// ```
+    ///
+    /// Since the keys are sorted, they can be combined with other sorted sequences using the
+    /// adapters in [`iter`](crate::iter):
+    ///
+    /// ```
+    /// use collect_me::iter::intersection_sorted;
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [5, 1, 3, 4] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert!(intersection_sorted(tree.keys(), &[2, 3, 5]).eq(&[3, 5]));
+    /// ```
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Returns an iterator over the values of the tree, sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from([(2, 'b'), (1, 'a')]);
+    ///
+    /// assert!(tree.values().eq(&['a', 'b']));
+    /// assert!(tree.values().rev().eq(&['b', 'a']));
+    /// ```
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Consumes the tree, returning an iterator over its keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(String::from("b"), 2);
+    /// tree.insert(String::from("a"), 1);
+    ///
+    /// let keys: Vec<String> = tree.into_keys().collect();
+    /// assert_eq!(keys, ["a", "b"]);
+    /// ```
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys {
+            inner: self.into_iter(),
+        }
+    }
+
+    /// Consumes the tree, returning an iterator over its values sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(2, String::from("b"));
+    /// tree.insert(1, String::from("a"));
+    ///
+    /// let values: Vec<String> = tree.into_values().collect();
+    /// assert_eq!(values, ["a", "b"]);
+    /// ```
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues {
+            inner: self.into_iter(),
+        }
+    }
+
+    /// Consumes the tree, transforming every value with `f` while keeping the keys and the shape
+    /// of the tree unchanged.
+    ///
+    /// The entries are visited in ascending key order and each one is visited exactly once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert("one", "1");
+    /// tree.insert("two", "2");
+    ///
+    /// let parsed = tree.map_values(|_, value| value.parse::<u32>().expect("valid number"));
+    /// assert_eq!(parsed.get("two"), Some(&2));
+    /// ```
+    pub fn map_values<U, F>(mut self, mut f: F) -> BinaryTree<K, U>
+    where
+        F: FnMut(&K, V) -> U,
+    {
+        BinaryTree {
+            root: BinaryTreeNode::map_values(self.root.take(), &mut f),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries of the tree, sorted by key, with mutable references
+    /// to the values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert("b", 2);
+    /// tree.insert("a", 1);
+    ///
+    /// for (_, value) in tree.iter_mut() {
+    ///     *value *= 10;
+    /// }
+    ///
+    /// assert_eq!(tree.get(&"a"), Some(&10));
+    /// assert_eq!(tree.get(&"b"), Some(&20));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut pieces = VecDeque::new();
+        if let Some(root) = self.root.as_deref_mut() {
+            pieces.push_back(MutPiece::Subtree(root));
+        }
+
+        IterMut {
+            pieces,
+            len: self.len,
+        }
+    }
+
+    /// Retains only the entries for which the predicate returns `true`.
+    ///
+    /// The entries are visited in ascending key order and the predicate may modify the values it
+    /// keeps. Rejected entries are unlinked from the tree as they are found, rebalancing the path
+    /// above them, so the nodes that are kept stay where they are. Each entry takes `O(log(n))`
+    /// to find and remove. If the predicate panics, the entry it was called with and every entry
+    /// it has not visited yet are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in 0..6 {
+    ///     tree.insert(key, key);
+    /// }
+    ///
+    /// tree.retain(|key, value| {
+    ///     *value *= 10;
+    ///     key % 2 == 0
+    /// });
+    /// assert_eq!(Vec::from(tree), [(0, 0), (2, 20), (4, 40)]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        // `ExtractIf` keeps the tree consistent between visits, so a panicking `f` leaves it
+        // intact.
+        self.extract_if(|key, value| !f(key, value)).for_each(drop);
+    }
+
+    /// Returns an iterator that removes and yields every entry for which the predicate returns
+    /// `true`, in ascending key order.
+    ///
+    /// The predicate may modify the values it keeps. Matching entries are unlinked from the tree
+    /// as they are yielded, so the tree stays whole while the iterator is alive. If the iterator
+    /// is dropped or leaked before it is exhausted, the entries it has not visited yet stay in the
+    /// tree, as does the entry the predicate was called with if it panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in 0..6 {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// let odd: Vec<_> = tree.extract_if(|key, _| key % 2 == 1).map(|(key, _)| key).collect();
+    /// assert_eq!(odd, [1, 3, 5]);
+    /// assert_eq!(tree.into_keys().collect::<Vec<_>>(), [0, 2, 4]);
+    /// ```
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            tree: self,
+            index: 0,
+            pred,
+        }
+    }
 }
 
 impl<K, V> BinaryTree<K, V>
 where
-    K: PartialOrd + Eq,
+    K: Ord,
 {
+    /// Builds a perfectly balanced tree from entries sorted in strictly ascending key order in
+    /// linear time.
+    ///
+    /// The caller is responsible for the entries being sorted and free of duplicate keys. This
+    /// is only checked in debug builds, and an unsorted input produces a tree whose lookups
+    /// return unspecified results.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the keys are not strictly ascending.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from_sorted_iter((0..1000).map(|key| (key, key * 2)));
+    /// assert_eq!(tree.len(), 1000);
+    /// assert_eq!(tree.height(), 10);
+    /// assert_eq!(tree.get(&500), Some(&1000));
+    /// ```
+    pub fn from_sorted_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let entries: Vec<_> = iter.into_iter().collect();
+        debug_assert!(
+            entries.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "keys passed to BinaryTree::from_sorted_iter are not strictly ascending"
+        );
+
+        let len = entries.len();
+        let root = BinaryTreeNode::from_sorted(&mut entries.into_iter(), len);
+        Self { root, len }
+    }
+
     /// Inserts a key-value pair into the [`BinaryTree`].
     ///
     /// Returns [`None`] if the key did not exist, otherwise updates
@@ -62,199 +574,4362 @@ where
     ///
     /// Like with `HashMap` the key does not get updated.
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        let result = if let Some(ref mut root) = self.root {
-            root.insert(key, value)
-        } else {
-            self.root = Some(BinaryTreeNode {
-                key,
-                value,
-                children: (None, None),
-            });
-            None
-        };
+        match self.insert_with(key, value, |node| Ok(Box::new(node))) {
+            Ok(result) => result,
+            Err(_) => unreachable!("infallible allocation failed"),
+        }
+    }
+
+    /// Inserts a key-value pair into the [`BinaryTree`] without aborting on allocation failure.
+    ///
+    /// Behaves exactly like [`BinaryTree::insert`], except that if the allocator is unable to
+    /// provide memory for a new node, the tree is left untouched and the key and value are
+    /// handed back inside an [`InsertAllocError`]. Replacing the value of an existing key never
+    /// allocates and therefore never fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert_eq!(tree.try_insert_alloc(1, "a"), Ok(None));
+    /// assert_eq!(tree.try_insert_alloc(1, "b"), Ok(Some("a")));
+    /// ```
+    pub fn try_insert_alloc(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> Result<Option<V>, InsertAllocError<K, V>> {
+        self.insert_with(key, value, try_box)
+    }
+
+    /// Inserts a key-value pair into the [`BinaryTree`] if the key is not present yet.
+    ///
+    /// Returns a mutable reference to the inserted value. If the key is already present, the
+    /// tree is left untouched and an [`OccupiedError`] is returned, which holds the rejected
+    /// value as well as the existing entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert_eq!(tree.try_insert(1, "a"), Ok(&mut "a"));
+    ///
+    /// let error = tree.try_insert(1, "b").expect_err("key 1 is already present");
+    /// assert_eq!(error.existing(), &"a");
+    /// assert_eq!(error.value(), &"b");
+    /// assert_eq!(tree.get(&1), Some(&"a"));
+    /// ```
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, OccupiedError<'_, K, V>> {
+        match self.insert_entry(key, value, |node| Ok(Box::new(node))) {
+            Ok(result) => result,
+            Err(_) => unreachable!("infallible allocation failed"),
+        }
+    }
+
+    fn insert_with<F>(
+        &mut self,
+        key: K,
+        value: V,
+        alloc: F,
+    ) -> Result<Option<V>, InsertAllocError<K, V>>
+    where
+        F: FnOnce(BinaryTreeNode<K, V>) -> Result<Box<BinaryTreeNode<K, V>>, BinaryTreeNode<K, V>>,
+    {
+        match self.insert_entry(key, value, alloc)? {
+            Ok(_) => Ok(None),
+            Err(occupied) => Ok(Some(core::mem::replace(occupied.existing, occupied.value))),
+        }
+    }
+
+    /// Inserts a new node for `key` unless the key is already present, in which case the
+    /// existing entry is handed back along with `value`.
+    fn insert_entry<F>(
+        &mut self,
+        key: K,
+        value: V,
+        alloc: F,
+    ) -> Result<EntryResult<'_, K, V>, InsertAllocError<K, V>>
+    where
+        F: FnOnce(BinaryTreeNode<K, V>) -> Result<Box<BinaryTreeNode<K, V>>, BinaryTreeNode<K, V>>,
+    {
+        // Find the position of the key in ascending order, which rotations leave unchanged.
+        let mut rank = 0;
+        let mut found = false;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let left = BinaryTreeNode::size(&current.children.0);
+            node = match key.cmp(&current.key) {
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => {
+                    rank += left + 1;
+                    current.children.1.as_deref()
+                }
+                Ordering::Equal => {
+                    rank += left;
+                    found = true;
+                    break;
+                }
+            };
+        }
 
-        if result.is_none() {
-            self.len += 1;
+        if found {
+            let node = BinaryTreeNode::nth_mut(&mut self.root, rank).expect("The key was found");
+            return Ok(Err(OccupiedError {
+                key: &node.key,
+                existing: &mut node.value,
+                value,
+            }));
         }
 
-        result
+        let node = BinaryTreeNode::new_boxed(key, value, alloc)?;
+        BinaryTreeNode::insert_nth(&mut self.root, rank, node);
+        self.len += 1;
+        let node =
+            BinaryTreeNode::nth_mut(&mut self.root, rank).expect("The tree holds the new node");
+        Ok(Ok(&mut node.value))
     }
 
     /// Returns a reference to the value corresponding to the key.
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
         K: core::borrow::Borrow<Q>,
-        Q: PartialOrd + Eq,
+        Q: Ord + ?Sized,
     {
-        self.root.as_ref().and_then(|root| root.get(key))
+        self.get_key_value(key).map(|(_, value)| value)
     }
 
-    /// Returns a mutable reference to the value corresponding to the key.
-    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    /// Returns the key-value pair corresponding to the supplied key.
+    ///
+    /// The returned key is the one stored in the tree, which is useful when distinct keys can
+    /// compare equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(String::from("apple"), 1);
+    ///
+    /// assert_eq!(tree.get_key_value("apple"), Some((&String::from("apple"), &1)));
+    /// assert_eq!(tree.get_key_value("pear"), None);
+    /// ```
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
         K: core::borrow::Borrow<Q>,
-        Q: PartialOrd + Eq,
+        Q: Ord + ?Sized,
     {
-        self.root.as_mut().and_then(|root| root.get_mut(key))
-    }
-
-    /// Returns the number of elements in the tree with constant-time complexity.
-    pub fn len(&self) -> usize {
-        self.len
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return Some((&current.key, &current.value)),
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => current.children.1.as_deref(),
+            };
+        }
+        None
     }
 
-    /// Returns `true` if the tree is empty.
-    pub fn is_empty(&self) -> bool {
-        self.len == 0
+    /// Returns the entry with the greatest key less than or equal to the supplied key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from([(10, 'a'), (20, 'b')]);
+    ///
+    /// assert_eq!(tree.floor_key_value(&15), Some((&10, &'a')));
+    /// assert_eq!(tree.floor_key_value(&20), Some((&20, &'b')));
+    /// assert_eq!(tree.floor_key_value(&5), None);
+    /// ```
+    pub fn floor_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut best = None;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return Some((&current.key, &current.value)),
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => {
+                    best = Some((&current.key, &current.value));
+                    current.children.1.as_deref()
+                }
+            };
+        }
+        best
     }
-}
 
-impl<K, V> BinaryTreeNode<K, V>
-where
-    K: PartialOrd + Eq,
-{
-    fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if key < self.key {
-            if let Some(ref mut child) = self.children.0 {
-                child.insert(key, value)
-            } else {
-                self.children.0 = Some(Box::new(BinaryTreeNode {
-                    key,
-                    value,
-                    children: (None, None),
-                }));
-                None
-            }
-        } else if key > self.key {
-            if let Some(ref mut child) = self.children.1 {
-                child.insert(key, value)
-            } else {
-                self.children.1 = Some(Box::new(BinaryTreeNode {
-                    key,
-                    value,
-                    children: (None, None),
-                }));
-                None
-            }
-        } else {
-            Some(core::mem::replace(&mut self.value, value))
+    /// Returns the entry with the least key greater than or equal to the supplied key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from([(10, 'a'), (20, 'b')]);
+    ///
+    /// assert_eq!(tree.ceiling_key_value(&15), Some((&20, &'b')));
+    /// assert_eq!(tree.ceiling_key_value(&10), Some((&10, &'a')));
+    /// assert_eq!(tree.ceiling_key_value(&25), None);
+    /// ```
+    pub fn ceiling_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut best = None;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return Some((&current.key, &current.value)),
+                Ordering::Less => {
+                    best = Some((&current.key, &current.value));
+                    current.children.0.as_deref()
+                }
+                Ordering::Greater => current.children.1.as_deref(),
+            };
         }
+        best
     }
 
-    fn get<Q>(&self, key: &Q) -> Option<&V>
+    /// Returns the number of keys in the tree that are strictly less than the supplied key.
+    ///
+    /// Every node keeps track of the size of its subtree, so this takes logarithmic time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [10, 20, 30] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert_eq!(tree.rank(&5), 0);
+    /// assert_eq!(tree.rank(&20), 1);
+    /// assert_eq!(tree.rank(&25), 2);
+    /// assert_eq!(tree.rank(&35), 3);
+    /// ```
+    pub fn rank<Q>(&self, key: &Q) -> usize
     where
         K: core::borrow::Borrow<Q>,
-        Q: PartialOrd + Eq,
+        Q: Ord + ?Sized,
     {
-        if *key == *self.key.borrow() {
-            Some(&self.value)
-        } else if *key < *self.key.borrow() {
-            self.children.0.as_ref().and_then(|child| child.get(key))
-        } else {
-            self.children.1.as_ref().and_then(|child| child.get(key))
+        let mut rank = 0;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return rank + BinaryTreeNode::size(&current.children.0),
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => {
+                    rank += BinaryTreeNode::size(&current.children.0) + 1;
+                    current.children.1.as_deref()
+                }
+            };
         }
+        rank
     }
 
-    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
     where
         K: core::borrow::Borrow<Q>,
-        Q: PartialOrd + Eq,
+        Q: Ord + ?Sized,
     {
-        if *key == *self.key.borrow() {
-            Some(&mut self.value)
-        } else if *key < *self.key.borrow() {
-            self.children
-                .0
-                .as_mut()
-                .and_then(|child| child.get_mut(key))
-        } else {
-            self.children
-                .1
-                .as_mut()
-                .and_then(|child| child.get_mut(key))
+        let mut node = self.root.as_deref_mut();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return Some(&mut current.value),
+                Ordering::Less => current.children.0.as_deref_mut(),
+                Ordering::Greater => current.children.1.as_deref_mut(),
+            };
         }
+        None
     }
-}
-
-impl<K, V> core::ops::Index<&K> for BinaryTree<K, V>
-where
-    K: PartialOrd + Eq,
-{
-    type Output = V;
 
-    /// Returns a reference to the value corresponding to the supplied key.
+    /// Returns an iterator over the entries of the tree whose keys lie within `range`, sorted by
+    /// key.
+    ///
+    /// Subtrees that lie entirely outside of the range are never visited.
     ///
     /// # Panics
     ///
-    /// Panics if the key is not present in the binary tree.
-    fn index(&self, index: &K) -> &Self::Output {
-        self.get(index)
-            .expect("Key is not present in the binary tree")
-    }
-}
-
-#[cfg(test)]
-mod tests {
+    /// Panics if the start of the range is greater than its end, or if both ends are equal and
+    /// excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in 0..10 {
+    ///     tree.insert(key, key * 10);
+    /// }
+    ///
+    /// let keys: Vec<_> = tree.range(3..6).map(|(key, _)| *key).collect();
+    /// assert_eq!(keys, [3, 4, 5]);
+    /// assert_eq!(tree.range(8..).count(), 2);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = check_range(&range);
+        let mut pieces = VecDeque::new();
+        BinaryTreeNode::range_pieces(self.root.as_deref(), start, end, &mut pieces);
+        Range { pieces }
+    }
+
+    /// Returns a mutable iterator over the entries of the tree whose keys lie within `range`,
+    /// sorted by key.
+    ///
+    /// Subtrees that lie entirely outside of the range are never visited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both ends are equal and
+    /// excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in 0..10 {
+    ///     tree.insert(key, key);
+    /// }
+    ///
+    /// for (_, value) in tree.range_mut(..=2) {
+    ///     *value = 0;
+    /// }
+    /// assert_eq!(tree.get(&2), Some(&0));
+    /// assert_eq!(tree.get(&3), Some(&3));
+    /// ```
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = check_range(&range);
+        let mut pieces = VecDeque::new();
+        BinaryTreeNode::range_pieces_mut(self.root.as_deref_mut(), start, end, &mut pieces);
+        RangeMut { pieces }
+    }
+
+    /// Returns the number of entries of the tree whose keys lie within `range`.
+    ///
+    /// Every node keeps track of the size of its subtree, so this takes logarithmic time no
+    /// matter how many entries the range contains.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both ends are equal and
+    /// excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in 0..100 {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert_eq!(tree.range_count(10..20), 10);
+    /// assert_eq!(tree.range_count(..=10), 11);
+    /// assert_eq!(tree.range_count(200..), 0);
+    /// ```
+    pub fn range_count<Q, R>(&self, range: R) -> usize
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = check_range(&range);
+        let before_range = self.prefix_len(|key| !after_start(key.borrow(), start));
+        let up_to_end = self.prefix_len(|key| before_end(key.borrow(), end));
+        up_to_end - before_range
+    }
+
+    /// Returns the number of keys for which `in_prefix` holds, which must be true for some
+    /// prefix of the keys in ascending order and false for the rest.
+    fn prefix_len<F>(&self, in_prefix: F) -> usize
+    where
+        F: Fn(&K) -> bool,
+    {
+        let mut len = 0;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = if in_prefix(&current.key) {
+                len += BinaryTreeNode::size(&current.children.0) + 1;
+                current.children.1.as_deref()
+            } else {
+                current.children.0.as_deref()
+            };
+        }
+        len
+    }
+
+    /// Returns a cursor positioned at the first entry whose key is greater than or equal to the
+    /// supplied key, or at the ghost position if there is no such entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [10, 20, 30] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// let mut cursor = tree.cursor_mut(&15);
+    /// assert_eq!(cursor.key(), Some(&20));
+    /// assert_eq!(cursor.remove_current(), Some((20, ())));
+    /// assert_eq!(cursor.key(), Some(&30));
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.key(), Some(&10));
+    /// ```
+    pub fn cursor_mut<Q>(&mut self, key: &Q) -> CursorMut<'_, K, V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.rank(key);
+        let mut cursor = CursorMut {
+            tree: self,
+            path: Vec::new(),
+            index,
+        };
+        cursor.seek(index);
+        cursor
+    }
+
+    /// Removes a key from the tree, returning its value if the key was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(1, "a");
+    ///
+    /// assert_eq!(tree.remove(&1), Some("a"));
+    /// assert_eq!(tree.remove(&1), None);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the tree, returning the stored key and its value if the key was
+    /// present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(String::from("a"), 1);
+    ///
+    /// assert_eq!(tree.remove_entry("a"), Some((String::from("a"), 1)));
+    /// assert_eq!(tree.remove_entry("a"), None);
+    /// ```
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = BinaryTreeNode::remove(&mut self.root, key)?;
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    /// Moves all entries from `other` into `self`, leaving `other` empty.
+    ///
+    /// If a key is present in both trees, the value from `other` replaces the one in `self`.
+    /// Both trees are merged in linear time and the result is rebuilt perfectly balanced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert(1, 'a');
+    /// a.insert(2, 'b');
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert(2, 'B');
+    /// b.insert(3, 'C');
+    ///
+    /// a.append(&mut b);
+    /// assert!(b.is_empty());
+    /// assert_eq!(Vec::from(a), [(1, 'a'), (2, 'B'), (3, 'C')]);
+    /// ```
+    pub fn append(&mut self, other: &mut BinaryTree<K, V>) {
+        let other = core::mem::replace(other, Self::new());
+        self.merge_with(other, |_, _, theirs| theirs);
+    }
+
+    /// Moves all entries from `other` into `self`, combining the values of keys that are present
+    /// in both trees.
+    ///
+    /// For every such key, `f` is called exactly once with the key, the value from `self` and the
+    /// value from `other`, and its result is stored under the key from `self`. Both trees are
+    /// merged in linear time and the result is rebuilt perfectly balanced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut a = BinaryTree::new();
+    /// a.insert("apple", 1);
+    /// a.insert("pear", 2);
+    ///
+    /// let mut b = BinaryTree::new();
+    /// b.insert("pear", 3);
+    /// b.insert("plum", 4);
+    ///
+    /// a.merge_with(b, |_, ours, theirs| ours + theirs);
+    /// assert_eq!(Vec::from(a), [("apple", 1), ("pear", 5), ("plum", 4)]);
+    /// ```
+    pub fn merge_with<F>(&mut self, other: BinaryTree<K, V>, mut f: F)
+    where
+        F: FnMut(&K, V, V) -> V,
+    {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+
+        let mut merged = Vec::with_capacity(self.len + other.len);
+        let mut ours = core::mem::replace(self, Self::new()).into_iter().peekable();
+        let mut theirs = other.into_iter().peekable();
+        loop {
+            let ordering = match (ours.peek(), theirs.peek()) {
+                (Some((a, _)), Some((b, _))) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let entry = match ordering {
+                Ordering::Less => ours.next(),
+                Ordering::Greater => theirs.next(),
+                Ordering::Equal => ours.next().zip(theirs.next()).map(|((key, a), (_, b))| {
+                    let value = f(&key, a, b);
+                    (key, value)
+                }),
+            };
+            merged.extend(entry);
+        }
+
+        self.len = merged.len();
+        self.root = BinaryTreeNode::from_sorted(&mut merged.into_iter(), self.len);
+    }
+
+    /// Splits the tree in two at the given key, returning everything at or after it.
+    ///
+    /// Both halves are rebuilt perfectly balanced in linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in 1..=5 {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// let upper = tree.split_off(&3);
+    /// assert_eq!(tree.into_keys().collect::<Vec<_>>(), [1, 2]);
+    /// assert_eq!(upper.into_keys().collect::<Vec<_>>(), [3, 4, 5]);
+    /// ```
+    pub fn split_off<Q>(&mut self, key: &Q) -> BinaryTree<K, V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let len = self.len;
+        let left_len = self.rank(key);
+        if left_len == len {
+            return Self::new();
+        }
+        if left_len == 0 {
+            return core::mem::replace(self, Self::new());
+        }
+
+        let mut entries = core::mem::replace(self, Self::new()).into_iter();
+        self.root = BinaryTreeNode::from_sorted(&mut entries, left_len);
+        self.len = left_len;
+        BinaryTree {
+            root: BinaryTreeNode::from_sorted(&mut entries, len - left_len),
+            len: len - left_len,
+        }
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a leaf, or `0` for an
+    /// empty tree.
+    ///
+    /// Every node keeps track of the height of its subtree, so this runs in constant time. Since
+    /// the tree is kept balanced, the height never exceeds roughly `1.44 * log2(len + 2)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// assert_eq!(tree.height(), 0);
+    ///
+    /// for key in 0..7 {
+    ///     tree.insert(key, ());
+    /// }
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        BinaryTreeNode::height(&self.root)
+    }
+
+    /// Returns `true` if the heights of the two subtrees of every node differ by at most one.
+    ///
+    /// The heights are recomputed from scratch in a single post-order pass rather than taken from
+    /// the nodes, so this is meant as a diagnostic for tests and debug assertions. It runs in
+    /// linear time without recursing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree: BinaryTree<_, _> = (0..100).map(|key| (key, ())).collect();
+    /// assert!(tree.is_balanced());
+    /// ```
+    pub fn is_balanced(&self) -> bool {
+        // Nodes still to visit, flagged once their children have been pushed.
+        let mut stack = Vec::new();
+        // The heights of the subtrees that have been visited but whose parent has not.
+        let mut heights: Vec<usize> = Vec::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.push((root, false));
+        }
+
+        while let Some((node, expanded)) = stack.pop() {
+            let (left, right) = (&node.children.0, &node.children.1);
+            if !expanded {
+                stack.push((node, true));
+                stack.extend(right.as_deref().map(|right| (right, false)));
+                stack.extend(left.as_deref().map(|left| (left, false)));
+                continue;
+            }
+
+            let mut child_height = |child: &NodeChild<K, V>| match child {
+                Some(_) => heights.pop().unwrap_or(0),
+                None => 0,
+            };
+            let right = child_height(right);
+            let left = child_height(left);
+            if left.abs_diff(right) > 1 {
+                return false;
+            }
+            heights.push(1 + left.max(right));
+        }
+        true
+    }
+
+    /// Reshapes the tree in place so that its height is as small as possible, i.e. at most
+    /// `floor(log2(len)) + 1`.
+    ///
+    /// This uses the Day–Stout–Warren algorithm: the tree is first flattened into a vine of right
+    /// children and then folded back up with a series of left rotations. It runs in linear time
+    /// and reuses the existing nodes, so no entry is moved or reallocated. The tree is always kept
+    /// balanced, so this only trims the last few levels off a tree that is due for a lot of
+    /// lookups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [5, 8, 1, 7, 3, 12, 4, 10, 6, 9, 2, 11] {
+    ///     tree.insert(key, ());
+    /// }
+    /// assert_eq!(tree.height(), 5);
+    ///
+    /// tree.rebalance();
+    /// assert_eq!(tree.height(), 4);
+    /// assert!(tree.keys().copied().eq(1..=12));
+    /// ```
+    pub fn rebalance(&mut self) {
+        // Flatten the tree into a vine by rotating every left child up onto the right spine.
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            while node.children.0.is_some() {
+                BinaryTreeNode::rotate_right(node);
+            }
+            link = &mut node.children.1;
+        }
+
+        // Fold the surplus below the largest perfect tree into leaves, then halve the vine until
+        // only the root is left on it.
+        let mut perfect = (self.len + 1).next_power_of_two();
+        if perfect > self.len + 1 {
+            perfect /= 2;
+        }
+        let mut spine = perfect - 1;
+        BinaryTreeNode::compress(&mut self.root, self.len - spine);
+        while spine > 1 {
+            spine /= 2;
+            BinaryTreeNode::compress(&mut self.root, spine);
+        }
+
+        BinaryTreeNode::update_subtree(&mut self.root);
+    }
+
+    /// Returns the entry with the `n`-th smallest key, counting from zero, or [`None`] if the
+    /// tree holds no more than `n` entries.
+    ///
+    /// Every node keeps track of the size of its subtree, so this takes logarithmic time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// for key in [30, 10, 20] {
+    ///     tree.insert(key, ());
+    /// }
+    ///
+    /// assert_eq!(tree.select(0), Some((&10, &())));
+    /// assert_eq!(tree.select(2), Some((&30, &())));
+    /// assert_eq!(tree.select(3), None);
+    /// ```
+    pub fn select(&self, mut n: usize) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            let left = BinaryTreeNode::size(&current.children.0);
+            node = match n.cmp(&left) {
+                Ordering::Equal => return Some((&current.key, &current.value)),
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => {
+                    n -= left + 1;
+                    current.children.1.as_deref()
+                }
+            };
+        }
+        None
+    }
+}
+
+impl<K, V> BinaryTreeNode<K, V> {
+    /// Allocates a new leaf node using `alloc`.
+    fn new_boxed<F>(key: K, value: V, alloc: F) -> Result<Box<Self>, InsertAllocError<K, V>>
+    where
+        F: FnOnce(Self) -> Result<Box<Self>, Self>,
+    {
+        alloc(BinaryTreeNode {
+            key,
+            value,
+            height: 1,
+            size: 1,
+            children: (None, None),
+        })
+        .map_err(|node| InsertAllocError {
+            key: node.key,
+            value: node.value,
+        })
+    }
+
+    fn height(link: &NodeChild<K, V>) -> usize {
+        link.as_ref().map_or(0, |node| node.height)
+    }
+
+    fn size(link: &NodeChild<K, V>) -> usize {
+        link.as_ref().map_or(0, |node| node.size)
+    }
+
+    /// Recomputes the height and size of this node from those of its children.
+    fn update(&mut self) {
+        self.height = 1 + Self::height(&self.children.0).max(Self::height(&self.children.1));
+        self.size = 1 + Self::size(&self.children.0) + Self::size(&self.children.1);
+    }
+
+    /// Returns how much taller the left subtree is than the right subtree.
+    fn balance(&self) -> isize {
+        Self::height(&self.children.0) as isize - Self::height(&self.children.1) as isize
+    }
+
+    /// Rotates the subtree rooted at `node` to the left, making its right child the new root.
+    fn rotate_left(node: &mut Box<Self>) {
+        if let Some(mut right) = node.children.1.take() {
+            node.children.1 = right.children.0.take();
+            node.update();
+            core::mem::swap(node, &mut right);
+            node.children.0 = Some(right);
+            node.update();
+        }
+    }
+
+    /// Rotates the subtree rooted at `node` to the right, making its left child the new root.
+    fn rotate_right(node: &mut Box<Self>) {
+        if let Some(mut left) = node.children.0.take() {
+            node.children.0 = left.children.1.take();
+            node.update();
+            core::mem::swap(node, &mut left);
+            node.children.1 = Some(left);
+            node.update();
+        }
+    }
+
+    /// Rotates every other node of the first `2 * count` nodes on the right spine of the subtree
+    /// held by `link` to the left, halving the length of that stretch of the spine.
+    ///
+    /// Rotations only update the two nodes involved, so the nodes above them are left with stale
+    /// heights and sizes.
+    fn compress(mut link: &mut NodeChild<K, V>, count: usize) {
+        for _ in 0..count {
+            let node = match link {
+                Some(node) => node,
+                None => return,
+            };
+            Self::rotate_left(node);
+            link = &mut node.children.1;
+        }
+    }
+
+    /// Recomputes the height and size of every node in the subtree held by `link`.
+    fn update_subtree(link: &mut NodeChild<K, V>) {
+        if let Some(node) = link {
+            Self::update_subtree(&mut node.children.0);
+            Self::update_subtree(&mut node.children.1);
+            node.update();
+        }
+    }
+
+    /// Restores the AVL invariant at `node`, assuming it already holds for both of its subtrees
+    /// and that their heights differ by at most two.
+    fn rebalance(node: &mut Box<Self>) {
+        node.update();
+        let balance = node.balance();
+
+        if balance > 1 {
+            if let Some(left) = node.children.0.as_mut() {
+                if left.balance() < 0 {
+                    Self::rotate_left(left);
+                }
+            }
+            Self::rotate_right(node);
+        } else if balance < -1 {
+            if let Some(right) = node.children.1.as_mut() {
+                if right.balance() > 0 {
+                    Self::rotate_right(right);
+                }
+            }
+            Self::rotate_left(node);
+        }
+    }
+
+    /// Detaches the node held by `link`, putting its subtrees back in its place.
+    ///
+    /// If the node has two children, its in-order successor takes its place. The returned node
+    /// has no children.
+    fn unlink(link: &mut NodeChild<K, V>) -> NodeChild<K, V> {
+        let mut node = link.take()?;
+        *link = match (node.children.0.take(), node.children.1.take()) {
+            (None, None) => None,
+            (Some(child), None) | (None, Some(child)) => Some(child),
+            (Some(left), Some(right)) => {
+                let mut right = Some(right);
+                let mut successor = Self::unlink_min(&mut right)?;
+                successor.children = (Some(left), right);
+                Self::rebalance(&mut successor);
+                Some(successor)
+            }
+        };
+        Some(node)
+    }
+
+    /// Detaches the node with the smallest key from the subtree held by `link`.
+    fn unlink_min(link: &mut NodeChild<K, V>) -> NodeChild<K, V> {
+        let node = link.as_mut()?;
+        if node.children.0.is_some() {
+            let min = Self::unlink_min(&mut node.children.0);
+            Self::rebalance(node);
+            return min;
+        }
+
+        let mut node = link.take()?;
+        *link = node.children.1.take();
+        Some(node)
+    }
+
+    /// Detaches the node with the largest key from the subtree held by `link`.
+    fn unlink_max(link: &mut NodeChild<K, V>) -> NodeChild<K, V> {
+        let node = link.as_mut()?;
+        if node.children.1.is_some() {
+            let max = Self::unlink_max(&mut node.children.1);
+            Self::rebalance(node);
+            return max;
+        }
+
+        let mut node = link.take()?;
+        *link = node.children.0.take();
+        Some(node)
+    }
+
+    /// Returns the node with the `n`-th smallest key in the subtree held by `link`.
+    fn nth_mut(mut link: &mut NodeChild<K, V>, mut n: usize) -> Option<&mut Self> {
+        while let Some(node) = link {
+            let left = Self::size(&node.children.0);
+            link = match n.cmp(&left) {
+                Ordering::Less => &mut node.children.0,
+                Ordering::Equal => return Some(node),
+                Ordering::Greater => {
+                    n -= left + 1;
+                    &mut node.children.1
+                }
+            };
+        }
+        None
+    }
+
+    /// Inserts `new` into the subtree held by `link` so that it becomes the `n`-th smallest node,
+    /// rebalancing every node on the way back up. The recursion is bounded by the height of the
+    /// tree, which the AVL invariant keeps logarithmic.
+    fn insert_nth(link: &mut NodeChild<K, V>, n: usize, new: Box<Self>) {
+        let node = match link {
+            Some(node) => node,
+            None => {
+                *link = Some(new);
+                return;
+            }
+        };
+
+        let left = Self::size(&node.children.0);
+        if n <= left {
+            Self::insert_nth(&mut node.children.0, n, new);
+        } else {
+            Self::insert_nth(&mut node.children.1, n - left - 1, new);
+        }
+        Self::rebalance(node);
+    }
+
+    /// Removes the node with the `n`-th smallest key from the subtree held by `link`,
+    /// rebalancing every node on the way back up.
+    fn remove_nth(link: &mut NodeChild<K, V>, n: usize) -> NodeChild<K, V> {
+        let node = link.as_mut()?;
+        let left = Self::size(&node.children.0);
+        let removed = match n.cmp(&left) {
+            Ordering::Less => Self::remove_nth(&mut node.children.0, n),
+            Ordering::Greater => Self::remove_nth(&mut node.children.1, n - left - 1),
+            Ordering::Equal => return Self::unlink(link),
+        };
+
+        if removed.is_some() {
+            Self::rebalance(node);
+        }
+        removed
+    }
+
+    /// Builds a balanced subtree out of the next `len` entries of `iter`, which must be sorted.
+    fn from_sorted<I>(iter: &mut I, len: usize) -> NodeChild<K, V>
+    where
+        I: Iterator<Item = (K, V)>,
+    {
+        if len == 0 {
+            return None;
+        }
+
+        let left = Self::from_sorted(iter, len / 2);
+        let (key, value) = iter.next()?;
+        let right = Self::from_sorted(iter, len - len / 2 - 1);
+
+        let mut node = BinaryTreeNode {
+            key,
+            value,
+            height: 0,
+            size: 0,
+            children: (left, right),
+        };
+        node.update();
+        Some(Box::new(node))
+    }
+
+    /// Rebuilds the subtree held by `link` node for node, transforming every value with `f` in
+    /// ascending key order.
+    fn map_values<U, F>(link: NodeChild<K, V>, f: &mut F) -> NodeChild<K, U>
+    where
+        F: FnMut(&K, V) -> U,
+    {
+        let node = *link?;
+        let BinaryTreeNode {
+            key,
+            value,
+            height,
+            size,
+            children: (left, right),
+        } = node;
+
+        let left = BinaryTreeNode::map_values(left, f);
+        let value = f(&key, value);
+        let right = BinaryTreeNode::map_values(right, f);
+
+        Some(Box::new(BinaryTreeNode {
+            key,
+            value,
+            height,
+            size,
+            children: (left, right),
+        }))
+    }
+
+    /// Frees every node of the subtree held by `link` without recursing.
+    ///
+    /// Whenever the current node has a left child, it is rotated to the right so that the left
+    /// child takes its place. Once there is no left child, the node is freed and its right child
+    /// takes its place instead. Each rotation moves one node off the left spine for good, so this
+    /// runs in linear time and constant space regardless of the shape of the tree.
+    fn drop_subtree(mut link: NodeChild<K, V>) {
+        while let Some(mut node) = link {
+            link = if let Some(mut left) = node.children.0.take() {
+                node.children.0 = left.children.1.take();
+                left.children.1 = Some(node);
+                Some(left)
+            } else {
+                node.children.1.take()
+            };
+        }
+    }
+}
+
+impl<K, V> BinaryTreeNode<K, V>
+where
+    K: Ord,
+{
+    /// Appends the parts of the subtree rooted at `node` that lie between `start` and `end` to
+    /// `pieces` in order.
+    ///
+    /// Once a node is known to be within the range, its left subtree can only fall short of
+    /// `start` and its right subtree can only exceed `end`, so the other bound is dropped. A
+    /// subtree without any remaining bounds is pushed whole.
+    fn range_pieces<'a, Q>(
+        node: Option<&'a Self>,
+        start: Bound<&Q>,
+        end: Bound<&Q>,
+        pieces: &mut VecDeque<Piece<'a, K, V>>,
+    ) where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = match (node, start, end) {
+            (None, _, _) => return,
+            (Some(node), Bound::Unbounded, Bound::Unbounded) => {
+                pieces.push_back(Piece::Subtree(node));
+                return;
+            }
+            (Some(node), _, _) => node,
+        };
+
+        let key = node.key.borrow();
+        if !after_start(key, start) {
+            Self::range_pieces(node.children.1.as_deref(), start, end, pieces);
+        } else if !before_end(key, end) {
+            Self::range_pieces(node.children.0.as_deref(), start, end, pieces);
+        } else {
+            Self::range_pieces(node.children.0.as_deref(), start, Bound::Unbounded, pieces);
+            pieces.push_back(Piece::Entry(&node.key, &node.value));
+            Self::range_pieces(node.children.1.as_deref(), Bound::Unbounded, end, pieces);
+        }
+    }
+
+    /// Appends the parts of the subtree rooted at `node` that lie between `start` and `end` to
+    /// `pieces` in order. See [`BinaryTreeNode::range_pieces`].
+    fn range_pieces_mut<'a, Q>(
+        node: Option<&'a mut Self>,
+        start: Bound<&Q>,
+        end: Bound<&Q>,
+        pieces: &mut VecDeque<MutPiece<'a, K, V>>,
+    ) where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = match (node, start, end) {
+            (None, _, _) => return,
+            (Some(node), Bound::Unbounded, Bound::Unbounded) => {
+                pieces.push_back(MutPiece::Subtree(node));
+                return;
+            }
+            (Some(node), _, _) => node,
+        };
+
+        let BinaryTreeNode {
+            key,
+            value,
+            children: (left, right),
+            ..
+        } = node;
+        if !after_start((*key).borrow(), start) {
+            Self::range_pieces_mut(right.as_deref_mut(), start, end, pieces);
+        } else if !before_end((*key).borrow(), end) {
+            Self::range_pieces_mut(left.as_deref_mut(), start, end, pieces);
+        } else {
+            Self::range_pieces_mut(left.as_deref_mut(), start, Bound::Unbounded, pieces);
+            pieces.push_back(MutPiece::Entry(key, value));
+            Self::range_pieces_mut(right.as_deref_mut(), Bound::Unbounded, end, pieces);
+        }
+    }
+
+    /// Removes the node with the given key from the subtree held by `link`, rebalancing every
+    /// node on the way back up.
+    fn remove<Q>(link: &mut NodeChild<K, V>, key: &Q) -> NodeChild<K, V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = link.as_mut()?;
+        let child = match key.cmp(node.key.borrow()) {
+            Ordering::Less => &mut node.children.0,
+            Ordering::Greater => &mut node.children.1,
+            Ordering::Equal => return Self::unlink(link),
+        };
+
+        let removed = Self::remove(child, key);
+        if removed.is_some() {
+            Self::rebalance(node);
+        }
+        removed
+    }
+}
+
+/// Returns the bounds of `range`, panicking if they are inverted like [`BTreeMap::range`] does.
+fn check_range<Q, R>(range: &R) -> (Bound<&Q>, Bound<&Q>)
+where
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    let (start, end) = (range.start_bound(), range.end_bound());
+    match (start, end) {
+        (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+            panic!("range start and end are equal and excluded in BinaryTree")
+        }
+        (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+            if s > e =>
+        {
+            panic!("range start is greater than range end in BinaryTree")
+        }
+        _ => (start, end),
+    }
+}
+
+/// Returns `true` if `key` does not lie before `start`.
+fn after_start<Q: Ord + ?Sized>(key: &Q, start: Bound<&Q>) -> bool {
+    match start {
+        Bound::Included(start) => key >= start,
+        Bound::Excluded(start) => key > start,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Returns `true` if `key` does not lie beyond `end`.
+fn before_end<Q: Ord + ?Sized>(key: &Q, end: Bound<&Q>) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+impl<K, V> Drop for BinaryTree<K, V> {
+    fn drop(&mut self) {
+        BinaryTreeNode::drop_subtree(self.root.take());
+    }
+}
+
+impl<K, V> From<BTreeMap<K, V>> for BinaryTree<K, V> {
+    /// Moves the entries of a [`BTreeMap`] into a [`BinaryTree`].
+    ///
+    /// Since the entries of a [`BTreeMap`] are already sorted, the resulting tree is built
+    /// perfectly balanced in linear time.
+    fn from(map: BTreeMap<K, V>) -> Self {
+        let len = map.len();
+        let root = BinaryTreeNode::from_sorted(&mut map.into_iter(), len);
+        Self { root, len }
+    }
+}
+
+impl<K: Ord, V, const N: usize> From<[(K, V); N]> for BinaryTree<K, V> {
+    /// Builds a tree out of an array of entries. If a key appears more than once, the last value
+    /// wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let tree = BinaryTree::from([(2, "b"), (1, "a"), (2, "c")]);
+    /// assert_eq!(tree.len(), 2);
+    /// assert_eq!(tree.get(&2), Some(&"c"));
+    /// ```
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BinaryTree<K, V> {
+    /// Inserts the entries in order, so that the last value wins if a key appears more than
+    /// once.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BinaryTree<K, V> {
+    /// Builds a tree by inserting the entries in order, so that the last value wins if a key
+    /// appears more than once.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K, V> From<BinaryTree<K, V>> for Vec<(K, V)> {
+    /// Moves the entries of a [`BinaryTree`] into a [`Vec`], sorted by key.
+    fn from(tree: BinaryTree<K, V>) -> Self {
+        tree.into_iter().collect()
+    }
+}
+
+/// A part of a tree that an [`Iter`] has not visited yet.
+enum Piece<'a, K, V> {
+    Entry(&'a K, &'a V),
+    Subtree(&'a BinaryTreeNode<K, V>),
+}
+
+impl<'a, K, V> Piece<'a, K, V> {
+    /// Removes and returns the first entry of `pieces`, splitting up subtrees as needed.
+    fn pop_front(pieces: &mut VecDeque<Self>) -> Option<(&'a K, &'a V)> {
+        loop {
+            match pieces.pop_front()? {
+                Piece::Entry(key, value) => return Some((key, value)),
+                Piece::Subtree(node) => {
+                    if let Some(right) = node.children.1.as_deref() {
+                        pieces.push_front(Piece::Subtree(right));
+                    }
+                    pieces.push_front(Piece::Entry(&node.key, &node.value));
+                    if let Some(left) = node.children.0.as_deref() {
+                        pieces.push_front(Piece::Subtree(left));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K, V> Piece<'a, K, V> {
+    /// Removes and returns the last entry of `pieces`, splitting up subtrees as needed.
+    fn pop_back(pieces: &mut VecDeque<Self>) -> Option<(&'a K, &'a V)> {
+        loop {
+            match pieces.pop_back()? {
+                Piece::Entry(key, value) => return Some((key, value)),
+                Piece::Subtree(node) => {
+                    if let Some(left) = node.children.0.as_deref() {
+                        pieces.push_back(Piece::Subtree(left));
+                    }
+                    pieces.push_back(Piece::Entry(&node.key, &node.value));
+                    if let Some(right) = node.children.1.as_deref() {
+                        pieces.push_back(Piece::Subtree(right));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Clone for Piece<'_, K, V> {
+    fn clone(&self) -> Self {
+        match *self {
+            Piece::Entry(key, value) => Piece::Entry(key, value),
+            Piece::Subtree(node) => Piece::Subtree(node),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`BinaryTree`], sorted by key.
+///
+/// This `struct` is created by [`BinaryTree::iter`].
+pub struct Iter<'a, K, V> {
+    /// The unvisited parts of the tree in order. Subtrees are only split up once the iterator
+    /// reaches them, which keeps the number of pieces proportional to the height of the tree.
+    pieces: VecDeque<Piece<'a, K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = Piece::pop_front(&mut self.pieces)?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = Piece::pop_back(&mut self.pieces)?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            pieces: self.pieces.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> core::fmt::Debug for Iter<'_, K, V>
+where
+    K: core::fmt::Debug,
+    V: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BinaryTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Implements the traits shared by the iterators that walk the shape of a [`BinaryTree`].
+macro_rules! traversal_impls {
+    ($name:ident, $field:ident) => {
+        impl<K, V> ExactSizeIterator for $name<'_, K, V> {}
+
+        impl<K, V> Clone for $name<'_, K, V> {
+            fn clone(&self) -> Self {
+                Self {
+                    $field: self.$field.clone(),
+                    len: self.len,
+                }
+            }
+        }
+
+        impl<K, V> core::fmt::Debug for $name<'_, K, V>
+        where
+            K: core::fmt::Debug,
+            V: core::fmt::Debug,
+        {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_list().entries(self.clone()).finish()
+            }
+        }
+    };
+}
+
+/// An iterator over the entries of a [`BinaryTree`] in pre-order.
+///
+/// This `struct` is created by [`BinaryTree::iter_preorder`].
+pub struct PreOrder<'a, K, V> {
+    /// The roots of the subtrees that are still to be visited, the next one on top.
+    stack: Vec<&'a BinaryTreeNode<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for PreOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.1.as_deref());
+        self.stack.extend(node.children.0.as_deref());
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+traversal_impls!(PreOrder, stack);
+
+/// An iterator over the entries of a [`BinaryTree`] in post-order.
+///
+/// This `struct` is created by [`BinaryTree::iter_postorder`].
+pub struct PostOrder<'a, K, V> {
+    /// The nodes that are still to be visited, the next one on top. Each node is marked with
+    /// whether its subtrees have already been pushed on top of it.
+    stack: Vec<(&'a BinaryTreeNode<K, V>, bool)>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for PostOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                self.len -= 1;
+                return Some((&node.key, &node.value));
+            }
+
+            self.stack.push((node, true));
+            self.stack
+                .extend(node.children.1.as_deref().map(|right| (right, false)));
+            self.stack
+                .extend(node.children.0.as_deref().map(|left| (left, false)));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+traversal_impls!(PostOrder, stack);
+
+/// An iterator over the entries of a [`BinaryTree`] in level-order.
+///
+/// This `struct` is created by [`BinaryTree::iter_levelorder`].
+pub struct LevelOrder<'a, K, V> {
+    /// The nodes that are still to be visited, the next one at the front.
+    queue: VecDeque<&'a BinaryTreeNode<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for LevelOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children.0.as_deref());
+        self.queue.extend(node.children.1.as_deref());
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+traversal_impls!(LevelOrder, queue);
+
+/// Displays the shape of a [`BinaryTree`], with one entry per line.
+///
+/// This `struct` is created by [`BinaryTree::display_structure`].
+pub struct DisplayStructure<'a, K, V> {
+    tree: &'a BinaryTree<K, V>,
+}
+
+impl<K, V> core::fmt::Display for DisplayStructure<'_, K, V>
+where
+    K: core::fmt::Debug,
+    V: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let root = match self.tree.root.as_deref() {
+            Some(root) => root,
+            None => return f.write_str("(empty)"),
+        };
+
+        // Every node on the stack comes with the start of its own line and the indentation of the
+        // lines of its children.
+        let mut stack = alloc::vec![(root, String::new(), String::new())];
+        let mut first = true;
+        while let Some((node, head, indent)) = stack.pop() {
+            if !first {
+                f.write_str("\n")?;
+            }
+            first = false;
+            write!(f, "{head}{:?}: {:?}", node.key, node.value)?;
+
+            let children = [(&node.children.0, 'L'), (&node.children.1, 'R')];
+            let mut children = children
+                .iter()
+                .filter_map(|(child, side)| Some((child.as_deref()?, *side)))
+                .peekable();
+            let mut pending = Vec::new();
+            while let Some((child, side)) = children.next() {
+                let (branch, continuation) = if children.peek().is_some() {
+                    ("├──", "│   ")
+                } else {
+                    ("└──", "    ")
+                };
+                pending.push((
+                    child,
+                    format!("{indent}{branch} {side} "),
+                    format!("{indent}{continuation}"),
+                ));
+            }
+            stack.extend(pending.into_iter().rev());
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> core::fmt::Debug for DisplayStructure<'_, K, V>
+where
+    K: core::fmt::Debug,
+    V: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(self, f)
+    }
+}
+
+/// An iterator over the keys of a [`BinaryTree`] in ascending order.
+///
+/// This `struct` is created by [`BinaryTree::keys`].
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V> core::fmt::Debug for Keys<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the values of a [`BinaryTree`], sorted by key.
+///
+/// This `struct` is created by [`BinaryTree::values`].
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V: core::fmt::Debug> core::fmt::Debug for Values<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A part of a tree that an [`IterMut`] has not visited yet.
+enum MutPiece<'a, K, V> {
+    Entry(&'a K, &'a mut V),
+    Subtree(&'a mut BinaryTreeNode<K, V>),
+}
+
+impl<'a, K, V> MutPiece<'a, K, V> {
+    /// Removes and returns the first entry of `pieces`, splitting up subtrees as needed.
+    fn pop_front(pieces: &mut VecDeque<Self>) -> Option<(&'a K, &'a mut V)> {
+        loop {
+            match pieces.pop_front()? {
+                MutPiece::Entry(key, value) => return Some((key, value)),
+                MutPiece::Subtree(node) => {
+                    let BinaryTreeNode {
+                        key,
+                        value,
+                        children: (left, right),
+                        ..
+                    } = node;
+
+                    if let Some(right) = right.as_deref_mut() {
+                        pieces.push_front(MutPiece::Subtree(right));
+                    }
+                    pieces.push_front(MutPiece::Entry(key, value));
+                    if let Some(left) = left.as_deref_mut() {
+                        pieces.push_front(MutPiece::Subtree(left));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K, V> MutPiece<'a, K, V> {
+    /// Removes and returns the last entry of `pieces`, splitting up subtrees as needed.
+    fn pop_back(pieces: &mut VecDeque<Self>) -> Option<(&'a K, &'a mut V)> {
+        loop {
+            match pieces.pop_back()? {
+                MutPiece::Entry(key, value) => return Some((key, value)),
+                MutPiece::Subtree(node) => {
+                    let BinaryTreeNode {
+                        key,
+                        value,
+                        children: (left, right),
+                        ..
+                    } = node;
+
+                    if let Some(left) = left.as_deref_mut() {
+                        pieces.push_back(MutPiece::Subtree(left));
+                    }
+                    pieces.push_back(MutPiece::Entry(key, value));
+                    if let Some(right) = right.as_deref_mut() {
+                        pieces.push_back(MutPiece::Subtree(right));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A mutable iterator over the entries of a [`BinaryTree`], sorted by key.
+///
+/// This `struct` is created by [`BinaryTree::iter_mut`].
+pub struct IterMut<'a, K, V> {
+    /// The unvisited parts of the tree in order. Subtrees are only split up once the iterator
+    /// reaches them, which keeps the number of pieces proportional to the height of the tree.
+    pieces: VecDeque<MutPiece<'a, K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = MutPiece::pop_front(&mut self.pieces)?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = MutPiece::pop_back(&mut self.pieces)?;
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<K, V> core::fmt::Debug for IterMut<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IterMut")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut BinaryTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over a sub-range of the entries of a [`BinaryTree`], sorted by key.
+///
+/// This `struct` is created by [`BinaryTree::range`].
+pub struct Range<'a, K, V> {
+    pieces: VecDeque<Piece<'a, K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Piece::pop_front(&mut self.pieces)
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Piece::pop_back(&mut self.pieces)
+    }
+}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            pieces: self.pieces.clone(),
+        }
+    }
+}
+
+impl<K, V> core::fmt::Debug for Range<'_, K, V>
+where
+    K: core::fmt::Debug,
+    V: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A mutable iterator over a sub-range of the entries of a [`BinaryTree`], sorted by key.
+///
+/// This `struct` is created by [`BinaryTree::range_mut`].
+pub struct RangeMut<'a, K, V> {
+    pieces: VecDeque<MutPiece<'a, K, V>>,
+}
+
+impl<'a, K, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        MutPiece::pop_front(&mut self.pieces)
+    }
+}
+
+impl<K, V> DoubleEndedIterator for RangeMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        MutPiece::pop_back(&mut self.pieces)
+    }
+}
+
+impl<K, V> core::fmt::Debug for RangeMut<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RangeMut").finish_non_exhaustive()
+    }
+}
+
+/// A cursor over the entries of a [`BinaryTree`] that can modify the tree while it moves.
+///
+/// Besides pointing at an entry, the cursor can point at a "ghost" position that sits between the
+/// last and the first entry. Moving forwards from the last entry or backwards from the first
+/// entry leads to the ghost position, and moving on from there wraps around.
+///
+/// This `struct` is created by [`BinaryTree::cursor_mut`].
+pub struct CursorMut<'a, K, V> {
+    tree: &'a mut BinaryTree<K, V>,
+    /// The nodes from the root down to the current entry, or nothing at the ghost position.
+    path: Vec<NodePtr<K, V>>,
+    /// The position of the current entry in ascending key order, or the length of the tree at the
+    /// ghost position.
+    index: usize,
+}
+
+impl<'a, K, V> CursorMut<'a, K, V>
+where
+    K: Ord,
+{
+    /// Returns a reference to the key of the current entry, or [`None`] at the ghost position.
+    pub fn key(&self) -> Option<&K> {
+        // SAFETY: Every pointer on the path points at a node of the tree, which the cursor
+        // borrows mutably. Nodes are only moved or freed through `&mut self`, after which the
+        // path is rebuilt, so the node is still alive for as long as `self` is borrowed.
+        self.path.last().map(|node| unsafe { &node.as_ref().key })
+    }
+
+    /// Returns a mutable reference to the value of the current entry, or [`None`] at the ghost
+    /// position.
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        // SAFETY: See `key`. The returned reference borrows `self` mutably, so no other
+        // reference into the tree can be created while it lives.
+        self.path
+            .last_mut()
+            .map(|node| unsafe { &mut node.as_mut().value })
+    }
+
+    /// Moves the cursor to the next entry in ascending key order.
+    ///
+    /// Moves to the ghost position when the cursor is at the last entry, and to the first entry
+    /// when the cursor is at the ghost position.
+    pub fn move_next(&mut self) {
+        if self.path.is_empty() {
+            self.seek(0);
+        } else {
+            self.step(right_child, left_child);
+            self.index = if self.path.is_empty() {
+                self.tree.len
+            } else {
+                self.index + 1
+            };
+        }
+    }
+
+    /// Moves the cursor to the previous entry in ascending key order.
+    ///
+    /// Moves to the ghost position when the cursor is at the first entry, and to the last entry
+    /// when the cursor is at the ghost position.
+    pub fn move_prev(&mut self) {
+        if self.path.is_empty() {
+            self.seek(self.tree.len.wrapping_sub(1));
+        } else {
+            self.step(left_child, right_child);
+            self.index = if self.path.is_empty() {
+                self.tree.len
+            } else {
+                self.index - 1
+            };
+        }
+    }
+
+    /// Removes the current entry from the tree and moves the cursor to the next entry.
+    ///
+    /// Returns [`None`] and does nothing at the ghost position.
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        if self.path.is_empty() {
+            return None;
+        }
+
+        self.path.clear();
+        let node = BinaryTreeNode::remove_nth(&mut self.tree.root, self.index)?;
+        self.tree.len -= 1;
+        self.seek(self.index);
+        Some((node.key, node.value))
+    }
+
+    /// Inserts an entry into the tree just before the current entry, or as the last entry at the
+    /// ghost position. The cursor keeps pointing at the current entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key does not lie strictly between the previous and the current key.
+    pub fn insert_before(&mut self, key: K, value: V) {
+        let after_prev = match self.index.checked_sub(1) {
+            Some(prev) => self.key_at(prev) < &key,
+            None => true,
+        };
+        let before_current = match self.key() {
+            Some(current) => &key < current,
+            None => true,
+        };
+        assert!(
+            after_prev && before_current,
+            "key does not fit before the cursor position"
+        );
+
+        self.path.clear();
+        self.tree.insert(key, value);
+        self.seek(self.index + 1);
+    }
+
+    /// Inserts an entry into the tree just after the current entry, or as the first entry at the
+    /// ghost position. The cursor keeps pointing at the current entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key does not lie strictly between the current and the next key.
+    pub fn insert_after(&mut self, key: K, value: V) {
+        let ghost = self.path.is_empty();
+        let next = if ghost { 0 } else { self.index + 1 };
+        let after_current = match self.key() {
+            Some(current) => current < &key,
+            None => true,
+        };
+        let before_next = next == self.tree.len || &key < self.key_at(next);
+        assert!(
+            after_current && before_next,
+            "key does not fit after the cursor position"
+        );
+
+        self.path.clear();
+        self.tree.insert(key, value);
+        self.seek(if ghost { self.tree.len } else { self.index });
+    }
+
+    /// Returns the key of the entry at `index`, which must exist.
+    fn key_at(&self, index: usize) -> &K {
+        match self.tree.select(index) {
+            Some((key, _)) => key,
+            None => unreachable!("cursor index out of bounds"),
+        }
+    }
+
+    /// Points the cursor at the entry at `index`, or at the ghost position if there is none.
+    fn seek(&mut self, index: usize) {
+        self.path.clear();
+        self.index = index.min(self.tree.len);
+
+        let mut n = self.index;
+        let mut node = self.tree.root.as_deref_mut().map(ptr::NonNull::from);
+        while let Some(current) = node {
+            self.path.push(current);
+            // SAFETY: `current` was just derived from the tree, which the cursor borrows mutably.
+            let left = BinaryTreeNode::size(unsafe { &current.as_ref().children.0 });
+            node = match n.cmp(&left) {
+                Ordering::Equal => return,
+                // SAFETY: As above.
+                Ordering::Less => unsafe { left_child(current) },
+                Ordering::Greater => {
+                    n -= left + 1;
+                    // SAFETY: As above.
+                    unsafe { right_child(current) }
+                }
+            };
+        }
+        self.path.clear();
+    }
+
+    /// Moves the cursor to the adjacent entry on the `forward` side. That is the closest entry
+    /// of the subtree on the `forward` side if there is one, and otherwise the closest ancestor
+    /// whose `back` subtree contains the current entry.
+    fn step(&mut self, forward: ChildFn<K, V>, back: ChildFn<K, V>) {
+        // SAFETY: Every pointer on the path was derived from the tree, which the cursor borrows
+        // mutably, and points at a node that has not been moved or freed since. References
+        // returned by `key` and `value_mut` borrow the cursor, so none of them are alive.
+        unsafe {
+            if let Some(mut node) = self.path.last().and_then(|&node| forward(node)) {
+                self.path.push(node);
+                while let Some(child) = back(node) {
+                    self.path.push(child);
+                    node = child;
+                }
+                return;
+            }
+
+            while let Some(child) = self.path.pop() {
+                match self.path.last() {
+                    Some(&parent) if back(parent) == Some(child) => return,
+                    Some(_) => {}
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
+/// A pointer to a node that a [`CursorMut`] keeps on its path.
+type NodePtr<K, V> = ptr::NonNull<BinaryTreeNode<K, V>>;
+
+/// Returns a pointer to one of the children of a node, see [`left_child`] and [`right_child`].
+type ChildFn<K, V> = unsafe fn(NodePtr<K, V>) -> Option<NodePtr<K, V>>;
+
+/// Returns a pointer to the left child of `node`.
+///
+/// # Safety
+///
+/// `node` must point at a live node that is not referenced anywhere else.
+unsafe fn left_child<K, V>(node: NodePtr<K, V>) -> Option<NodePtr<K, V>> {
+    (*node.as_ptr())
+        .children
+        .0
+        .as_deref_mut()
+        .map(ptr::NonNull::from)
+}
+
+/// Returns a pointer to the right child of `node`.
+///
+/// # Safety
+///
+/// `node` must point at a live node that is not referenced anywhere else.
+unsafe fn right_child<K, V>(node: NodePtr<K, V>) -> Option<NodePtr<K, V>> {
+    (*node.as_ptr())
+        .children
+        .1
+        .as_deref_mut()
+        .map(ptr::NonNull::from)
+}
+
+impl<K, V> core::fmt::Debug for CursorMut<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CursorMut")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An owning iterator over the entries of a [`BinaryTree`], sorted by key.
+///
+/// Nodes are freed as their entries are yielded, and any entries left over when the iterator is
+/// dropped are freed along with it.
+///
+/// This `struct` is created by the [`into_iter`](IntoIterator::into_iter) method on
+/// [`BinaryTree`].
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    root: NodeChild<K, V>,
+    len: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = BinaryTreeNode::unlink_min(&mut self.root)?;
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = BinaryTreeNode::unlink_max(&mut self.root)?;
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        BinaryTreeNode::drop_subtree(self.root.take());
+    }
+}
+
+impl<K, V> IntoIterator for BinaryTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consumes the tree, returning an iterator over its entries sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::binary_tree::BinaryTree;
+    ///
+    /// let mut tree = BinaryTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    ///
+    /// for (key, value) in tree {
+    ///     println!("{key}: {value}");
+    /// }
+    /// ```
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter {
+            root: self.root.take(),
+            len: core::mem::take(&mut self.len),
+        }
+    }
+}
+
+/// An owning iterator over the keys of a [`BinaryTree`] in ascending order.
+///
+/// This `struct` is created by [`BinaryTree::into_keys`].
+#[derive(Debug)]
+pub struct IntoKeys<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoKeys<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+/// An owning iterator over the values of a [`BinaryTree`], sorted by key.
+///
+/// This `struct` is created by [`BinaryTree::into_values`].
+#[derive(Debug)]
+pub struct IntoValues<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoValues<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+/// An iterator that removes the entries of a [`BinaryTree`] matching a predicate, in ascending
+/// key order.
+///
+/// This `struct` is created by [`BinaryTree::extract_if`].
+pub struct ExtractIf<'a, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    tree: &'a mut BinaryTree<K, V>,
+    /// The position of the next entry to visit in ascending key order. Every entry before it has
+    /// been kept.
+    index: usize,
+    pred: F,
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = BinaryTreeNode::nth_mut(&mut self.tree.root, self.index) {
+            if (self.pred)(&node.key, &mut node.value) {
+                let node = BinaryTreeNode::remove_nth(&mut self.tree.root, self.index)?;
+                self.tree.len -= 1;
+                return Some((node.key, node.value));
+            }
+            self.index += 1;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.tree.len - self.index))
+    }
+}
+
+impl<K, V, F> core::fmt::Debug for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExtractIf").finish_non_exhaustive()
+    }
+}
+
+/// The error returned by [`BinaryTree::try_insert_alloc`] when a new node could not be allocated.
+///
+/// The key and value that could not be inserted are handed back to the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertAllocError<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K, V> InsertAllocError<K, V> {
+    /// Returns a reference to the key that could not be inserted.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns a reference to the value that could not be inserted.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Consumes the error, returning the key and value that could not be inserted.
+    pub fn into_inner(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<K, V> core::fmt::Display for InsertAllocError<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("memory allocation failed while inserting into the binary tree")
+    }
+}
+
+/// The error returned by [`BinaryTree::try_insert`] when the key is already present.
+///
+/// The rejected value is handed back to the caller along with the entry that is already
+/// stored in the tree.
+#[derive(Debug, PartialEq, Eq)]
+pub struct OccupiedError<'a, K, V> {
+    key: &'a K,
+    existing: &'a mut V,
+    value: V,
+}
+
+impl<'a, K, V> OccupiedError<'a, K, V> {
+    /// Returns a reference to the key stored in the tree.
+    pub fn key(&self) -> &'a K {
+        self.key
+    }
+
+    /// Returns a reference to the value stored in the tree.
+    pub fn existing(&self) -> &V {
+        self.existing
+    }
+
+    /// Returns a mutable reference to the value stored in the tree.
+    pub fn existing_mut(&mut self) -> &mut V {
+        self.existing
+    }
+
+    /// Converts the error into a mutable reference to the value stored in the tree, with the
+    /// lifetime of the tree itself.
+    pub fn into_existing(self) -> &'a mut V {
+        self.existing
+    }
+
+    /// Returns a reference to the value that was rejected.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Consumes the error, returning the value that was rejected.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
+
+impl<K, V> core::fmt::Display for OccupiedError<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("key is already present in the binary tree")
+    }
+}
+
+/// Moves `value` onto the heap, returning it back if the allocator fails.
+fn try_box<T>(value: T) -> Result<Box<T>, T> {
+    let layout = Layout::new::<T>();
+    if layout.size() == 0 {
+        return Ok(Box::new(value));
+    }
+
+    // SAFETY: `layout` has a non-zero size, which is the only requirement of `alloc`.
+    let ptr = unsafe { alloc::alloc::alloc(layout) }.cast::<T>();
+    if ptr.is_null() {
+        return Err(value);
+    }
+
+    // SAFETY: `ptr` is non-null and was allocated by the global allocator with the layout of
+    // `T`, so it is valid for writes and properly aligned. Writing `value` initialises it, which
+    // makes it valid to hand over to `Box::from_raw` as per `Box`'s memory layout guarantees.
+    unsafe {
+        ptr.write(value);
+        Ok(Box::from_raw(ptr))
+    }
+}
+
+impl<K, Q, V> core::ops::Index<&Q> for BinaryTree<K, V>
+where
+    K: Ord + core::borrow::Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the binary tree.
+    fn index(&self, index: &Q) -> &Self::Output {
+        self.get(index)
+            .expect("Key is not present in the binary tree")
+    }
+}
+
+impl<K, Q, V> core::ops::IndexMut<&Q> for BinaryTree<K, V>
+where
+    K: Ord + core::borrow::Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    /// Returns a mutable reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the binary tree.
+    fn index_mut(&mut self, index: &Q) -> &mut Self::Output {
+        self.get_mut(index)
+            .expect("Key is not present in the binary tree")
+    }
+}
+
+impl<K, V> PartialEq for BinaryTree<K, V>
+where
+    K: PartialEq,
+    V: PartialEq,
+{
+    /// Two trees are equal if they contain the same entries, regardless of their shape.
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K, V> Eq for BinaryTree<K, V>
+where
+    K: Eq,
+    V: Eq,
+{
+}
+
+impl<K, V> PartialOrd for BinaryTree<K, V>
+where
+    K: PartialOrd,
+    V: PartialOrd,
+{
+    /// Compares the entries of both trees lexicographically in ascending key order.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<K, V> Ord for BinaryTree<K, V>
+where
+    K: Ord,
+    V: Ord,
+{
+    /// Compares the entries of both trees lexicographically in ascending key order.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V> serde::Serialize for BinaryTree<K, V>
+where
+    K: serde::Serialize,
+    V: serde::Serialize,
+{
+    /// Serializes the tree as a map with its entries in ascending key order.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for BinaryTree<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    /// Deserializes a map into a tree by inserting its entries in order, so that the last value
+    /// wins if a key appears more than once.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct TreeVisitor<K, V>(core::marker::PhantomData<BinaryTree<K, V>>);
+
+        impl<'de, K, V> serde::de::Visitor<'de> for TreeVisitor<K, V>
+        where
+            K: serde::Deserialize<'de> + Ord,
+            V: serde::Deserialize<'de>,
+        {
+            type Value = BinaryTree<K, V>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                formatter.write_str("a map")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut tree = BinaryTree::new();
+                while let Some((key, value)) = map.next_entry()? {
+                    tree.insert(key, value);
+                }
+                Ok(tree)
+            }
+        }
+
+        deserializer.deserialize_map(TreeVisitor(core::marker::PhantomData))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, K, V> arbitrary::Arbitrary<'a> for BinaryTree<K, V>
+where
+    K: arbitrary::Arbitrary<'a> + Ord,
+    V: arbitrary::Arbitrary<'a>,
+{
+    /// Generates a tree by inserting arbitrary entries in order, so that the last value wins if a
+    /// key is generated more than once.
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut tree = BinaryTree::new();
+        for entry in u.arbitrary_iter::<(K, V)>()? {
+            let (key, value) = entry?;
+            tree.insert(key, value);
+        }
+        Ok(tree)
+    }
+
+    fn arbitrary_take_rest(u: arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut tree = BinaryTree::new();
+        for entry in u.arbitrary_take_rest_iter::<(K, V)>()? {
+            let (key, value) = entry?;
+            tree.insert(key, value);
+        }
+        Ok(tree)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        Self::try_size_hint(depth).unwrap_or_default()
+    }
+
+    /// An empty tree needs no input and there is no bound on the number of entries, so the hints
+    /// of the key and value types only matter to detect unbounded recursion.
+    fn try_size_hint(
+        depth: usize,
+    ) -> Result<(usize, Option<usize>), arbitrary::MaxRecursionReached> {
+        arbitrary::size_hint::try_recursion_guard(depth, |depth| {
+            let entry =
+                arbitrary::size_hint::and(K::try_size_hint(depth)?, V::try_size_hint(depth)?);
+            Ok(arbitrary::size_hint::or((0, Some(0)), (entry.0, None)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
     use super::*;
+    use crate::test_util::{assert_empty, Rng};
+
+    #[test]
+    fn empty() {
+        let tree: BinaryTree<i32, i32> = BinaryTree::new();
+        assert_eq!(tree.get(&0), None);
+        assert_empty(&tree);
+    }
+
+    #[test]
+    fn one_key() {
+        let mut tree = BinaryTree::new();
+        tree.insert(3, "Hello");
+        assert_eq!(tree.get(&3), Some(&"Hello"));
+        assert_eq!(tree.insert(3, "World"), Some("Hello"));
+        assert_eq!(tree.get(&3), Some(&"World"));
+    }
+
+    #[test]
+    fn three_keys() {
+        let mut tree = BinaryTree::new();
+        tree.insert(1, 'A');
+        tree.insert(0, 'B');
+        tree.insert(2, 'C');
+
+        assert_eq!(tree.get(&1), Some(&'A'));
+        assert_eq!(tree.get(&0), Some(&'B'));
+        assert_eq!(tree.get(&2), Some(&'C'));
+        assert_eq!(tree.get(&-1), None);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut tree = BinaryTree::new();
+        tree.insert(1, 'A');
+        tree.insert(2, 'B');
+        tree.insert(0, 'C');
+
+        assert_eq!(tree.get(&0), Some(&'C'));
+
+        let val = tree.get_mut(&1).expect("Failed to mutably reference value");
+        *val = 'X';
+
+        let val = tree.get_mut(&2).expect("Failed to mutably reference value");
+        *val = 'Y';
+
+        let val = tree.get_mut(&0).expect("Failed to mutably reference value");
+        *val = 'Z';
+
+        assert_eq!(tree.get(&1), Some(&'X'));
+        assert_eq!(tree.get(&2), Some(&'Y'));
+        assert_eq!(tree.get(&0), Some(&'Z'));
+    }
+
+    #[test]
+    fn index() {
+        let mut tree = BinaryTree::new();
+        tree.insert(0, 'A');
+        assert_eq!(tree[&0], 'A');
+    }
+
+    #[test]
+    fn try_insert_alloc() {
+        let mut tree = BinaryTree::new();
+        assert_eq!(tree.try_insert_alloc(2, 'B'), Ok(None));
+        assert_eq!(tree.try_insert_alloc(1, 'A'), Ok(None));
+        assert_eq!(tree.try_insert_alloc(3, 'C'), Ok(None));
+        assert_eq!(tree.try_insert_alloc(3, 'D'), Ok(Some('C')));
+
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get(&1), Some(&'A'));
+        assert_eq!(tree.get(&2), Some(&'B'));
+        assert_eq!(tree.get(&3), Some(&'D'));
+    }
+
+    #[test]
+    fn insert_alloc_failure() {
+        let mut tree = BinaryTree::new();
+        tree.insert(2, 'B');
+
+        let err = tree
+            .insert_with(1, 'A', Err)
+            .expect_err("Failing allocator should produce an error");
+        assert_eq!(err.key(), &1);
+        assert_eq!(err.value(), &'A');
+        assert_eq!(err.into_inner(), (1, 'A'));
+
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.get(&1), None);
+
+        // Replacing an existing value never allocates.
+        assert_eq!(tree.insert_with(2, 'X', Err), Ok(Some('B')));
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn from_btree_map() {
+        let map: BTreeMap<i32, char> = (0..100).map(|i| (i, 'A')).collect();
+        let tree = BinaryTree::from(map.clone());
+
+        assert_eq!(tree.len(), map.len());
+        for key in map.keys() {
+            assert_eq!(tree.get(key), Some(&'A'));
+        }
+
+        let entries: Vec<(i32, char)> = tree.into();
+        assert!(entries.into_iter().eq(map));
+    }
+
+    #[test]
+    fn from_empty_btree_map() {
+        let tree = BinaryTree::from(BTreeMap::<i32, i32>::new());
+        assert!(tree.is_empty());
+        assert_eq!(Vec::from(tree), Vec::new());
+    }
+
+    #[test]
+    fn into_vec() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            tree.insert(key, key * 10);
+        }
+
+        let entries: Vec<(i32, i32)> = tree.into();
+        assert_eq!(entries, (1..=9).map(|k| (k, k * 10)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn remove() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9, 6] {
+            tree.insert(key, key * 10);
+        }
+
+        // Leaf, node with one child, and node with two children.
+        assert_eq!(tree.remove(&1), Some(10));
+        assert_eq!(tree.remove(&7), Some(70));
+        assert_eq!(tree.remove(&3), Some(30));
+        assert_eq!(tree.remove(&3), None);
+        assert_eq!(tree.len(), 5);
+
+        for key in [4, 5, 6, 8, 9] {
+            assert_eq!(tree.get(&key), Some(&(key * 10)));
+        }
+        for key in [1, 3, 7] {
+            assert_eq!(tree.get(&key), None);
+        }
+    }
+
+    #[test]
+    fn remove_root() {
+        let mut tree = BinaryTree::new();
+        tree.insert(2, 'B');
+        tree.insert(1, 'A');
+        tree.insert(3, 'C');
+
+        // The successor is the immediate right child of the root.
+        assert_eq!(tree.remove(&2), Some('B'));
+        assert_eq!(tree.get(&1), Some(&'A'));
+        assert_eq!(tree.get(&3), Some(&'C'));
+
+        assert_eq!(tree.remove(&3), Some('C'));
+        assert_eq!(tree.remove(&1), Some('A'));
+        assert!(tree.is_empty());
+        assert_eq!(tree.remove(&1), None);
+    }
+
+    #[test]
+    fn remove_successor_deep() {
+        let mut tree = BinaryTree::new();
+        for key in [2, 1, 6, 4, 7, 3, 5] {
+            tree.insert(key, key);
+        }
+
+        assert_eq!(tree.remove(&2), Some(2));
+        assert_eq!(tree.len(), 6);
+        assert_eq!(
+            Vec::from(tree),
+            [(1, 1), (3, 3), (4, 4), (5, 5), (6, 6), (7, 7)]
+        );
+    }
+
+    #[test]
+    fn remove_degenerate() {
+        let mut tree = BinaryTree::new();
+        for key in 0..32 {
+            tree.insert(key, key);
+        }
+
+        for key in (0..32).step_by(2) {
+            assert_eq!(tree.remove(&key), Some(key));
+        }
+        assert_eq!(tree.len(), 16);
+
+        for key in 0..32 {
+            let expected = if key % 2 == 0 { None } else { Some(&key) };
+            assert_eq!(tree.get(&key), expected);
+        }
+
+        assert_eq!(tree.insert(4, 40), None);
+        assert_eq!(tree.get(&4), Some(&40));
+        assert_eq!(tree.len(), 17);
+    }
+
+    /// A key whose ordering ignores the attached payload.
+    #[derive(Debug)]
+    struct Tagged(i32, &'static str);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Tagged {}
+
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn remove_entry() {
+        let mut tree = BinaryTree::new();
+        tree.insert(Tagged(2, "stored"), 'B');
+        tree.insert(Tagged(1, "stored"), 'A');
+        tree.insert(Tagged(3, "stored"), 'C');
+
+        // Removing a node with two children still decrements `len` only once.
+        let (key, value) = tree
+            .remove_entry(&Tagged(2, "lookup"))
+            .expect("Key should be present");
+        assert_eq!((key.0, key.1, value), (2, "stored", 'B'));
+        assert_eq!(tree.len(), 2);
+
+        assert!(tree.remove_entry(&Tagged(2, "lookup")).is_none());
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            tree.insert(key, key);
+        }
+
+        let mut iter = tree.iter_mut();
+        assert_eq!(iter.size_hint(), (9, Some(9)));
+        for (expected, (key, value)) in (1..).zip(&mut iter) {
+            assert_eq!(*key, expected);
+            *value *= 100;
+        }
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+
+        for key in 1..=9 {
+            assert_eq!(tree.get(&key), Some(&(key * 100)));
+        }
+    }
+
+    #[test]
+    fn iter_mut_partial() {
+        let mut tree = BinaryTree::new();
+        for key in 0..10 {
+            tree.insert(key, 0);
+        }
+        tree.remove(&4);
+
+        for (_, value) in tree.iter_mut().take(5) {
+            *value += 1;
+        }
+
+        let values: Vec<(i32, i32)> = tree.into();
+        assert_eq!(
+            values,
+            [
+                (0, 1),
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (5, 1),
+                (6, 0),
+                (7, 0),
+                (8, 0),
+                (9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn iter_mut_empty() {
+        let mut tree: BinaryTree<i32, i32> = BinaryTree::new();
+        assert!(tree.iter_mut().next().is_none());
+    }
+
+    /// Counts how many times it has been dropped.
+    #[derive(Debug)]
+    struct DropCounter<'a>(&'a core::cell::Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            tree.insert(key, key * 2);
+        }
+
+        let mut iter = tree.into_iter();
+        for remaining in (0..9).rev() {
+            assert!(iter.next().is_some());
+            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+        }
+        assert_eq!(iter.next(), None);
+
+        let mut tree = BinaryTree::new();
+        for key in [2, 1, 3] {
+            tree.insert(key, key * 2);
+        }
+        let mut expected = [(1, 2), (2, 4), (3, 6)].into_iter();
+        for entry in tree {
+            assert_eq!(Some(entry), expected.next());
+        }
+    }
+
+    #[test]
+    fn into_iter_drop_partial() {
+        let drops = core::cell::Cell::new(0);
+        let mut tree = BinaryTree::new();
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            tree.insert(key, DropCounter(&drops));
+        }
+
+        let mut iter = tree.into_iter();
+        let (key, value) = iter.next().expect("Tree should not be empty");
+        assert_eq!(key, 1);
+        drop(value);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(iter.len, 6);
+
+        drop(iter);
+        assert_eq!(drops.get(), 7);
+    }
+
+    #[test]
+    fn iter() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            tree.insert(key, key * 10);
+        }
+
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 9);
+        assert_eq!(iter.next(), Some((&1, &10)));
+        assert_eq!(iter.len(), 8);
+        let rest: Vec<(i32, i32)> = iter.map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(rest, (2..=9).map(|k| (k, k * 10)).collect::<Vec<_>>());
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    #[test]
+    fn keys() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+            tree.insert(key, ());
+        }
+        tree.remove(&5);
+
+        let mut keys = tree.keys();
+        assert_eq!(keys.len(), 8);
+        assert_eq!(keys.next(), Some(&1));
+        assert_eq!(keys.len(), 7);
+        assert_eq!(keys.copied().collect::<Vec<_>>(), [2, 3, 4, 6, 7, 8, 9]);
+        assert_eq!(format!("{:?}", tree.keys()), "[1, 2, 3, 4, 6, 7, 8, 9]");
+
+        let empty: BinaryTree<i32, ()> = BinaryTree::new();
+        assert_eq!(empty.keys().len(), 0);
+        assert_eq!(empty.keys().next(), None);
+    }
+
+    #[test]
+    fn into_keys_and_values() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4] {
+            tree.insert(key, key * 10);
+        }
+
+        let mut keys = tree.clone().into_keys();
+        assert_eq!(keys.size_hint(), (5, Some(5)));
+        assert_eq!(keys.next(), Some(1));
+        assert_eq!(keys.size_hint(), (4, Some(4)));
+        assert_eq!(keys.collect::<Vec<_>>(), [3, 4, 5, 8]);
+
+        assert_eq!(tree.into_values().collect::<Vec<_>>(), [10, 30, 40, 50, 80]);
+    }
+
+    #[test]
+    fn into_values_drop_partial() {
+        let drops = core::cell::Cell::new(0);
+        let mut tree = BinaryTree::new();
+        for key in 0..5 {
+            tree.insert(key, DropCounter(&drops));
+        }
+
+        let mut values = tree.into_values();
+        drop(values.next());
+        drop(values.next());
+        assert_eq!(drops.get(), 2);
+
+        drop(values);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn first_and_last_key_value() {
+        let mut rng = Rng::new(0xF1257);
+        let mut tree = BinaryTree::new();
+        let (mut min, mut max) = (u64::MAX, u64::MIN);
+
+        for _ in 0..200 {
+            let key = rng.below(10_000);
+            tree.insert(key, key + 1);
+            min = min.min(key);
+            max = max.max(key);
+
+            assert_eq!(tree.first_key_value(), Some((&min, &(min + 1))));
+            assert_eq!(tree.last_key_value(), Some((&max, &(max + 1))));
+        }
+    }
+
+    #[test]
+    fn first_and_last_key_value_empty() {
+        let tree: BinaryTree<i32, i32> = BinaryTree::new();
+        assert_eq!(tree.first_key_value(), None);
+        assert_eq!(tree.last_key_value(), None);
+    }
+
+    #[test]
+    fn pop_first_and_last() {
+        let mut tree = BinaryTree::new();
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            tree.insert(key, key * 10);
+        }
+
+        assert_eq!(tree.pop_first(), Some((1, 10)));
+        // The minimum now has a right subtree.
+        assert_eq!(tree.pop_first(), Some((2, 20)));
+        assert_eq!(tree.pop_last(), Some((7, 70)));
+        assert_eq!(tree.pop_last(), Some((6, 60)));
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.first_key_value(), Some((&3, &30)));
+        assert_eq!(tree.last_key_value(), Some((&5, &50)));
+    }
+
+    #[test]
+    fn pop_root() {
+        let mut tree = BinaryTree::new();
+        tree.insert(1, 'A');
+        tree.insert(2, 'B');
+        tree.insert(3, 'C');
+        assert_eq!(tree.pop_first(), Some((1, 'A')));
+        assert_eq!(tree.get(&2), Some(&'B'));
+
+        let mut tree = BinaryTree::new();
+        tree.insert(3, 'C');
+        tree.insert(2, 'B');
+        assert_eq!(tree.pop_last(), Some((3, 'C')));
+        assert_eq!(tree.get(&2), Some(&'B'));
+    }
+
+    #[test]
+    fn pop_until_empty() {
+        let mut rng = Rng::new(0x909);
+        let mut tree = BinaryTree::new();
+        for _ in 0..100 {
+            tree.insert(rng.below(1000), ());
+        }
+
+        let mut previous = None;
+        while let Some((key, ())) = tree.pop_first() {
+            assert!(previous < Some(key));
+            previous = Some(key);
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.pop_last(), None);
+
+        tree.insert(7, ());
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.pop_last(), Some((7, ())));
+    }
+
+    /// Checks the AVL invariants and the stored heights, returning the height of the subtree.
+    fn assert_avl<K: Ord, V>(link: &NodeChild<K, V>) -> usize {
+        let node = match link {
+            Some(node) => node,
+            None => return 0,
+        };
+        if let Some(left) = &node.children.0 {
+            assert!(left.key < node.key);
+        }
+        if let Some(right) = &node.children.1 {
+            assert!(right.key > node.key);
+        }
+
+        let left = assert_avl(&node.children.0);
+        let right = assert_avl(&node.children.1);
+        assert!(
+            left.abs_diff(right) <= 1,
+            "subtree heights differ by more than one"
+        );
+        assert_eq!(node.height, 1 + left.max(right), "stored height is stale");
+        assert_eq!(
+            node.size,
+            1 + BinaryTreeNode::size(&node.children.0) + BinaryTreeNode::size(&node.children.1),
+            "stored size is stale"
+        );
+        node.height
+    }
+
+    fn assert_balanced<K: Ord, V>(tree: &BinaryTree<K, V>) {
+        assert_avl(&tree.root);
+        assert_eq!(BinaryTreeNode::size(&tree.root), tree.len());
+    }
+
+    crate::tree::map_tests!(BinaryTree, assert_balanced);
+
+    #[test]
+    fn sequential_inserts_stay_balanced() {
+        let n = 100_000;
+        let mut tree = BinaryTree::new();
+        for key in 0..n {
+            tree.insert(key, ());
+        }
+
+        assert_avl(&tree.root);
+        let height = tree.height();
+        let bound = 1.45 * f64::from(n).log2() + 2.0;
+        assert!((height as f64) <= bound, "height {height} exceeds {bound}");
+        assert_eq!(tree.len(), n as usize);
+        assert_eq!(tree.get(&(n - 1)), Some(&()));
+    }
+
+    #[test]
+    fn random_operations_stay_balanced() {
+        let mut rng = Rng::new(0xA71);
+        let mut tree = BinaryTree::new();
+        let mut reference = BTreeMap::new();
+
+        for _ in 0..2_000 {
+            let key = rng.below(200);
+            if rng.below(3) == 0 {
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+            } else {
+                assert_eq!(tree.insert(key, key), reference.insert(key, key));
+            }
+            assert_avl(&tree.root);
+            assert_eq!(tree.len(), reference.len());
+        }
+
+        while tree.pop_first().is_some() {
+            assert_avl(&tree.root);
+        }
+    }
+
+    /// A key that counts how often it is compared.
+    #[derive(Debug)]
+    struct CountingKey<'a>(i32, &'a core::cell::Cell<usize>);
+
+    impl PartialEq for CountingKey<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for CountingKey<'_> {}
+
+    impl PartialOrd for CountingKey<'_> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for CountingKey<'_> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.1.set(self.1.get() + 1);
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn one_comparison_per_level() {
+        let comparisons = core::cell::Cell::new(0);
+        let mut tree = BinaryTree::new();
+        for key in [2, 1, 3] {
+            tree.insert(CountingKey(key, &comparisons), ());
+        }
+
+        comparisons.set(0);
+        assert_eq!(tree.get(&CountingKey(3, &comparisons)), Some(&()));
+        assert_eq!(comparisons.get(), 2);
+
+        comparisons.set(0);
+        assert_eq!(tree.insert(CountingKey(4, &comparisons), ()), None);
+        assert_eq!(comparisons.get(), 2);
+    }
+
+    #[test]
+    fn get_key_value() {
+        let mut tree = BinaryTree::new();
+        for word in ["pear", "apple", "fig"] {
+            tree.insert(String::from(word), word.len());
+        }
+
+        let (key, value) = tree.get_key_value("apple").expect("Key should be present");
+        assert_eq!(key, "apple");
+        assert_eq!(*value, 5);
+        let stored = tree.keys().find(|stored| *stored == "apple");
+        assert!(ptr::eq(key, stored.expect("Key should be present")));
+        assert_eq!(tree.get_key_value("plum"), None);
+
+        let mut tree = BinaryTree::new();
+        tree.insert(Tagged(1, "stored"), ());
+        let (key, _) = tree
+            .get_key_value(&Tagged(1, "probe"))
+            .expect("Key should be present");
+        assert_eq!(key.1, "stored");
+    }
+
+    #[test]
+    fn append() {
+        let mut a = BinaryTree::new();
+        let mut b = BinaryTree::new();
+        for key in 0..100 {
+            a.insert(key, 'a');
+        }
+        for key in 50..200 {
+            b.insert(key, 'b');
+        }
+
+        a.append(&mut b);
+        assert_eq!(a.len(), 200);
+        assert_empty(&b);
+        assert_balanced(&a);
+        for key in 0..200 {
+            let expected = if key < 50 { 'a' } else { 'b' };
+            assert_eq!(a.get(&key), Some(&expected));
+        }
+
+        b.append(&mut a);
+        assert_eq!(b.len(), 200);
+        assert!(a.is_empty());
+        b.append(&mut a);
+        assert_eq!(b.len(), 200);
+    }
+
+    #[test]
+    fn merge_with() {
+        let mut a = BinaryTree::new();
+        let mut b = BinaryTree::new();
+        for key in 0..100 {
+            a.insert(key, key);
+        }
+        for key in 50..200 {
+            b.insert(key, 1000 + key);
+        }
+
+        let mut calls = Vec::new();
+        a.merge_with(b, |key, ours, theirs| {
+            calls.push(*key);
+            ours + theirs
+        });
+        assert_eq!(calls, (50..100).collect::<Vec<_>>());
+        assert_eq!(a.len(), 200);
+        assert_balanced(&a);
+        for key in 0..200 {
+            let expected = match key {
+                0..=49 => key,
+                50..=99 => 1000 + 2 * key,
+                _ => 1000 + key,
+            };
+            assert_eq!(a.get(&key), Some(&expected));
+        }
+
+        a.merge_with(BinaryTree::new(), |_, _, _| unreachable!());
+        assert_eq!(a.len(), 200);
+        let mut empty = BinaryTree::new();
+        empty.merge_with(a, |_, _, _| unreachable!());
+        assert_eq!(empty.len(), 200);
+    }
+
+    #[test]
+    fn merge_with_keeps_existing_keys() {
+        let mut a = BinaryTree::new();
+        a.insert(Tagged(1, "ours"), 1);
+        let mut b = BinaryTree::new();
+        b.insert(Tagged(1, "theirs"), 2);
+        b.insert(Tagged(2, "theirs"), 3);
+
+        a.merge_with(b, |_, ours, theirs| ours * 10 + theirs);
+        let entries: Vec<_> = a.iter().map(|(key, value)| (key.1, *value)).collect();
+        assert_eq!(entries, [("ours", 12), ("theirs", 3)]);
+    }
+
+    #[test]
+    fn append_drops_replaced_values() {
+        let drops = core::cell::Cell::new(0);
+        let mut a = BinaryTree::new();
+        let mut b = BinaryTree::new();
+        for key in 0..10 {
+            a.insert(key, DropCounter(&drops));
+            b.insert(key + 5, DropCounter(&drops));
+        }
+
+        a.append(&mut b);
+        assert_eq!(drops.get(), 5);
+        assert_eq!(a.len(), 15);
+    }
+
+    #[test]
+    fn split_off() {
+        for pivot in [-5, 0, 1, 37, 99, 100, 150] {
+            let mut tree = BinaryTree::new();
+            for key in 0..100 {
+                tree.insert(key, key * 2);
+            }
+
+            let upper = tree.split_off(&pivot);
+            let split = pivot.clamp(0, 100);
+            assert_eq!(tree.len(), split as usize);
+            assert_eq!(upper.len(), 100 - split as usize);
+            assert_balanced(&tree);
+            assert_balanced(&upper);
+            assert_eq!(
+                Vec::from(tree),
+                (0..split).map(|k| (k, k * 2)).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Vec::from(upper),
+                (split..100).map(|k| (k, k * 2)).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn split_off_borrowed() {
+        let mut tree = BinaryTree::new();
+        for word in ["apple", "fig", "pear", "plum"] {
+            tree.insert(String::from(word), ());
+        }
+
+        let upper = tree.split_off("grape");
+        assert_eq!(tree.into_keys().collect::<Vec<_>>(), ["apple", "fig"]);
+        assert_eq!(upper.into_keys().collect::<Vec<_>>(), ["pear", "plum"]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut rng = Rng::new(275);
+        for _ in 0..20 {
+            let mut tree = BinaryTree::new();
+            for _ in 0..200 {
+                tree.insert(rng.below(500), rng.below(10));
+            }
+            let threshold = rng.below(10);
+
+            let mut expected = BinaryTree::new();
+            for (&key, &value) in &tree {
+                if value < threshold {
+                    expected.insert(key, value + 1);
+                }
+            }
+
+            tree.retain(|_, value| {
+                *value += 1;
+                *value <= threshold
+            });
+            assert_eq!(tree.len(), expected.len());
+            assert_balanced(&tree);
+            assert_eq!(Vec::from(tree), Vec::from(expected));
+        }
+    }
+
+    #[test]
+    fn retain_panic_keeps_unvisited_entries() {
+        extern crate std;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut tree: BinaryTree<u32, u32> = (0..10).map(|key| (key, key)).collect();
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            tree.retain(|&key, value| {
+                assert!(key != 5, "Predicate panicked on purpose");
+                *value *= 10;
+                key % 2 == 0
+            })
+        }));
+        assert!(outcome.is_err());
+
+        assert_eq!(tree.len(), 8);
+        assert_balanced(&tree);
+        assert_eq!(
+            Vec::from(tree),
+            [
+                (0, 0),
+                (2, 20),
+                (4, 40),
+                (5, 5),
+                (6, 6),
+                (7, 7),
+                (8, 8),
+                (9, 9)
+            ]
+        );
+    }
+
+    #[test]
+    fn retain_keeps_nodes_in_place() {
+        let mut tree: BinaryTree<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        let before: Vec<*const u32> = tree.values().map(|value| value as *const u32).collect();
+
+        tree.retain(|_, _| true);
+        let after: Vec<*const u32> = tree.values().map(|value| value as *const u32).collect();
+        assert_eq!(before, after);
+
+        let kept = tree.get(&7).map(|value| value as *const u32);
+        tree.retain(|key, _| key % 10 != 0);
+        assert_eq!(tree.len(), 90);
+        assert_balanced(&tree);
+        assert_eq!(tree.get(&7).map(|value| value as *const u32), kept);
+    }
+
+    #[test]
+    fn retain_drops_removed_values() {
+        let drops = core::cell::Cell::new(0);
+        let mut tree = BinaryTree::new();
+        for key in 0..10 {
+            tree.insert(key, DropCounter(&drops));
+        }
+
+        tree.retain(|key, _| *key < 3);
+        assert_eq!(drops.get(), 7);
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn height() {
+        let mut root = None;
+        for key in (0..100).rev() {
+            let height = BinaryTreeNode::height(&root) + 1;
+            root = Some(Box::new(BinaryTreeNode {
+                key,
+                value: (),
+                height,
+                size: height,
+                children: (None, root),
+            }));
+        }
+        let skewed = BinaryTree { root, len: 100 };
+        assert_eq!(skewed.height(), skewed.len());
+
+        for n in [0_usize, 1, 2, 3, 7, 8, 1000, 1023, 1024] {
+            let tree = BinaryTree::from((0..n).map(|key| (key, ())).collect::<BTreeMap<_, _>>());
+            let expected = (usize::BITS - n.leading_zeros()) as usize;
+            assert_eq!(tree.height(), expected, "n = {n}");
+            assert_eq!(tree.height(), assert_avl(&tree.root));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_round_trip() {
+        let mut tree = BinaryTree::new();
+        for (key, value) in [("b", 2), ("c", 3), ("a", 1)] {
+            tree.insert(String::from(key), value);
+        }
+
+        let json = serde_json::to_string(&tree).expect("Tree should serialize");
+        assert_eq!(json, r#"{"a":1,"b":2,"c":3}"#);
+        let decoded: BinaryTree<String, i32> =
+            serde_json::from_str(&json).expect("Tree should deserialize");
+        assert_eq!(Vec::from(decoded), Vec::from(tree));
+
+        let decoded: BinaryTree<String, i32> =
+            serde_json::from_str(r#"{"a":1,"b":2,"a":3}"#).expect("Tree should deserialize");
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded.get(&String::from("a")), Some(&3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_postcard_round_trip() {
+        let mut tree = BinaryTree::new();
+        for key in 0..1000_u32 {
+            tree.insert(key, key.wrapping_mul(2_654_435_761));
+        }
+
+        let bytes = postcard::to_allocvec(&tree).expect("Tree should serialize");
+        let decoded: BinaryTree<u32, u32> =
+            postcard::from_bytes(&bytes).expect("Tree should deserialize");
+        assert_balanced(&decoded);
+        assert_eq!(Vec::from(decoded), Vec::from(tree));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_trees() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let inputs: [&[u8]; 4] = [
+            b"",
+            b"\x01\x07\x00\x01",
+            b"\x01\x05\x10\x00\x01\x03\x20\x00\x01\x05\x30\x00\x00",
+            b"quick brown foxes jump over lazy dogs, again and again and again",
+        ];
+        for bytes in inputs {
+            let tree = BinaryTree::<u8, u16>::arbitrary(&mut Unstructured::new(bytes))
+                .expect("Tree should generate");
+            let entries = Vec::<(u8, u16)>::arbitrary(&mut Unstructured::new(bytes))
+                .expect("Entries should generate");
+            let keys: alloc::collections::BTreeSet<u8> = entries.iter().map(|(k, _)| *k).collect();
+            let expected: BTreeMap<u8, u16> = entries.into_iter().collect();
+
+            assert_eq!(tree.len(), keys.len());
+            assert_balanced(&tree);
+            assert!(tree.iter().eq(expected.iter()));
+
+            let rest = BinaryTree::<u8, u16>::arbitrary_take_rest(Unstructured::new(bytes))
+                .expect("Tree should generate");
+            assert_balanced(&rest);
+        }
+
+        assert_eq!(BinaryTree::<u8, u16>::size_hint(0), (0, None));
+    }
+
+    #[test]
+    fn equality_ignores_shape() {
+        let mut a = BinaryTree::new();
+        for (key, value) in [(1, 'a'), (2, 'b'), (3, 'c')] {
+            a.insert(key, value);
+        }
+        let mut b = BinaryTree::new();
+        for (key, value) in [(2, 'b'), (1, 'a'), (3, 'c')] {
+            b.insert(key, value);
+        }
+        assert_eq!(a, b);
+
+        let mut skewed = BinaryTree::new();
+        skewed.insert(1, 'a');
+        skewed.insert(2, 'b');
+        let mut bushy = BinaryTree::new();
+        bushy.insert(2, 'b');
+        bushy.insert(1, 'a');
+        assert_eq!(skewed, bushy);
+
+        b.insert(3, 'd');
+        assert_ne!(a, b);
+        b.remove(&3);
+        assert_ne!(a, b);
+        b.insert(3, 'c');
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn ordering() {
+        let tree = |entries: &[(i32, char)]| {
+            let mut tree = BinaryTree::new();
+            for &(key, value) in entries {
+                tree.insert(key, value);
+            }
+            tree
+        };
+
+        let abc = tree(&[(1, 'a'), (2, 'b'), (3, 'c')]);
+        let shuffled = tree(&[(2, 'b'), (3, 'c'), (1, 'a')]);
+        assert_eq!(abc.cmp(&shuffled), Ordering::Equal);
+        assert_eq!(abc.partial_cmp(&shuffled), Some(Ordering::Equal));
+
+        assert!(tree(&[(1, 'a'), (2, 'b')]) < abc);
+        assert!(tree(&[(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]) > abc);
+        assert!(tree(&[(1, 'a'), (2, 'a'), (3, 'c')]) < abc);
+        assert!(tree(&[(1, 'a'), (2, 'b'), (3, 'd')]) > abc);
+        assert!(tree(&[(2, 'a')]) > abc);
+        assert!(BinaryTree::new() < abc);
+
+        let mut sorted = [tree(&[(2, 'a')]), shuffled, BinaryTree::new()];
+        sorted.sort();
+        assert!(sorted[0].is_empty());
+        assert_eq!(sorted[1].len(), 3);
+        assert_eq!(sorted[2].len(), 1);
+    }
 
     #[test]
-    fn empty() {
-        let tree: BinaryTree<i32, i32> = BinaryTree::new();
-        assert_eq!(tree.get(&0), None);
-        assert_eq!(tree.len(), 0);
-        assert!(tree.is_empty());
+    fn range() {
+        let mut tree = BinaryTree::new();
+        for key in (0..100).step_by(2) {
+            tree.insert(key, key * 10);
+        }
+        let reference: BTreeMap<_, _> = tree.iter().map(|(&k, &v)| (k, v)).collect();
+
+        let ranges = [
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(10), Bound::Excluded(20)),
+            (Bound::Excluded(10), Bound::Included(20)),
+            (Bound::Included(11), Bound::Included(11)),
+            (Bound::Included(10), Bound::Included(10)),
+            (Bound::Included(10), Bound::Excluded(10)),
+            (Bound::Unbounded, Bound::Excluded(7)),
+            (Bound::Excluded(90), Bound::Unbounded),
+            (Bound::Included(-50), Bound::Included(500)),
+            (Bound::Included(200), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Excluded(0)),
+        ];
+        for range in ranges {
+            let actual: Vec<_> = tree.range(range).collect();
+            let expected: Vec<_> = reference.range(range).collect();
+            assert_eq!(actual, expected, "{range:?}");
+        }
+
+        assert_eq!(tree.range(..).count(), tree.len());
+        assert_eq!(
+            tree.range(4..=8).map(|(k, _)| *k).collect::<Vec<_>>(),
+            [4, 6, 8]
+        );
+        assert_eq!(format!("{:?}", tree.range(4..7)), "[(4, 40), (6, 60)]");
     }
 
     #[test]
-    fn one_key() {
+    fn range_random() {
+        let mut rng = Rng::new(281);
         let mut tree = BinaryTree::new();
-        tree.insert(3, "Hello");
-        assert_eq!(tree.get(&3), Some(&"Hello"));
-        assert_eq!(tree.insert(3, "World"), Some("Hello"));
-        assert_eq!(tree.get(&3), Some(&"World"));
+        let mut reference = BTreeMap::new();
+        for _ in 0..500 {
+            let key = rng.below(1000);
+            tree.insert(key, key);
+            reference.insert(key, key);
+        }
+
+        for _ in 0..200 {
+            let (a, b) = (rng.below(1100), rng.below(1100));
+            let (start, end) = (a.min(b), a.max(b));
+            assert!(tree.range(start..end).eq(reference.range(start..end)));
+            assert!(tree.range(start..=end).eq(reference.range(start..=end)));
+            assert!(tree.range(..end).eq(reference.range(..end)));
+            assert!(tree.range(start..).eq(reference.range(start..)));
+        }
     }
 
     #[test]
-    fn three_keys() {
+    fn range_borrowed() {
         let mut tree = BinaryTree::new();
-        tree.insert(1, 'A');
-        tree.insert(0, 'B');
-        tree.insert(2, 'C');
+        for word in ["apple", "fig", "pear", "plum"] {
+            tree.insert(String::from(word), ());
+        }
 
-        assert_eq!(tree.get(&1), Some(&'A'));
-        assert_eq!(tree.get(&0), Some(&'B'));
-        assert_eq!(tree.get(&2), Some(&'C'));
-        assert_eq!(tree.get(&-1), None);
+        let words: Vec<_> = tree
+            .range::<str, _>((Bound::Included("b"), Bound::Excluded("pl")))
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(words, ["fig", "pear"]);
     }
 
     #[test]
-    fn get_mut() {
+    fn range_count() {
+        let mut rng = Rng::new(289);
+        for _ in 0..20 {
+            let mut tree = BinaryTree::new();
+            for _ in 0..rng.below(300) {
+                tree.insert(rng.below(1000), ());
+            }
+
+            for _ in 0..50 {
+                let (a, b) = (rng.below(1100), rng.below(1100));
+                let (start, end) = (a.min(b), a.max(b));
+                let ranges = [
+                    (Bound::Included(start), Bound::Included(end)),
+                    (Bound::Included(start), Bound::Excluded(end)),
+                    (Bound::Excluded(start), Bound::Included(end)),
+                    (Bound::Unbounded, Bound::Excluded(end)),
+                    (Bound::Excluded(start), Bound::Unbounded),
+                    (Bound::Unbounded, Bound::Unbounded),
+                ];
+                for range in ranges {
+                    assert_eq!(
+                        tree.range_count(range),
+                        tree.range(range).count(),
+                        "{range:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn range_count_inverted() {
+        let tree = BinaryTree::<i32, ()>::new();
+        tree.range_count((Bound::Included(2), Bound::Included(1)));
+    }
+
+    #[test]
+    fn range_mut() {
         let mut tree = BinaryTree::new();
-        tree.insert(1, 'A');
-        tree.insert(2, 'B');
-        tree.insert(0, 'C');
+        for key in 0..20 {
+            tree.insert(key, key);
+        }
 
-        assert_eq!(tree.get(&0), Some(&'C'));
+        for (_, value) in tree.range_mut(5..10) {
+            *value = -1;
+        }
+        for (key, value) in &tree {
+            let expected = if (5..10).contains(key) { -1 } else { *key };
+            assert_eq!(*value, expected);
+        }
+        assert_eq!(tree.range_mut(20..).count(), 0);
+        assert_eq!(tree.range_mut(..).count(), 20);
+    }
 
-        let val = tree.get_mut(&1).expect("Failed to mutably reference value");
-        *val = 'X';
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn range_inverted() {
+        let mut tree = BinaryTree::new();
+        tree.insert(1, ());
+        #[allow(clippy::reversed_empty_ranges)]
+        tree.range(5..3).for_each(drop);
+    }
 
-        let val = tree.get_mut(&2).expect("Failed to mutably reference value");
-        *val = 'Y';
+    #[test]
+    #[should_panic(expected = "range start and end are equal and excluded")]
+    fn range_equal_excluded() {
+        let tree = BinaryTree::<i32, ()>::new();
+        tree.range((Bound::Excluded(3), Bound::Excluded(3)))
+            .for_each(drop);
+    }
 
-        let val = tree.get_mut(&0).expect("Failed to mutably reference value");
-        *val = 'Z';
+    #[test]
+    fn floor_and_ceiling() {
+        let mut tree = BinaryTree::new();
+        assert_eq!(tree.floor_key_value(&0), None);
+        assert_eq!(tree.ceiling_key_value(&0), None);
 
-        assert_eq!(tree.get(&1), Some(&'X'));
-        assert_eq!(tree.get(&2), Some(&'Y'));
-        assert_eq!(tree.get(&0), Some(&'Z'));
+        for key in (10..=100).step_by(10) {
+            tree.insert(key, key / 10);
+        }
+        for probe in 0..=110 {
+            let floor = tree.iter().rfind(|(k, _)| **k <= probe);
+            let ceiling = tree.iter().find(|(k, _)| **k >= probe);
+            assert_eq!(tree.floor_key_value(&probe), floor, "floor of {probe}");
+            assert_eq!(
+                tree.ceiling_key_value(&probe),
+                ceiling,
+                "ceiling of {probe}"
+            );
+        }
+
+        assert_eq!(tree.floor_key_value(&9), None);
+        assert_eq!(tree.ceiling_key_value(&101), None);
+        assert_eq!(tree.floor_key_value(&40), Some((&40, &4)));
+        assert_eq!(tree.ceiling_key_value(&40), Some((&40, &4)));
     }
 
     #[test]
-    fn index() {
+    fn from_sorted_iter() {
+        for n in [0_usize, 1, 2, 5, 100, 4095, 4096, 100_000] {
+            let tree = BinaryTree::from_sorted_iter((0..n).map(|key| (key, key + 1)));
+            assert_eq!(tree.len(), n);
+            assert_eq!(tree.height(), (usize::BITS - n.leading_zeros()) as usize);
+            assert_balanced(&tree);
+            for key in [0, n / 3, n / 2, n.saturating_sub(1)].into_iter().take(n) {
+                assert_eq!(tree.get(&key), Some(&(key + 1)));
+            }
+            assert_eq!(tree.get(&n), None);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "not strictly ascending")]
+    fn from_sorted_iter_unsorted() {
+        BinaryTree::from_sorted_iter([(1, ()), (3, ()), (2, ())]);
+    }
+
+    #[test]
+    fn try_insert() {
         let mut tree = BinaryTree::new();
-        tree.insert(0, 'A');
-        assert_eq!(tree[&0], 'A');
+        for key in 0..100 {
+            *tree.try_insert(key, key).expect("Key should be vacant") += 1;
+        }
+        assert_eq!(tree.len(), 100);
+        assert_balanced(&tree);
+        for key in 0..100 {
+            assert_eq!(tree.get(&key), Some(&(key + 1)));
+        }
+
+        let mut error = tree.try_insert(42, 0).expect_err("Key should be occupied");
+        assert_eq!(error.key(), &42);
+        assert_eq!(error.existing(), &43);
+        assert_eq!(error.value(), &0);
+        *error.existing_mut() = 7;
+        assert_eq!(error.into_value(), 0);
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.get(&42), Some(&7));
+
+        *tree
+            .try_insert(42, 0)
+            .expect_err("Key should be occupied")
+            .into_existing() = 8;
+        assert_eq!(tree.get(&42), Some(&8));
+        assert_eq!(
+            format!(
+                "{}",
+                tree.try_insert(42, 0).expect_err("Key should be occupied")
+            ),
+            "key is already present in the binary tree"
+        );
+    }
+
+    #[test]
+    fn try_insert_rotated_to_root() {
+        // The new key becomes the root through a double rotation, which moves its node.
+        let mut tree = BinaryTree::new();
+        tree.insert(3, 'c');
+        tree.insert(1, 'a');
+        *tree.try_insert(2, 'x').expect("Key should be vacant") = 'b';
+        assert_balanced(&tree);
+        assert_eq!(tree.get(&2), Some(&'b'));
+    }
+
+    #[test]
+    fn try_insert_stored_key() {
+        let mut tree = BinaryTree::new();
+        tree.try_insert(Tagged(1, "stored"), ())
+            .expect("Key should be vacant");
+        let error = tree
+            .try_insert(Tagged(1, "rejected"), ())
+            .expect_err("Key should be occupied");
+        assert_eq!(error.key().1, "stored");
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut rng = Rng::new(286);
+        for _ in 0..20 {
+            let mut tree = BinaryTree::new();
+            for _ in 0..200 {
+                let key = rng.below(500);
+                tree.insert(key, key);
+            }
+            let modulus = rng.below(4) + 2;
+            let (expected_removed, expected_kept): (Vec<_>, Vec<_>) = tree
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .partition(|(k, _)| k % modulus == 0);
+
+            let removed: Vec<_> = tree.extract_if(|k, _| k % modulus == 0).collect();
+            assert_eq!(removed, expected_removed);
+            assert_eq!(tree.len(), expected_kept.len());
+            assert_balanced(&tree);
+            assert_eq!(Vec::from(tree), expected_kept);
+        }
+    }
+
+    #[test]
+    fn extract_if_partial() {
+        let mut tree = BinaryTree::new();
+        for key in 0..10 {
+            tree.insert(key, key);
+        }
+
+        let mut iter = tree.extract_if(|key, value| {
+            *value *= 10;
+            key % 2 == 0
+        });
+        assert_eq!(iter.next(), Some((0, 0)));
+        assert_eq!(iter.next(), Some((2, 20)));
+
+        assert_eq!(tree.len(), 8);
+        assert_balanced(&tree);
+        assert_eq!(
+            Vec::from(tree),
+            [
+                (1, 10),
+                (3, 3),
+                (4, 4),
+                (5, 5),
+                (6, 6),
+                (7, 7),
+                (8, 8),
+                (9, 9)
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_if_leaked() {
+        let mut tree: BinaryTree<u32, u32> = (0..10).map(|key| (key, key)).collect();
+        let mut iter = tree.extract_if(|key, _| key % 3 == 0);
+        assert_eq!(iter.next(), Some((0, 0)));
+        assert_eq!(iter.next(), Some((3, 3)));
+        let _ = core::mem::ManuallyDrop::new(iter);
+
+        assert_eq!(tree.len(), 8);
+        assert_balanced(&tree);
+        assert_eq!(
+            tree.into_keys().collect::<Vec<_>>(),
+            [1, 2, 4, 5, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn extract_if_panic_keeps_entries() {
+        extern crate std;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut tree: BinaryTree<u32, u32> = (0..10).map(|key| (key, key)).collect();
+        let mut removed = Vec::new();
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            for entry in tree.extract_if(|&key, _| {
+                assert!(key != 6, "Predicate panicked on purpose");
+                key % 2 == 1
+            }) {
+                removed.push(entry);
+            }
+        }));
+        assert!(outcome.is_err());
+
+        assert_eq!(removed, [(1, 1), (3, 3), (5, 5)]);
+        assert_eq!(tree.len(), 7);
+        assert_balanced(&tree);
+        assert_eq!(tree.into_keys().collect::<Vec<_>>(), [0, 2, 4, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn traversal_orders() {
+        //       4
+        //     /   \
+        //    2     6
+        //   / \   / \
+        //  1   3 5   7
+        let mut tree = BinaryTree::new();
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            tree.insert(key, key * 10);
+        }
+        let keys = |iter: &mut dyn Iterator<Item = (&i32, &i32)>| {
+            iter.map(|(k, _)| *k).collect::<Vec<_>>()
+        };
+
+        assert_eq!(keys(&mut tree.iter_preorder()), [4, 2, 1, 3, 6, 5, 7]);
+        assert_eq!(keys(&mut tree.iter_postorder()), [1, 3, 2, 5, 7, 6, 4]);
+        assert_eq!(keys(&mut tree.iter_levelorder()), [4, 2, 6, 1, 3, 5, 7]);
+        assert_eq!(tree.iter_preorder().len(), 7);
+        assert!(tree.iter_postorder().all(|(k, v)| *v == k * 10));
+
+        //    2
+        //   / \
+        //  1   3
+        //       \
+        //        4
+        let mut tree = BinaryTree::new();
+        for key in [2, 1, 3, 4] {
+            tree.insert(key, ());
+        }
+        let keys =
+            |iter: &mut dyn Iterator<Item = (&i32, &())>| iter.map(|(k, _)| *k).collect::<Vec<_>>();
+
+        assert_eq!(keys(&mut tree.iter_preorder()), [2, 1, 3, 4]);
+        assert_eq!(keys(&mut tree.iter_postorder()), [1, 4, 3, 2]);
+        assert_eq!(keys(&mut tree.iter_levelorder()), [2, 1, 3, 4]);
+        assert_eq!(
+            format!("{:?}", tree.iter_postorder()),
+            "[(1, ()), (4, ()), (3, ()), (2, ())]"
+        );
+
+        let mut postorder = tree.iter_postorder();
+        postorder.next();
+        assert_eq!(postorder.len(), 3);
+        assert_eq!(keys(&mut postorder.clone()), [4, 3, 2]);
+    }
+
+    #[test]
+    fn traversal_orders_empty() {
+        let tree = BinaryTree::<i32, ()>::new();
+        assert_eq!(tree.iter_preorder().next(), None);
+        assert_eq!(tree.iter_postorder().next(), None);
+        assert_eq!(tree.iter_levelorder().next(), None);
+    }
+
+    #[test]
+    fn select_and_rank() {
+        let mut rng = Rng::new(288);
+        let mut tree = BinaryTree::new();
+        let mut reference = BTreeMap::new();
+
+        for _ in 0..2_000 {
+            let key = rng.below(300);
+            match rng.below(4) {
+                0 => assert_eq!(tree.remove(&key), reference.remove(&key)),
+                _ => assert_eq!(tree.insert(key, key * 2), reference.insert(key, key * 2)),
+            }
+        }
+        assert_balanced(&tree);
+
+        let sorted: Vec<_> = reference.iter().collect();
+        for (n, entry) in sorted.iter().enumerate() {
+            assert_eq!(tree.select(n), Some(*entry));
+        }
+        assert_eq!(tree.select(sorted.len()), None);
+        for probe in 0..=300 {
+            let expected = sorted.partition_point(|(key, _)| **key < probe);
+            assert_eq!(tree.rank(&probe), expected, "rank of {probe}");
+        }
+    }
+
+    #[test]
+    fn sizes_survive_every_mutation() {
+        let mut tree = BinaryTree::new();
+        for key in 0..100 {
+            tree.insert(key, ());
+        }
+        tree.insert(50, ());
+        tree.try_insert(50, ()).expect_err("Key should be occupied");
+        assert_balanced(&tree);
+
+        tree.pop_first();
+        tree.pop_last();
+        tree.remove(&50);
+        assert_balanced(&tree);
+        assert_eq!(tree.select(0), Some((&1, &())));
+        assert_eq!(tree.rank(&51), 49);
+
+        let mut upper = tree.split_off(&40);
+        assert_balanced(&tree);
+        assert_balanced(&upper);
+        tree.append(&mut upper);
+        tree.retain(|key, _| key % 3 != 0);
+        assert_balanced(&tree);
+        tree.extract_if(|key, _| key % 2 == 0).for_each(drop);
+        assert_balanced(&tree);
+    }
+
+    #[test]
+    fn cursor_walk() {
+        let mut tree = BinaryTree::new();
+        for key in (0..100).step_by(2) {
+            tree.insert(key, key);
+        }
+
+        let mut cursor = tree.cursor_mut(&31);
+        assert_eq!(cursor.key(), Some(&32));
+        for expected in (34..100).step_by(2) {
+            cursor.move_next();
+            assert_eq!(cursor.key(), Some(&expected));
+        }
+        cursor.move_next();
+        assert_eq!(cursor.key(), None);
+        assert_eq!(cursor.value_mut(), None);
+        cursor.move_next();
+        assert_eq!(cursor.key(), Some(&0));
+        cursor.move_prev();
+        assert_eq!(cursor.key(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.key(), Some(&98));
+        for expected in (0..98).step_by(2).rev() {
+            cursor.move_prev();
+            assert_eq!(cursor.key(), Some(&expected));
+            *cursor.value_mut().expect("Cursor should point at an entry") += 1;
+        }
+
+        assert_eq!(tree.get(&98), Some(&98));
+        assert_eq!(tree.get(&0), Some(&1));
+        assert_eq!(tree.cursor_mut(&1000).key(), None);
+        assert_eq!(BinaryTree::<i32, ()>::new().cursor_mut(&0).key(), None);
+    }
+
+    #[test]
+    fn cursor_mutations() {
+        let mut rng = Rng::new(291);
+        let mut tree = BinaryTree::new();
+        let mut reference = BTreeMap::new();
+        for _ in 0..300 {
+            let key = rng.below(1000) * 4;
+            tree.insert(key, ());
+            reference.insert(key, ());
+        }
+
+        let mut cursor = tree.cursor_mut(&0);
+        let mut position = 0;
+        for _ in 0..2_000 {
+            let keys: Vec<_> = reference.keys().copied().collect();
+            assert_eq!(cursor.key(), keys.get(position));
+            match rng.below(5) {
+                0 => {
+                    cursor.move_next();
+                    position = if position >= keys.len() {
+                        0
+                    } else {
+                        position + 1
+                    };
+                }
+                1 => {
+                    cursor.move_prev();
+                    position = if position == keys.len() {
+                        keys.len().saturating_sub(1)
+                    } else if position == 0 {
+                        keys.len()
+                    } else {
+                        position - 1
+                    };
+                }
+                2 => {
+                    let removed = cursor.remove_current().map(|(key, _)| key);
+                    let expected = keys.get(position).copied();
+                    assert_eq!(removed, expected);
+                    if let Some(key) = expected {
+                        reference.remove(&key);
+                    }
+                }
+                3 => {
+                    let prev = position.checked_sub(1).map_or(-1, |p| keys[p] as i64);
+                    let current = keys.get(position).map_or(i64::MAX, |&k| k as i64);
+                    if current - prev > 1 {
+                        let key = (prev + 1) as u64;
+                        cursor.insert_before(key, ());
+                        reference.insert(key, ());
+                        position += 1;
+                    }
+                }
+                _ => {
+                    let current = keys.get(position).map_or(-1, |&k| k as i64);
+                    let next_position = if position >= keys.len() {
+                        0
+                    } else {
+                        position + 1
+                    };
+                    let next = keys.get(next_position).map_or(i64::MAX, |&k| k as i64);
+                    if next - current > 1 {
+                        let key = (current + 1) as u64;
+                        cursor.insert_after(key, ());
+                        reference.insert(key, ());
+                        if position >= keys.len() {
+                            position = keys.len() + 1;
+                        }
+                    }
+                }
+            }
+        }
+        drop(cursor);
+
+        assert_eq!(tree.len(), reference.len());
+        assert_balanced(&tree);
+        assert!(tree.keys().eq(reference.keys()));
+    }
+
+    #[test]
+    #[should_panic(expected = "key does not fit before the cursor position")]
+    fn cursor_insert_out_of_order() {
+        let mut tree = BinaryTree::new();
+        tree.insert(1, ());
+        tree.insert(5, ());
+        tree.cursor_mut(&5).insert_before(7, ());
+    }
+
+    #[test]
+    fn display_structure() {
+        assert_eq!(
+            format!("{}", BinaryTree::<i32, ()>::new().display_structure()),
+            "(empty)"
+        );
+
+        let mut tree = BinaryTree::new();
+        for (key, value) in [(4, 'd'), (2, 'b'), (6, 'f'), (1, 'a'), (3, 'c'), (7, 'g')] {
+            tree.insert(key, value);
+        }
+        let expected = "\
+4: 'd'
+├── L 2: 'b'
+│   ├── L 1: 'a'
+│   └── R 3: 'c'
+└── R 6: 'f'
+    └── R 7: 'g'";
+        assert_eq!(format!("{}", tree.display_structure()), expected);
+        assert_eq!(format!("{:?}", tree.display_structure()), expected);
+
+        let mut tree = BinaryTree::new();
+        tree.insert("only", 1);
+        assert_eq!(format!("{}", tree.display_structure()), "\"only\": 1");
+    }
+
+    #[test]
+    fn double_ended_iterators_meet_in_the_middle() {
+        let mut rng = Rng::new(294);
+        for n in [0_u64, 1, 2, 3, 10, 100, 257] {
+            let mut tree = BinaryTree::new();
+            for key in 0..n {
+                tree.insert((key * 7919) % n, key);
+            }
+
+            let mut iter = tree.iter();
+            let mut keys = tree.keys();
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            loop {
+                assert_eq!(iter.len(), n as usize - front.len() - back.len());
+                assert_eq!(keys.len(), iter.len());
+                let key = if rng.below(2) == 0 {
+                    let key = iter.next().map(|(key, _)| *key);
+                    assert_eq!(keys.next().copied(), key);
+                    front.extend(key);
+                    key
+                } else {
+                    let key = iter.next_back().map(|(key, _)| *key);
+                    assert_eq!(keys.next_back().copied(), key);
+                    back.extend(key);
+                    key
+                };
+                if key.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn reversed_iterators() {
+        let mut tree = BinaryTree::new();
+        for key in [5, 3, 8, 1, 4, 7, 9] {
+            tree.insert(key, key * 10);
+        }
+        let descending = [9, 8, 7, 5, 4, 3, 1];
+
+        assert!(tree.iter().rev().map(|(k, _)| *k).eq(descending));
+        assert!(tree.keys().rev().copied().eq(descending));
+        assert!(tree.values().rev().map(|v| v / 10).eq(descending));
+        assert!(tree.range(2..8).rev().map(|(k, _)| *k).eq([7, 5, 4, 3]));
+        for (key, value) in tree.iter_mut().rev().take(2) {
+            *value = -key;
+        }
+        for (key, value) in tree.range_mut(..=3).rev() {
+            *value = -key;
+        }
+        assert!(tree.values().copied().eq([-1, -3, 40, 50, 70, -8, -9]));
+        assert_eq!(tree.iter_mut().len(), 7);
+        assert_eq!(tree.values().len(), 7);
+
+        let mut into_iter = tree.clone().into_iter();
+        assert_eq!(into_iter.len(), 7);
+        assert_eq!(into_iter.next_back(), Some((9, -9)));
+        assert_eq!(into_iter.next(), Some((1, -1)));
+        assert_eq!(into_iter.len(), 5);
+        assert!(tree.clone().into_keys().rev().eq(descending));
+        assert_eq!(tree.clone().into_values().len(), 7);
+        assert_eq!(tree.into_values().next_back(), Some(-9));
+    }
+
+    #[test]
+    fn map_values() {
+        let mut rng = Rng::new(295);
+        let mut tree = BinaryTree::new();
+        for _ in 0..500 {
+            let key = rng.below(1000);
+            tree.insert(key, key.to_le_bytes());
+        }
+        let keys: Vec<u64> = tree.keys().copied().collect();
+        let heights = tree.height();
+
+        let mut visited = Vec::new();
+        let mapped = tree.map_values(|key, bytes| {
+            visited.push(*key);
+            u64::from_le_bytes(bytes) * 2
+        });
+
+        assert_eq!(visited, keys);
+        assert_eq!(mapped.len(), keys.len());
+        assert_eq!(mapped.height(), heights);
+        assert!(mapped.keys().eq(&keys));
+        assert!(mapped.iter().all(|(key, value)| *value == key * 2));
+        assert_balanced(&mapped);
+    }
+
+    #[test]
+    fn from_array() {
+        let tree = BinaryTree::from([(3, 'c'), (1, 'a'), (2, 'b'), (1, 'd'), (3, 'e')]);
+        assert_eq!(tree.len(), 3);
+        assert_balanced(&tree);
+        assert_eq!(Vec::from(tree), [(1, 'd'), (2, 'b'), (3, 'e')]);
+
+        let empty: BinaryTree<i32, char> = BinaryTree::from([]);
+        assert!(empty.is_empty());
+
+        let collected: BinaryTree<_, _> = (0..100).rev().map(|key| (key % 10, key)).collect();
+        assert_eq!(collected.len(), 10);
+        assert!(collected.values().copied().eq(0..10));
+    }
+
+    #[test]
+    fn is_balanced() {
+        let empty: BinaryTree<i32, ()> = BinaryTree::new();
+        assert!(empty.is_balanced());
+        assert!(BinaryTree::from([(1, ())]).is_balanced());
+
+        let perfect = BinaryTree::from((0..15).map(|key| (key, ())).collect::<BTreeMap<_, _>>());
+        assert_eq!(perfect.height(), 4);
+        assert!(perfect.is_balanced());
+
+        let mut root = None;
+        for key in 0..3 {
+            let height = BinaryTreeNode::height(&root) + 1;
+            root = Some(Box::new(BinaryTreeNode {
+                key,
+                value: (),
+                height,
+                size: height,
+                children: (root, None),
+            }));
+        }
+        let mut chain = BinaryTree { root, len: 3 };
+        assert!(!chain.is_balanced());
+        chain.rebalance();
+        assert!(chain.is_balanced());
+
+        let mut rng = Rng::new(300);
+        let mut tree = BinaryTree::new();
+        for _ in 0..1000 {
+            tree.insert(rng.below(500), ());
+            assert!(tree.is_balanced());
+        }
+    }
+
+    #[test]
+    fn rebalance() {
+        let mut root = None;
+        for key in 0..100 {
+            let height = BinaryTreeNode::height(&root) + 1;
+            root = Some(Box::new(BinaryTreeNode {
+                key,
+                value: key * 2,
+                height,
+                size: height,
+                children: (root, None),
+            }));
+        }
+        let mut skewed = BinaryTree { root, len: 100 };
+        let before: Vec<_> = skewed.iter().map(|(k, v)| (*k, *v)).collect();
+        skewed.rebalance();
+        assert_eq!(skewed.height(), 7);
+        assert_balanced(&skewed);
+        assert!(skewed.iter().map(|(k, v)| (*k, *v)).eq(before));
+
+        let mut rng = Rng::new(299);
+        for n in [0_usize, 1, 2, 3, 4, 7, 8, 15, 16, 100, 1000, 1023, 1024] {
+            let mut tree = BinaryTree::new();
+            while tree.len() < n {
+                let key = rng.below(10 * n as u64 + 1);
+                tree.insert(key, key + 1);
+            }
+            let before = tree.clone();
+
+            tree.rebalance();
+            // One more than the position of the highest set bit, i.e. `floor(log2(n)) + 1`.
+            let bound = (usize::BITS - n.leading_zeros()) as usize;
+            assert!(tree.height() <= bound);
+            assert_balanced(&tree);
+            assert!(tree.iter().eq(before.iter()));
+
+            tree.insert(u64::MAX, 0);
+            assert_balanced(&tree);
+        }
+    }
+
+    #[test]
+    fn drop_long_chain() {
+        let mut root = None;
+        for key in (0..1_000_000).rev() {
+            root = Some(Box::new(BinaryTreeNode {
+                key,
+                value: (),
+                height: 1,
+                size: 1,
+                children: (None, root),
+            }));
+        }
+        drop(BinaryTree {
+            root,
+            len: 1_000_000,
+        });
+    }
+
+    #[test]
+    fn drop_counts() {
+        let drops = core::cell::Cell::new(0);
+        let mut tree = BinaryTree::new();
+        for key in 0..1000 {
+            tree.insert(key, DropCounter(&drops));
+        }
+        drop(tree);
+        assert_eq!(drops.get(), 1000);
+    }
+
+    #[test]
+    fn many_sequential_keys() {
+        let mut tree = BinaryTree::new();
+        for key in 0..500_000 {
+            assert_eq!(tree.insert(key, key), None);
+        }
+
+        assert_eq!(tree.len(), 500_000);
+        for key in [0, 1, 250_000, 499_999] {
+            assert_eq!(tree.get(&key), Some(&key));
+        }
+        *tree.get_mut(&499_999).expect("Key should be present") = 0;
+        assert_eq!(tree.get(&499_999), Some(&0));
+        assert_eq!(tree.get(&500_000), None);
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut tree = BinaryTree::new();
+        tree.insert(String::from("apple"), 1);
+        tree.insert(String::from("pear"), 2);
+
+        tree["apple"] = 10;
+        tree[&String::from("pear")] += 5;
+        assert_eq!(tree["apple"], 10);
+        assert_eq!(tree["pear"], 7);
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Key is not present in the binary tree")]
+    fn index_mut_nonexistent() {
+        let mut tree = BinaryTree::new();
+        tree.insert(String::from("apple"), 1);
+        tree["plum"] = 2;
+    }
+
+    #[test]
+    #[should_panic(expected = "Key is not present in the binary tree")]
+    fn index_borrowed_nonexistent() {
+        let mut tree = BinaryTree::new();
+        tree.insert(String::from("apple"), 1);
+        let _ = tree["plum"];
     }
 
     #[test]
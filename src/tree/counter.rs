@@ -0,0 +1,292 @@
+use crate::tree::binary_tree::{self, BinaryTree};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+/// An ordered multiset that counts how many times each item was added, like Python's
+/// `collections.Counter`.
+///
+/// The counts are kept in a [`BinaryTree`], so items must implement [`Ord`] and are iterated in
+/// ascending order. Items whose count drops to zero are removed, so every item the counter holds
+/// has a count of at least one.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::counter::Counter;
+///
+/// let mut letters: Counter<char> = "mississippi".chars().collect();
+/// assert_eq!(letters.count(&'s'), 4);
+/// assert_eq!(letters.count(&'x'), 0);
+/// assert_eq!(letters.most_common(2), [(&'i', 4), (&'s', 4)]);
+///
+/// letters.remove(&'m');
+/// assert!(letters.iter().eq([(&'i', 4), (&'p', 2), (&'s', 4)]));
+/// assert_eq!(letters.total(), 10);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counter<T> {
+    counts: BinaryTree<T, usize>,
+    total: usize,
+}
+
+impl<T> Counter<T> {
+    /// Constructs an empty counter.
+    pub fn new() -> Self {
+        Self {
+            counts: BinaryTree::new(),
+            total: 0,
+        }
+    }
+
+    /// Returns the number of distinct items in the counter.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns `true` if the counter holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Removes every item from the counter.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns the sum of the counts of every item.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns an iterator over the distinct items and their counts, in ascending order of the
+    /// items.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.counts.iter(),
+        }
+    }
+}
+
+impl<T: Ord> Counter<T> {
+    /// Adds one occurrence of `item`.
+    pub fn add(&mut self, item: T) {
+        self.add_n(item, 1);
+    }
+
+    /// Adds `n` occurrences of `item`. Adding zero occurrences does nothing.
+    pub fn add_n(&mut self, item: T, n: usize) {
+        if n == 0 {
+            return;
+        }
+        match self.counts.get_mut(&item) {
+            Some(count) => *count += n,
+            None => {
+                self.counts.insert(item, n);
+            }
+        }
+        self.total += n;
+    }
+
+    /// Returns how many times `item` occurs, which is zero if it is not present.
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Removes one occurrence of `item`, returning `true` if it was present. The item is removed
+    /// entirely once its count reaches zero.
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_n(item, 1) == 1
+    }
+
+    /// Removes up to `n` occurrences of `item`, returning how many were removed. The item is
+    /// removed entirely once its count reaches zero.
+    pub fn remove_n<Q>(&mut self, item: &Q, n: usize) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = match self.counts.get_mut(item) {
+            Some(count) if *count > n => {
+                *count -= n;
+                n
+            }
+            Some(_) => self.counts.remove(item).unwrap_or(0),
+            None => 0,
+        };
+        self.total -= removed;
+        removed
+    }
+
+    /// Removes every occurrence of `item`, returning its count.
+    pub fn remove_all<Q>(&mut self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_n(item, usize::MAX)
+    }
+
+    /// Returns the `k` items with the highest counts, from most to least common.
+    ///
+    /// Items with equal counts are listed in ascending order, so the result only depends on the
+    /// contents of the counter. If it holds fewer than `k` distinct items, all of them are
+    /// returned.
+    pub fn most_common(&self, k: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<_> = self.iter().collect();
+        // The sort is stable, so ties keep the ascending order of the iterator.
+        items.sort_by(|(_, a), (_, b)| b.cmp(a));
+        items.truncate(k);
+        items
+    }
+}
+
+impl<T: Ord> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Counter<T> {
+    /// Builds a counter from a stream of items, counting every occurrence.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Counter<T> {
+    type Item = (&'a T, usize);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the items of a [`Counter`] and their counts, in ascending order.
+///
+/// This `struct` is created by [`Counter::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    inner: binary_tree::Iter<'a, T, usize>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(item, count)| (item, *count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(item, count)| (item, *count))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeMap, string::String};
+
+    #[test]
+    fn counts_cross_zero() {
+        let mut counter = Counter::new();
+        counter.add_n("a", 2);
+        counter.add("b");
+        assert_eq!(counter.total(), 3);
+
+        assert!(counter.remove("a"));
+        assert_eq!(counter.count("a"), 1);
+        assert!(counter.remove("a"));
+        assert_eq!(counter.count("a"), 0);
+        assert_eq!(counter.len(), 1);
+        assert!(!counter.remove("a"));
+        assert_eq!(counter.total(), 1);
+
+        counter.add("a");
+        assert_eq!(counter.count("a"), 1);
+        assert_eq!(counter.remove_n("a", 5), 1);
+        assert_eq!(counter.remove_all("b"), 1);
+        assert!(counter.is_empty());
+        assert_eq!(counter.total(), 0);
+
+        counter.add_n("c", 0);
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn most_common_ties() {
+        let counter: Counter<char> = "cabbcad".chars().collect();
+        assert_eq!(counter.most_common(3), [(&'a', 2), (&'b', 2), (&'c', 2)]);
+        assert_eq!(
+            counter.most_common(4),
+            [(&'a', 2), (&'b', 2), (&'c', 2), (&'d', 1)]
+        );
+        assert_eq!(counter.most_common(10).len(), 4);
+        assert!(counter.most_common(0).is_empty());
+
+        let mut counter = counter;
+        counter.add('d');
+        counter.add('d');
+        assert_eq!(counter.most_common(2), [(&'d', 3), (&'a', 2)]);
+    }
+
+    #[test]
+    fn borrowed_items() {
+        let mut words: Counter<String> = ["x", "y", "x"].iter().map(|&w| String::from(w)).collect();
+        assert_eq!(words.count("x"), 2);
+        assert!(words.remove("y"));
+        assert!(words.iter().eq([(&String::from("x"), 2)]));
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = Rng::new(453);
+        let mut counter = Counter::new();
+        let mut model = BTreeMap::new();
+        for _ in 0..5_000 {
+            let item = rng.below(64);
+            let n = rng.below(4) as usize;
+            if rng.below(2) == 0 {
+                counter.add_n(item, n);
+                if n > 0 {
+                    *model.entry(item).or_insert(0) += n;
+                }
+            } else {
+                let present = model.get(&item).copied().unwrap_or(0);
+                assert_eq!(counter.remove_n(&item, n), present.min(n));
+                if present > n {
+                    model.insert(item, present - n);
+                } else {
+                    model.remove(&item);
+                }
+            }
+            assert_eq!(counter.count(&item), model.get(&item).copied().unwrap_or(0));
+        }
+        assert!(counter
+            .iter()
+            .eq(model.iter().map(|(item, count)| (item, *count))));
+        assert_eq!(counter.total(), model.values().sum::<usize>());
+        assert_eq!(counter.len(), model.len());
+    }
+}
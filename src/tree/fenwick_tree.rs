@@ -0,0 +1,292 @@
+use crate::monoid::{Group, Monoid, Sum};
+use alloc::vec::Vec;
+use core::{marker::PhantomData, ops::Range};
+
+/// A binary indexed tree over a sequence of values, supporting point updates and prefix
+/// aggregates in `O(log(n))` time.
+///
+/// The values are combined with the operation of the [`Monoid`] `M`, which defaults to [`Sum`].
+/// Since updates fold a change into the middle of a prefix, the operation must also be
+/// commutative. Entry `i` of the underlying array (counting from one) holds the combination of
+/// the `i & i.wrapping_neg()` values ending at position `i`, so every prefix decomposes into one
+/// entry per set bit of its length.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::fenwick_tree::FenwickTree;
+///
+/// let mut scores: FenwickTree<u64> = FenwickTree::from_slice(&[5, 0, 3, 2]);
+/// scores.add(1, 4);
+///
+/// assert_eq!(scores.prefix_sum(2), 9);
+/// assert_eq!(scores.range_sum(1..3), 7);
+/// assert_eq!(scores.get(1), 4);
+/// ```
+pub struct FenwickTree<T, M = Sum> {
+    tree: Vec<T>,
+    monoid: PhantomData<M>,
+}
+
+/// Returns the lowest set bit of `i`, which is the number of values summarised by entry `i`.
+fn low_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+impl<T, M> FenwickTree<T, M>
+where
+    M: Monoid<T>,
+{
+    /// Constructs a tree of `len` identity elements.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            tree: (0..len).map(|_| M::identity()).collect(),
+            monoid: PhantomData,
+        }
+    }
+
+    /// Builds a tree over `values` in linear time.
+    pub fn from_slice(values: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        let mut tree = values.to_vec();
+        for i in 1..=tree.len() {
+            let parent = i + low_bit(i);
+            if parent <= tree.len() {
+                tree[parent - 1] = M::combine(&tree[parent - 1], &tree[i - 1]);
+            }
+        }
+        Self {
+            tree,
+            monoid: PhantomData,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the tree holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Removes every value, leaving a tree of length zero.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Combines `delta` into the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(index < self.len(), "Index out of bounds in FenwickTree");
+        let mut i = index + 1;
+        while i <= self.tree.len() {
+            self.tree[i - 1] = M::combine(&self.tree[i - 1], &delta);
+            i += low_bit(i);
+        }
+    }
+
+    /// Returns the combination of the first `len` values, or the identity if `len` is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the length of the tree.
+    pub fn prefix_sum(&self, len: usize) -> T {
+        assert!(
+            len <= self.len(),
+            "Prefix length out of bounds in FenwickTree"
+        );
+        let mut sum = M::identity();
+        let mut i = len;
+        while i > 0 {
+            sum = M::combine(&self.tree[i - 1], &sum);
+            i -= low_bit(i);
+        }
+        sum
+    }
+
+    /// Returns the index of the first value at which the running combination reaches
+    /// `at_least`, or the length of the tree if it never does.
+    ///
+    /// This binary searches over the prefix sums, so they must never decrease. For sums that
+    /// means no value may be negative.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::fenwick_tree::FenwickTree;
+    ///
+    /// // The number of samples that fell into each bucket.
+    /// let histogram: FenwickTree<u32> = FenwickTree::from_slice(&[4, 0, 3, 3]);
+    ///
+    /// // The median, sample number 5 of 10, falls into bucket 2.
+    /// assert_eq!(histogram.find_prefix(&5), 2);
+    /// assert_eq!(histogram.find_prefix(&4), 0);
+    /// assert_eq!(histogram.find_prefix(&11), 4);
+    /// ```
+    pub fn find_prefix(&self, at_least: &T) -> usize
+    where
+        T: PartialOrd,
+    {
+        let len = self.tree.len();
+        if len == 0 {
+            return 0;
+        }
+
+        // Descend from the largest power of two, only stepping ahead while the prefix stays below
+        // the target.
+        let mut position = 0;
+        let mut sum = M::identity();
+        let mut step = 1 << (usize::BITS - 1 - len.leading_zeros());
+        while step > 0 {
+            if position + step <= len {
+                let next = M::combine(&sum, &self.tree[position + step - 1]);
+                if next < *at_least {
+                    position += step;
+                    sum = next;
+                }
+            }
+            step >>= 1;
+        }
+        position
+    }
+}
+
+impl<T, M> FenwickTree<T, M>
+where
+    M: Group<T>,
+{
+    /// Returns the combination of the values in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is greater than its end.
+    pub fn range_sum(&self, range: Range<usize>) -> T {
+        assert!(
+            range.start <= range.end,
+            "range start is greater than range end in FenwickTree"
+        );
+        M::difference(&self.prefix_sum(range.end), &self.prefix_sum(range.start))
+    }
+
+    /// Returns the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> T {
+        assert!(index < self.len(), "Index out of bounds in FenwickTree");
+        self.range_sum(index..index + 1)
+    }
+}
+
+impl<T: Clone, M> Clone for FenwickTree<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            monoid: PhantomData,
+        }
+    }
+}
+
+impl<T: core::fmt::Debug, M> core::fmt::Debug for FenwickTree<T, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FenwickTree")
+            .field("tree", &self.tree)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::Min;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn randomized_against_vec() {
+        let mut rng = Rng::new(441);
+        let initial: Vec<i64> = (0..300).map(|_| rng.below(100) as i64 - 50).collect();
+        let mut tree: FenwickTree<i64> = FenwickTree::from_slice(&initial);
+        let mut reference = initial;
+
+        for _ in 0..5_000 {
+            let index = rng.below(300) as usize;
+            let delta = rng.below(100) as i64 - 50;
+            tree.add(index, delta);
+            reference[index] += delta;
+
+            let len = rng.below(301) as usize;
+            assert_eq!(tree.prefix_sum(len), reference[..len].iter().sum::<i64>());
+            let (a, b) = (rng.below(301) as usize, rng.below(301) as usize);
+            let range = a.min(b)..a.max(b);
+            assert_eq!(
+                tree.range_sum(range.clone()),
+                reference[range].iter().sum::<i64>()
+            );
+            let probe = rng.below(300) as usize;
+            assert_eq!(tree.get(probe), reference[probe]);
+        }
+    }
+
+    #[test]
+    fn from_slice_matches_adds() {
+        let values: Vec<u32> = (1..=37).collect();
+        let built: FenwickTree<u32> = FenwickTree::from_slice(&values);
+        let mut added: FenwickTree<u32> = FenwickTree::with_len(values.len());
+        for (index, value) in values.iter().enumerate() {
+            added.add(index, *value);
+        }
+        assert_eq!(built.tree, added.tree);
+    }
+
+    #[test]
+    fn find_prefix_against_vec() {
+        let mut rng = Rng::new(4410);
+        for len in [0, 1, 2, 7, 64, 100] {
+            let values: Vec<u64> = (0..len).map(|_| rng.below(5)).collect();
+            let tree: FenwickTree<u64> = FenwickTree::from_slice(&values);
+            let total: u64 = values.iter().sum();
+
+            for at_least in 0..=total + 1 {
+                let expected = (0..len)
+                    .find(|&i| values[..=i].iter().sum::<u64>() >= at_least)
+                    .unwrap_or(len);
+                assert_eq!(tree.find_prefix(&at_least), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn prefix_minimum() {
+        let mut tree: FenwickTree<i32, Min> = FenwickTree::from_slice(&[5, 8, 3, 9, 7]);
+        assert_eq!(tree.prefix_sum(0), i32::MAX);
+        assert_eq!(tree.prefix_sum(2), 5);
+        assert_eq!(tree.prefix_sum(5), 3);
+
+        // Adding under `Min` lowers the value.
+        tree.add(1, 1);
+        assert_eq!(tree.prefix_sum(2), 1);
+        assert_eq!(tree.prefix_sum(1), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds in FenwickTree")]
+    fn add_out_of_bounds() {
+        let mut tree: FenwickTree<u8> = FenwickTree::with_len(3);
+        tree.add(3, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Prefix length out of bounds in FenwickTree")]
+    fn prefix_out_of_bounds() {
+        let tree: FenwickTree<u8> = FenwickTree::with_len(3);
+        let _ = tree.prefix_sum(4);
+    }
+}
@@ -0,0 +1,622 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{cmp::Ordering, ops::Range};
+
+/// A balanced tree of half-open ranges, each with a value, that answers overlap queries.
+///
+/// The entries are kept in an AVL tree ordered by the start and then the end of their ranges.
+/// Every node also remembers the largest range end within its subtree, which lets queries skip
+/// any subtree that ends before the query starts. Finding `m` overlapping entries out of `n`
+/// takes `O((m + 1) log(n))` time.
+///
+/// Ranges are half-open, so `2..5` and `5..8` do not overlap. An empty range, such as `3..3` or
+/// the inverted `5..2`, can be stored and iterated over but contains no points, so it never shows
+/// up in query results; an empty query matches nothing. The same range may be inserted several
+/// times. Entries with identical ranges are kept in the order they were inserted, both when
+/// iterating and when removing.
+///
+/// The range ends are cloned into the nodes above them, which is why keys need to be [`Clone`].
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::interval_tree::IntervalTree;
+///
+/// let mut meetings = IntervalTree::new();
+/// meetings.insert(9..11, "standup");
+/// meetings.insert(10..12, "review");
+/// meetings.insert(13..14, "lunch");
+///
+/// let at_ten: Vec<_> = meetings.query_point(&10).map(|(_, name)| *name).collect();
+/// assert_eq!(at_ten, ["standup", "review"]);
+///
+/// let afternoon: Vec<_> = meetings.query_interval(&(11..15)).map(|(_, name)| *name).collect();
+/// assert_eq!(afternoon, ["review", "lunch"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntervalTree<K, V> {
+    root: NodeChild<K, V>,
+    len: usize,
+}
+
+type NodeChild<K, V> = Option<Box<IntervalTreeNode<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct IntervalTreeNode<K, V> {
+    range: Range<K>,
+    value: V,
+    /// The largest range end in the subtree rooted at this node.
+    max_end: K,
+    /// The number of nodes on the longest path from this node down to a leaf, including itself.
+    height: usize,
+    children: (NodeChild<K, V>, NodeChild<K, V>),
+}
+
+impl<K, V> IntervalTree<K, V> {
+    /// Constructs an empty tree.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of entries in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every entry from the tree.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Returns an iterator over every entry, sorted by the start and then the end of its range.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            iter.stack.push(current);
+            node = current.children.0.as_deref();
+        }
+        iter
+    }
+}
+
+impl<K, V> IntervalTree<K, V>
+where
+    K: Ord + Clone,
+{
+    /// Inserts a range with its value into the tree. If the range is already present, the new
+    /// entry is placed after the existing ones.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        let node = Box::new(IntervalTreeNode {
+            max_end: range.end.clone(),
+            range,
+            value,
+            height: 1,
+            children: (None, None),
+        });
+        IntervalTreeNode::insert(&mut self.root, node);
+        self.len += 1;
+    }
+
+    /// Removes the earliest inserted entry whose range is identical to `range` and returns its
+    /// value, or [`None`] if there is no such entry.
+    pub fn remove(&mut self, range: &Range<K>) -> Option<V> {
+        let (_, value) = IntervalTreeNode::remove_first(&mut self.root, range)?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns an iterator over the entries whose range contains `point`, sorted like
+    /// [`IntervalTree::iter`].
+    pub fn query_point(&self, point: &K) -> Overlapping<'_, K, V> {
+        Overlapping::new(self, point.clone(), Query::Point)
+    }
+
+    /// Returns an iterator over the entries whose range shares at least one point with `range`,
+    /// sorted like [`IntervalTree::iter`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(0..10, 'a');
+    /// tree.insert(4..4, 'b');
+    /// tree.insert(10..12, 'c');
+    ///
+    /// // `4..4` is empty and `10..12` only starts where the query ends.
+    /// let hits: Vec<_> = tree.query_interval(&(3..10)).map(|(_, v)| *v).collect();
+    /// assert_eq!(hits, ['a']);
+    /// ```
+    pub fn query_interval(&self, range: &Range<K>) -> Overlapping<'_, K, V> {
+        if range.start >= range.end {
+            return Overlapping {
+                stack: Vec::new(),
+                start: range.start.clone(),
+                query: Query::Interval(range.end.clone()),
+            };
+        }
+        Overlapping::new(
+            self,
+            range.start.clone(),
+            Query::Interval(range.end.clone()),
+        )
+    }
+}
+
+impl<K, V> IntervalTreeNode<K, V> {
+    fn height(link: &NodeChild<K, V>) -> usize {
+        link.as_ref().map_or(0, |node| node.height)
+    }
+}
+
+impl<K, V> IntervalTreeNode<K, V>
+where
+    K: Ord + Clone,
+{
+    fn cmp_ranges(a: &Range<K>, b: &Range<K>) -> Ordering {
+        a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+    }
+
+    /// Recomputes the height and largest range end of this node from its children.
+    fn update(&mut self) {
+        self.height = 1 + Self::height(&self.children.0).max(Self::height(&self.children.1));
+        let mut max_end = &self.range.end;
+        for child in [&self.children.0, &self.children.1].into_iter().flatten() {
+            if child.max_end > *max_end {
+                max_end = &child.max_end;
+            }
+        }
+        self.max_end = max_end.clone();
+    }
+
+    fn rotate_left(node: &mut Box<Self>) {
+        if let Some(mut right) = node.children.1.take() {
+            node.children.1 = right.children.0.take();
+            node.update();
+            core::mem::swap(node, &mut right);
+            node.children.0 = Some(right);
+            node.update();
+        }
+    }
+
+    fn rotate_right(node: &mut Box<Self>) {
+        if let Some(mut left) = node.children.0.take() {
+            node.children.0 = left.children.1.take();
+            node.update();
+            core::mem::swap(node, &mut left);
+            node.children.1 = Some(left);
+            node.update();
+        }
+    }
+
+    /// Restores the AVL property at `node`, assuming both of its subtrees satisfy it and differ
+    /// in height by at most two.
+    fn rebalance(node: &mut Box<Self>) {
+        node.update();
+        let (left, right) = (
+            Self::height(&node.children.0),
+            Self::height(&node.children.1),
+        );
+        if left > right + 1 {
+            if let Some(child) = node.children.0.as_mut() {
+                if Self::height(&child.children.0) < Self::height(&child.children.1) {
+                    Self::rotate_left(child);
+                }
+            }
+            Self::rotate_right(node);
+        } else if right > left + 1 {
+            if let Some(child) = node.children.1.as_mut() {
+                if Self::height(&child.children.1) < Self::height(&child.children.0) {
+                    Self::rotate_right(child);
+                }
+            }
+            Self::rotate_left(node);
+        }
+    }
+
+    /// Inserts `new` below `link`, after every node with an identical range.
+    fn insert(link: &mut NodeChild<K, V>, new: Box<Self>) {
+        match link {
+            None => *link = Some(new),
+            Some(node) => {
+                if Self::cmp_ranges(&new.range, &node.range) == Ordering::Less {
+                    Self::insert(&mut node.children.0, new);
+                } else {
+                    Self::insert(&mut node.children.1, new);
+                }
+                Self::rebalance(node);
+            }
+        }
+    }
+
+    /// Removes the leftmost node with a range identical to `range` from the subtree at `link`.
+    ///
+    /// Rotations keep the in-order sequence intact, so the leftmost such node is also the one that
+    /// was inserted first.
+    fn remove_first(link: &mut NodeChild<K, V>, range: &Range<K>) -> Option<(Range<K>, V)> {
+        let node = link.as_mut()?;
+        let removed = match Self::cmp_ranges(range, &node.range) {
+            Ordering::Less => Self::remove_first(&mut node.children.0, range),
+            Ordering::Greater => Self::remove_first(&mut node.children.1, range),
+            Ordering::Equal => match Self::remove_first(&mut node.children.0, range) {
+                Some(removed) => Some(removed),
+                None => return Self::unlink(link),
+            },
+        };
+        if removed.is_some() {
+            Self::rebalance(node);
+        }
+        removed
+    }
+
+    /// Removes the node at `link`, replacing it with the smallest node of its right subtree if it
+    /// has two children.
+    fn unlink(link: &mut NodeChild<K, V>) -> Option<(Range<K>, V)> {
+        let mut node = link.take()?;
+        *link = match (node.children.0.take(), node.children.1.take()) {
+            (None, right) => right,
+            (left, None) => left,
+            (left, right) => {
+                let mut right = right;
+                let mut min = Self::unlink_min(&mut right)?;
+                min.children = (left, right);
+                Self::rebalance(&mut min);
+                Some(min)
+            }
+        };
+        Some((node.range, node.value))
+    }
+
+    fn unlink_min(link: &mut NodeChild<K, V>) -> Option<Box<Self>> {
+        let node = link.as_mut()?;
+        if node.children.0.is_some() {
+            let min = Self::unlink_min(&mut node.children.0);
+            Self::rebalance(node);
+            min
+        } else {
+            let mut node = link.take()?;
+            *link = node.children.1.take();
+            Some(node)
+        }
+    }
+}
+
+impl<K, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V> Extend<(Range<K>, V)> for IntervalTree<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> FromIterator<(Range<K>, V)> for IntervalTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a IntervalTree<K, V> {
+    type Item = (&'a Range<K>, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`IntervalTree`].
+///
+/// This `struct` is created by [`IntervalTree::iter`].
+pub struct Iter<'a, K, V> {
+    /// The nodes whose entry and right subtree are still to be visited, the next one on top.
+    stack: Vec<&'a IntervalTreeNode<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        let mut child = node.children.1.as_deref();
+        while let Some(current) = child {
+            self.stack.push(current);
+            child = current.children.0.as_deref();
+        }
+        self.len -= 1;
+        Some((&node.range, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// The upper end of a query, either a point or the exclusive end of an interval.
+#[derive(Debug, Clone)]
+enum Query<K> {
+    Point,
+    Interval(K),
+}
+
+/// An iterator over the entries of an [`IntervalTree`] that overlap a point or an interval.
+///
+/// This `struct` is created by [`IntervalTree::query_point`] and
+/// [`IntervalTree::query_interval`].
+pub struct Overlapping<'a, K, V> {
+    /// The nodes whose entry and right subtree are still to be visited, the next one on top.
+    /// Subtrees whose ranges all end before the query starts are never pushed.
+    stack: Vec<&'a IntervalTreeNode<K, V>>,
+    /// The point of a point query, or the start of an interval query.
+    start: K,
+    query: Query<K>,
+}
+
+impl<'a, K: Ord, V> Overlapping<'a, K, V> {
+    fn new(tree: &'a IntervalTree<K, V>, start: K, query: Query<K>) -> Self {
+        let mut overlapping = Self {
+            stack: Vec::new(),
+            start,
+            query,
+        };
+        overlapping.push_left_spine(tree.root.as_deref());
+        overlapping
+    }
+
+    fn push_left_spine(&mut self, mut node: Option<&'a IntervalTreeNode<K, V>>) {
+        while let Some(current) = node {
+            if current.max_end <= self.start {
+                return;
+            }
+            self.stack.push(current);
+            node = current.children.0.as_deref();
+        }
+    }
+
+    /// Returns `true` if a range starting at `start` may still overlap the query.
+    fn starts_in_time(&self, start: &K) -> bool {
+        match &self.query {
+            Query::Point => *start <= self.start,
+            Query::Interval(end) => start < end,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            if !self.starts_in_time(&node.range.start) {
+                // Every entry after this one starts at least as late.
+                self.stack.clear();
+                return None;
+            }
+            self.push_left_spine(node.children.1.as_deref());
+
+            let range = &node.range;
+            if range.start < range.end && range.end > self.start {
+                return Some((range, &node.value));
+            }
+        }
+        None
+    }
+}
+
+impl<K: Clone, V> Clone for Overlapping<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            start: self.start.clone(),
+            query: self.query.clone(),
+        }
+    }
+}
+
+impl<K, V> core::fmt::Debug for Overlapping<'_, K, V>
+where
+    K: core::fmt::Debug + Ord + Clone,
+    V: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    /// Checks the search tree order, the AVL balance, the heights and the largest range ends.
+    fn assert_interval_tree<K: Ord + Clone + core::fmt::Debug, V>(tree: &IntervalTree<K, V>) {
+        fn check<K: Ord + Clone + core::fmt::Debug, V>(
+            node: &IntervalTreeNode<K, V>,
+        ) -> (usize, usize) {
+            let mut max_end = &node.range.end;
+            let (mut count, mut heights) = (1, [0, 0]);
+            for (side, child) in [&node.children.0, &node.children.1].into_iter().enumerate() {
+                if let Some(child) = child {
+                    let ordering = IntervalTreeNode::<K, V>::cmp_ranges(&child.range, &node.range);
+                    if side == 0 {
+                        assert_ne!(ordering, Ordering::Greater);
+                    } else {
+                        assert_ne!(ordering, Ordering::Less);
+                    }
+                    let (size, height) = check(child);
+                    count += size;
+                    heights[side] = height;
+                    max_end = max_end.max(&child.max_end);
+                }
+            }
+            assert_eq!(&node.max_end, max_end);
+            assert!(heights[0].abs_diff(heights[1]) <= 1);
+            assert_eq!(node.height, 1 + heights[0].max(heights[1]));
+            (count, node.height)
+        }
+
+        let count = tree.root.as_deref().map_or(0, |root| check(root).0);
+        assert_eq!(count, tree.len());
+        let ranges: Vec<_> = tree.iter().map(|(range, _)| range).collect();
+        assert!(ranges
+            .windows(2)
+            .all(
+                |pair| IntervalTreeNode::<K, V>::cmp_ranges(pair[0], pair[1]) != Ordering::Greater
+            ));
+    }
+
+    #[test]
+    fn randomized_against_brute_force() {
+        let mut rng = Rng::new(440);
+        let mut tree = IntervalTree::new();
+        // Entries in insertion order, which a stable sort turns into the order of the tree.
+        let mut reference: Vec<(Range<u32>, u32)> = Vec::new();
+        let sorted = |entries: &[(Range<u32>, u32)]| {
+            let mut entries = entries.to_vec();
+            entries.sort_by(|a, b| IntervalTreeNode::<u32, u32>::cmp_ranges(&a.0, &b.0));
+            entries
+        };
+        let random_range = |rng: &mut Rng| {
+            // Inverted and empty ranges are included on purpose.
+            let start = rng.below(100) as u32;
+            start..start + rng.below(20) as u32 - 3.min(start)
+        };
+
+        for step in 0..3_000 {
+            let candidate = random_range(&mut rng);
+            if rng.below(3) == 0 && !reference.is_empty() {
+                // Remove an existing range most of the time, and a missing one otherwise.
+                let range = if rng.below(4) == 0 {
+                    candidate
+                } else {
+                    reference[rng.below(reference.len() as u64) as usize]
+                        .0
+                        .clone()
+                };
+                let position = reference.iter().position(|(r, _)| *r == range);
+                let expected = position.map(|position| reference.remove(position).1);
+                assert_eq!(tree.remove(&range), expected);
+            } else {
+                tree.insert(candidate.clone(), step);
+                reference.push((candidate, step));
+            }
+            assert_eq!(tree.len(), reference.len());
+            if step % 100 == 0 {
+                assert_interval_tree(&tree);
+            }
+
+            let expected = sorted(&reference);
+            let point = rng.below(120) as u32;
+            let hits: Vec<_> = tree
+                .query_point(&point)
+                .map(|(range, value)| (range.clone(), *value))
+                .collect();
+            let brute: Vec<_> = expected
+                .iter()
+                .filter(|(range, _)| range.contains(&point))
+                .cloned()
+                .collect();
+            assert_eq!(hits, brute);
+
+            let query = random_range(&mut rng);
+            let hits: Vec<_> = tree
+                .query_interval(&query)
+                .map(|(range, value)| (range.clone(), *value))
+                .collect();
+            let brute: Vec<_> = expected
+                .iter()
+                .filter(|(range, _)| {
+                    !range.is_empty()
+                        && !query.is_empty()
+                        && range.start < query.end
+                        && query.start < range.end
+                })
+                .cloned()
+                .collect();
+            assert_eq!(hits, brute);
+        }
+
+        assert_interval_tree(&tree);
+        let all: Vec<_> = tree.iter().map(|(r, v)| (r.clone(), *v)).collect();
+        assert_eq!(all, sorted(&reference));
+    }
+
+    #[test]
+    fn duplicate_ranges() {
+        let mut tree: IntervalTree<i32, char> =
+            [(1..4, 'a'), (0..2, 'b'), (1..4, 'c'), (1..4, 'd')]
+                .into_iter()
+                .collect();
+        let values: Vec<_> = tree.query_point(&3).map(|(_, v)| *v).collect();
+        assert_eq!(values, ['a', 'c', 'd']);
+
+        assert_eq!(tree.remove(&(1..4)), Some('a'));
+        assert_eq!(tree.remove(&(1..4)), Some('c'));
+        assert_eq!(tree.remove(&(1..3)), None);
+        let values: Vec<_> = tree.iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, ['b', 'd']);
+        assert_interval_tree(&tree);
+    }
+
+    #[test]
+    fn empty_ranges() {
+        let mut tree = IntervalTree::new();
+        tree.insert(5..5, ());
+        tree.insert(Range { start: 7, end: 3 }, ());
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.iter().count(), 2);
+        assert_eq!(tree.query_point(&5).count(), 0);
+        assert_eq!(tree.query_interval(&(0..10)).count(), 0);
+
+        tree.insert(0..10, ());
+        assert_eq!(tree.query_interval(&(4..4)).count(), 0);
+        assert_eq!(tree.query_interval(&Range { start: 6, end: 2 }).count(), 0);
+        assert_eq!(tree.query_interval(&(9..10)).count(), 1);
+        assert_eq!(tree.query_interval(&(10..11)).count(), 0);
+        assert_eq!(tree.remove(&Range { start: 7, end: 3 }), Some(()));
+        assert_interval_tree(&tree);
+    }
+
+    #[test]
+    fn sequential_inserts_stay_balanced() {
+        let tree: IntervalTree<u32, ()> = (0..1_000).map(|start| (start..start + 5, ())).collect();
+        assert_interval_tree(&tree);
+        assert!(tree.root.as_ref().map_or(0, |root| root.height) <= 15);
+        assert_eq!(tree.query_point(&500).count(), 5);
+    }
+}
@@ -0,0 +1,395 @@
+use crate::ord::TotalF32;
+use alloc::{collections::BinaryHeap, vec::Vec};
+
+/// A static k-d tree over points in `D`-dimensional space, each with a value, that answers
+/// nearest-neighbour and radius queries.
+///
+/// The tree is built once by [`KdTree::from_points`], which recursively splits the points at the
+/// median along one axis after the other. The result is stored in a single array in which every
+/// subtree occupies a contiguous slice with its root in the middle, so no pointers are needed.
+/// Queries skip every subtree whose splitting plane is farther away than the best candidates
+/// found so far.
+///
+/// Distances are Euclidean. Points may share coordinates; whenever two points are equally far from
+/// a query, the one that came first in the input to [`KdTree::from_points`] is preferred. Results
+/// are always sorted by distance and then by input order.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::kd_tree::KdTree;
+///
+/// let cities = KdTree::from_points(vec![
+///     ([0.0, 0.0], "Origin"),
+///     ([3.0, 4.0], "Far"),
+///     ([1.0, 1.0], "Near"),
+/// ]);
+///
+/// assert_eq!(cities.nearest(&[0.9, 0.8]), Some((&[1.0, 1.0], &"Near")));
+///
+/// let two: Vec<_> = cities.k_nearest(&[0.0, 0.5], 2).into_iter().map(|(_, v)| *v).collect();
+/// assert_eq!(two, ["Origin", "Near"]);
+///
+/// assert_eq!(cities.within_radius(&[3.0, 3.0], 1.0).len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KdTree<const D: usize, T> {
+    entries: Vec<Entry<D, T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<const D: usize, T> {
+    point: [f32; D],
+    value: T,
+    /// The position of the point in the input, used to break ties.
+    index: usize,
+}
+
+/// A query result, ordered by distance and then by input order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Candidate {
+    distance: TotalF32,
+    index: usize,
+    /// The position of the entry in the tree.
+    position: usize,
+}
+
+fn squared_distance<const D: usize>(a: &[f32; D], b: &[f32; D]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+impl<const D: usize, T> KdTree<D, T> {
+    /// Builds a tree over `points` in `O(n log(n))` time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `D` is zero or any coordinate is `NaN`.
+    pub fn from_points<I>(points: I) -> Self
+    where
+        I: IntoIterator<Item = ([f32; D], T)>,
+    {
+        assert!(D > 0, "a KdTree needs at least one dimension");
+        let mut entries: Vec<Entry<D, T>> = points
+            .into_iter()
+            .enumerate()
+            .map(|(index, (point, value))| {
+                assert!(
+                    point.iter().all(|coordinate| !coordinate.is_nan()),
+                    "KdTree coordinates must not be NaN"
+                );
+                Entry {
+                    point,
+                    value,
+                    index,
+                }
+            })
+            .collect();
+        Self::build(&mut entries, 0);
+        Self { entries }
+    }
+
+    /// Arranges `entries` so that the median along `axis` sits in the middle, with smaller
+    /// coordinates before it and larger ones after, then does the same for both halves along the
+    /// next axis.
+    fn build(entries: &mut [Entry<D, T>], axis: usize) {
+        if entries.len() <= 1 {
+            return;
+        }
+        let middle = entries.len() / 2;
+        entries.select_nth_unstable_by(middle, |a, b| {
+            a.point[axis]
+                .total_cmp(&b.point[axis])
+                .then(a.index.cmp(&b.index))
+        });
+        let (left, right) = entries.split_at_mut(middle);
+        let next = (axis + 1) % D;
+        Self::build(left, next);
+        Self::build(&mut right[1..], next);
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the tree holds no points.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every point from the tree.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns an iterator over the points and their values, in no particular order.
+    pub fn iter(&self) -> Iter<'_, D, T> {
+        Iter {
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Returns the point closest to `query`, or [`None`] if the tree is empty.
+    pub fn nearest(&self, query: &[f32; D]) -> Option<(&[f32; D], &T)> {
+        self.k_nearest(query, 1).into_iter().next()
+    }
+
+    /// Returns the `k` points closest to `query`, nearest first. Returns every point if there are
+    /// fewer than `k`.
+    pub fn k_nearest(&self, query: &[f32; D], k: usize) -> Vec<(&[f32; D], &T)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut collector = Collector::Nearest {
+            k,
+            best: BinaryHeap::with_capacity(k + 1),
+        };
+        self.search(0, self.entries.len(), 0, query, &mut collector);
+        self.results(collector.into_sorted_vec())
+    }
+
+    /// Returns every point whose distance to `query` is at most `radius`, nearest first.
+    pub fn within_radius(&self, query: &[f32; D], radius: f32) -> Vec<(&[f32; D], &T)> {
+        if radius.is_nan() || radius < 0.0 {
+            return Vec::new();
+        }
+        let mut collector = Collector::Radius {
+            limit: radius * radius,
+            found: Vec::new(),
+        };
+        self.search(0, self.entries.len(), 0, query, &mut collector);
+        self.results(collector.into_sorted_vec())
+    }
+
+    /// Offers every entry in `start..end` that may lie within the bound of `collector` to it,
+    /// nearest subtrees first.
+    fn search(
+        &self,
+        start: usize,
+        end: usize,
+        axis: usize,
+        query: &[f32; D],
+        collector: &mut Collector,
+    ) {
+        if start >= end {
+            return;
+        }
+        let middle = start + (end - start) / 2;
+        let entry = &self.entries[middle];
+        collector.offer(Candidate {
+            distance: TotalF32(squared_distance(&entry.point, query)),
+            index: entry.index,
+            position: middle,
+        });
+
+        let offset = query[axis] - entry.point[axis];
+        let next = (axis + 1) % D;
+        let (near, far) = if offset < 0.0 {
+            ((start, middle), (middle + 1, end))
+        } else {
+            ((middle + 1, end), (start, middle))
+        };
+        self.search(near.0, near.1, next, query, collector);
+        // Points exactly on the bound are still wanted, since they may win a tie.
+        if offset * offset <= collector.bound() {
+            self.search(far.0, far.1, next, query, collector);
+        }
+    }
+
+    fn results(&self, candidates: Vec<Candidate>) -> Vec<(&[f32; D], &T)> {
+        candidates
+            .into_iter()
+            .map(|candidate| {
+                let entry = &self.entries[candidate.position];
+                (&entry.point, &entry.value)
+            })
+            .collect()
+    }
+}
+
+/// Gathers the results of a query while the tree is searched.
+enum Collector {
+    /// Keeps the `k` best candidates, with the worst of them on top of the heap.
+    Nearest {
+        k: usize,
+        best: BinaryHeap<Candidate>,
+    },
+    /// Keeps every candidate within a squared distance of `limit`.
+    Radius { limit: f32, found: Vec<Candidate> },
+}
+
+impl Collector {
+    fn offer(&mut self, candidate: Candidate) {
+        match self {
+            Self::Nearest { k, best } => {
+                best.push(candidate);
+                if best.len() > *k {
+                    best.pop();
+                }
+            }
+            Self::Radius { limit, found } => {
+                if candidate.distance.0 <= *limit {
+                    found.push(candidate);
+                }
+            }
+        }
+    }
+
+    /// Returns the squared distance beyond which no candidate is wanted.
+    fn bound(&self) -> f32 {
+        match self {
+            Self::Nearest { k, best } => match best.peek() {
+                Some(worst) if best.len() == *k => worst.distance.0,
+                _ => f32::INFINITY,
+            },
+            Self::Radius { limit, .. } => *limit,
+        }
+    }
+
+    fn into_sorted_vec(self) -> Vec<Candidate> {
+        match self {
+            Self::Nearest { best, .. } => best.into_sorted_vec(),
+            Self::Radius { mut found, .. } => {
+                found.sort_unstable();
+                found
+            }
+        }
+    }
+}
+
+impl<'a, const D: usize, T> IntoIterator for &'a KdTree<D, T> {
+    type Item = (&'a [f32; D], &'a T);
+    type IntoIter = Iter<'a, D, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the points of a [`KdTree`] and their values, in no particular order.
+///
+/// This `struct` is created by [`KdTree::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, const D: usize, T> {
+    entries: core::slice::Iter<'a, Entry<D, T>>,
+}
+
+impl<'a, const D: usize, T> Iterator for Iter<'a, D, T> {
+    type Item = (&'a [f32; D], &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries
+            .next()
+            .map(|entry| (&entry.point, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<const D: usize, T> ExactSizeIterator for Iter<'_, D, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    /// Sorts every point by its distance to `query` and then by input order.
+    fn brute_force<const D: usize>(points: &[[f32; D]], query: &[f32; D]) -> Vec<(f32, usize)> {
+        let mut order: Vec<(f32, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| (squared_distance(point, query), index))
+            .collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        order
+    }
+
+    fn randomized_against_brute_force<const D: usize>(seed: u64, len: usize, grid: u64) {
+        let mut rng = Rng::new(seed);
+        // Coordinates on a coarse grid, so that duplicate points and exact ties are common.
+        let random_point = |rng: &mut Rng| {
+            let mut point = [0.0; D];
+            for coordinate in &mut point {
+                *coordinate = rng.below(grid) as f32 / 2.0;
+            }
+            point
+        };
+        let points: Vec<[f32; D]> = (0..len).map(|_| random_point(&mut rng)).collect();
+        let tree = KdTree::from_points(points.iter().copied().zip(0..));
+        assert_eq!(tree.len(), len);
+
+        for _ in 0..200 {
+            let query = random_point(&mut rng);
+            let expected = brute_force(&points, &query);
+            let indices = |results: Vec<(&[f32; D], &usize)>| {
+                results
+                    .into_iter()
+                    .map(|(_, index)| *index)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                tree.nearest(&query).map(|(_, index)| *index),
+                expected.first().map(|(_, index)| *index)
+            );
+
+            let k = rng.below(12) as usize;
+            let nearest: Vec<usize> = expected.iter().take(k).map(|(_, index)| *index).collect();
+            assert_eq!(indices(tree.k_nearest(&query, k)), nearest);
+
+            let radius = rng.below(grid) as f32 / 4.0;
+            let within: Vec<usize> = expected
+                .iter()
+                .take_while(|(distance, _)| *distance <= radius * radius)
+                .map(|(_, index)| *index)
+                .collect();
+            assert_eq!(indices(tree.within_radius(&query, radius)), within);
+        }
+    }
+
+    #[test]
+    fn randomized_2d() {
+        randomized_against_brute_force::<2>(443, 500, 40);
+        randomized_against_brute_force::<2>(4430, 300, 6);
+    }
+
+    #[test]
+    fn randomized_3d() {
+        randomized_against_brute_force::<3>(4431, 500, 20);
+    }
+
+    #[test]
+    fn ties_prefer_input_order() {
+        let tree = KdTree::from_points([([1.0], 'a'), ([-1.0], 'b'), ([1.0], 'c'), ([0.0], 'd')]);
+        assert_eq!(tree.nearest(&[0.5]), Some((&[1.0], &'a')));
+        let values: Vec<char> = tree
+            .k_nearest(&[0.0], 4)
+            .into_iter()
+            .map(|(_, value)| *value)
+            .collect();
+        assert_eq!(values, ['d', 'a', 'b', 'c']);
+        assert_eq!(tree.within_radius(&[1.0], 0.0).len(), 2);
+    }
+
+    #[test]
+    fn degenerate_queries() {
+        let empty: KdTree<2, ()> = KdTree::from_points([]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest(&[0.0, 0.0]), None);
+
+        let tree = KdTree::from_points([([0.0, 0.0], ()), ([1.0, 0.0], ())]);
+        assert!(tree.k_nearest(&[0.0, 0.0], 0).is_empty());
+        assert_eq!(tree.k_nearest(&[0.0, 0.0], 5).len(), 2);
+        assert!(tree.within_radius(&[0.0, 0.0], -1.0).is_empty());
+        assert!(tree.within_radius(&[0.0, 0.0], f32::NAN).is_empty());
+        assert_eq!(tree.iter().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "KdTree coordinates must not be NaN")]
+    fn nan_coordinates() {
+        let _ = KdTree::from_points([([0.0, f32::NAN], ())]);
+    }
+}
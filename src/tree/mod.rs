@@ -1,2 +1,183 @@
+//! # Complexity
+//!
+//! | Structure                                                   | Lookup      | Insertion   | Worst-case height | Rotations per insertion | Rotations per deletion |
+//! |-------------------------------------------------------------|-------------|-------------|-------------------|-------------------------|------------------------|
+//! | [`BinaryTree`](crate::tree::binary_tree::BinaryTree)        | `O(log(n))` | `O(log(n))` | `1.44 log2(n)`    | at most 2               | `O(log(n))`            |
+//! | [`RedBlackTree`](crate::tree::red_black_tree::RedBlackTree) | `O(log(n))` | `O(log(n))` | `2 log2(n)`       | at most 2               | at most 3              |
+//! | [`BTree`](crate::tree::b_tree::BTree)                       | `O(log(n))` | `O(log(n))` | `logB(n)`         | none, nodes split       | none, nodes merge      |
+//! | [`Treap`](crate::tree::treap::Treap)                        | `O(log(n))` | `O(log(n))` | `n`               | none, subtrees split    | none, subtrees merge   |
+//! | [`SplayTree`](crate::tree::splay_tree::SplayTree)           | `O(log(n))` | `O(log(n))` | `n`               | `O(log(n))`             | `O(log(n))`            |
+//!
+//! Both [`BinaryTree`](crate::tree::binary_tree::BinaryTree), an AVL tree, and
+//! [`RedBlackTree`](crate::tree::red_black_tree::RedBlackTree) restore their balance after an
+//! insertion with at most two rotations. The AVL tree balances more strictly and stays
+//! shallower, which keeps lookups as short as possible, while the red-black tree settles any
+//! removal with at most three rotations where the AVL tree may rotate at every level on the way
+//! back to the root, which makes it the better fit when removals are frequent.
+//! [`BTree`](crate::tree::b_tree::BTree) stores many entries per node, trading a few extra
+//! comparisons per level for far fewer allocations and pointer hops. The bounds for
+//! [`Treap`](crate::tree::treap::Treap) only hold in expectation over its random priorities, but
+//! in exchange it can be split and merged cheaply. The bounds for
+//! [`SplayTree`](crate::tree::splay_tree::SplayTree) are amortized, and it moves every key it
+//! touches to the root, which suits workloads that keep returning to the same few keys.
+
+/// Generates tests shared by every map-like tree in this module, so that they stay in lockstep.
+///
+/// `$tree` is the tree type and `$check` a function that validates the invariants of a tree,
+/// which is called after every operation in debug builds.
+#[cfg(test)]
+macro_rules! map_tests {
+    ($tree:ident, $check:expr) => {
+        mod map_tests {
+            use super::*;
+            use crate::test_util::{assert_clears, assert_empty, Rng};
+            use alloc::{collections::BTreeMap, string::String};
+
+            fn check<K: Ord, V>(tree: &$tree<K, V>) {
+                if cfg!(debug_assertions) {
+                    $check(tree);
+                }
+            }
+
+            #[test]
+            fn empty() {
+                let tree: $tree<i32, i32> = $tree::new();
+                assert_eq!(tree.get(&0), None);
+                assert_empty(&tree);
+                check(&tree);
+            }
+
+            #[test]
+            fn clear() {
+                let mut tree = $tree::new();
+                for key in 0..100 {
+                    tree.insert(key, key);
+                }
+                assert_clears(&mut tree);
+                assert_eq!(tree.get(&0), None);
+                check(&tree);
+
+                tree.insert(1, 2);
+                assert_eq!(tree.get(&1), Some(&2));
+                assert_eq!(tree.len(), 1);
+                check(&tree);
+            }
+
+            #[test]
+            fn replace() {
+                let mut tree = $tree::new();
+                assert_eq!(tree.insert(3, "Hello"), None);
+                assert_eq!(tree.insert(3, "World"), Some("Hello"));
+                assert_eq!(tree.get(&3), Some(&"World"));
+                assert_eq!(tree.len(), 1);
+                check(&tree);
+            }
+
+            #[test]
+            fn get_mut() {
+                let mut tree = $tree::new();
+                for key in [1, 2, 0] {
+                    tree.insert(key, key);
+                }
+
+                for key in [1, 2, 0] {
+                    *tree.get_mut(&key).expect("Key should be present") += 10;
+                }
+                for key in [1, 2, 0] {
+                    assert_eq!(tree.get(&key), Some(&(key + 10)));
+                }
+                assert_eq!(tree.get_mut(&3), None);
+            }
+
+            #[test]
+            fn borrowed_keys() {
+                let mut tree = $tree::new();
+                for key in ["b", "a", "c"] {
+                    tree.insert(String::from(key), key.len());
+                }
+
+                assert_eq!(tree.get("a"), Some(&1));
+                assert_eq!(tree.get("d"), None);
+                *tree.get_mut("b").expect("Key should be present") += 1;
+                assert_eq!(tree["b"], 2);
+                assert_eq!(tree.remove("c"), Some(1));
+                assert_eq!(tree.remove("c"), None);
+                assert_eq!(tree.len(), 2);
+                check(&tree);
+            }
+
+            #[test]
+            fn index() {
+                let mut tree = $tree::new();
+                tree.insert(0, 'A');
+                assert_eq!(tree[&0], 'A');
+            }
+
+            #[test]
+            #[should_panic]
+            fn index_missing() {
+                let mut tree = $tree::new();
+                tree.insert(0, 'A');
+                let _ = tree[&1];
+            }
+
+            #[test]
+            fn randomized() {
+                let mut rng = Rng::new(0x7AB1E);
+                let mut tree = $tree::new();
+                let mut reference = BTreeMap::new();
+
+                for _ in 0..5_000 {
+                    let key = rng.below(1_000);
+                    let value = rng.next_u64();
+                    if rng.below(3) == 0 {
+                        assert_eq!(tree.remove(&key), reference.remove(&key));
+                    } else {
+                        assert_eq!(tree.insert(key, value), reference.insert(key, value));
+                    }
+                    assert_eq!(tree.len(), reference.len());
+                    check(&tree);
+
+                    let probe = rng.below(1_000);
+                    assert_eq!(tree.get(&probe), reference.get(&probe));
+                }
+
+                for (key, value) in &reference {
+                    assert_eq!(tree.get(key), Some(value));
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) use map_tests;
+
+/// B-Trees
+pub mod b_tree;
 /// Binary Trees
 pub mod binary_tree;
+/// Ordered multisets that count occurrences
+pub mod counter;
+/// Fenwick Trees, also known as binary indexed trees
+pub mod fenwick_tree;
+/// Interval Trees
+pub mod interval_tree;
+/// k-d Trees for spatial lookup
+pub mod kd_tree;
+/// Pairing heaps, mergeable priority queues
+pub mod pairing_heap;
+/// Radix trees for integer keys
+pub mod radix_tree;
+/// Maps from non-overlapping key ranges to values
+pub mod range_map;
+/// Red-Black Trees
+pub mod red_black_tree;
+/// Segment Trees
+pub mod segment_tree;
+/// Splay Trees
+pub mod splay_tree;
+/// Treaps, randomized binary search trees
+pub mod treap;
+/// Prefix trees for string keys
+pub mod trie;
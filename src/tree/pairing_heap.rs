@@ -0,0 +1,601 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{marker::PhantomData, ptr::NonNull};
+
+/// A min-heap that can be merged with another heap in constant time.
+///
+/// The heap is a tree in which every node is no greater than its children, stored as a
+/// leftmost child with a list of siblings. Pushing and merging hang one tree below the root of
+/// the other, and popping pairs up the children of the root from left to right before merging
+/// the pairs from right to left. Popping takes `O(log(n))` amortized time, and both passes run
+/// iteratively, so even the degenerate trees left by adversarial sequences do not overflow the
+/// stack.
+///
+/// Elements pushed with [`push_handle`](Self::push_handle) can have their value lowered later
+/// through the returned [`Handle`].
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::pairing_heap::PairingHeap;
+///
+/// let mut left: PairingHeap<u32> = [5, 3, 8].into_iter().collect();
+/// let right: PairingHeap<u32> = [4, 1].into_iter().collect();
+/// left = left.merge(right);
+///
+/// assert_eq!(left.peek_min(), Some(&1));
+/// assert_eq!(left.pop_min(), Some(1));
+/// assert_eq!(left.pop_min(), Some(3));
+/// assert_eq!(left.len(), 3);
+/// ```
+pub struct PairingHeap<T> {
+    root: Link<T>,
+    len: usize,
+    /// The heap owns its nodes, as far as drop checking and variance are concerned.
+    marker: PhantomData<Box<PairingHeapNode<T>>>,
+}
+
+type Link<T> = Option<NonNull<PairingHeapNode<T>>>;
+
+struct PairingHeapNode<T> {
+    value: T,
+    child: Link<T>,
+    sibling: Link<T>,
+    /// The parent of a leftmost child, the previous sibling of any other child, and [`None`]
+    /// for a root.
+    prev: Link<T>,
+}
+
+/// A reference to an element of a [`PairingHeap`], for use with
+/// [`PairingHeap::decrease_key`].
+///
+/// A handle is a plain pointer to its node. The heap cannot tell which heap a node belongs to
+/// without giving up constant-time merges, so handles are not checked, and using one is
+/// `unsafe`.
+///
+/// This `struct` is created by [`PairingHeap::push_handle`].
+pub struct Handle<T> {
+    node: NonNull<PairingHeapNode<T>>,
+}
+
+// SAFETY: The heap owns its elements like a `Box` would, and the raw pointers are never shared
+// with anything outside the heap and the borrows handed out from it. Handles are neither `Send`
+// nor `Sync`, and using them requires `unsafe` anyway.
+unsafe impl<T: Send> Send for PairingHeap<T> {}
+
+// SAFETY: As above. A shared heap only ever hands out shared references to its elements.
+unsafe impl<T: Sync> Sync for PairingHeap<T> {}
+
+impl<T> PairingHeap<T> {
+    /// Constructs an empty heap.
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the smallest element, or [`None`] if the heap is empty.
+    pub fn peek_min(&self) -> Option<&T> {
+        // SAFETY: The root is a live node owned by the heap, and the returned reference borrows
+        // the heap.
+        self.root.map(|root| unsafe { &(*root.as_ptr()).value })
+    }
+
+    /// Returns an iterator over the elements of the heap, in no particular order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: self.root.into_iter().collect(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Removes every element from the heap.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<T: Ord> PairingHeap<T> {
+    /// Adds `value` to the heap.
+    pub fn push(&mut self, value: T) {
+        self.push_handle(value);
+    }
+
+    /// Adds `value` to the heap and returns a handle to it, which can be passed to
+    /// [`decrease_key`](Self::decrease_key) for as long as the element stays in the heap.
+    pub fn push_handle(&mut self, value: T) -> Handle<T> {
+        let node = NonNull::from(Box::leak(Box::new(PairingHeapNode {
+            value,
+            child: None,
+            sibling: None,
+            prev: None,
+        })));
+        self.len += 1;
+        // SAFETY: The node was just allocated as a root of its own, and the current root is a
+        // root of this heap.
+        self.root = Some(match self.root {
+            Some(root) => unsafe { PairingHeapNode::meld(root, node) },
+            None => node,
+        });
+        Handle { node }
+    }
+
+    /// Removes the smallest element and returns it, or [`None`] if the heap is empty.
+    pub fn pop_min(&mut self) -> Option<T> {
+        let root = self.root.take()?;
+        self.len -= 1;
+        // SAFETY: The root was allocated by `push_handle` and is no longer reachable from the
+        // heap. Its children become the roots to be paired up, which is done before anything
+        // else touches the heap.
+        let root = unsafe { Box::from_raw(root.as_ptr()) };
+        // SAFETY: See above.
+        self.root = unsafe { PairingHeapNode::pair_up(root.child) };
+        Some(root.value)
+    }
+
+    /// Moves every element of `other` into the heap and returns it, in constant time.
+    pub fn merge(mut self, mut other: Self) -> Self {
+        self.root = match (self.root, other.root.take()) {
+            // SAFETY: Both nodes are roots of their heap, and `other` has given up its nodes.
+            (Some(left), Some(right)) => Some(unsafe { PairingHeapNode::meld(left, right) }),
+            (left, right) => left.or(right),
+        };
+        self.len += core::mem::take(&mut other.len);
+        self
+    }
+
+    /// Replaces the element of `handle` with `value`, which must be no greater than the element,
+    /// and moves it up the heap accordingly. This takes constant time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is greater than the current element.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must have been returned by [`push_handle`](Self::push_handle) of this heap, or
+    /// of a heap that has since been merged into it, and its element must not have been popped
+    /// or cleared since. Otherwise the handle may point at freed memory or at a node of another
+    /// heap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::pairing_heap::PairingHeap;
+    ///
+    /// let mut distances = PairingHeap::new();
+    /// distances.push((4, "a"));
+    /// let b = distances.push_handle((9, "b"));
+    ///
+    /// // SAFETY: `b` was pushed onto `distances` and has not been popped.
+    /// unsafe { distances.decrease_key(b, (2, "b")) };
+    /// assert_eq!(distances.pop_min(), Some((2, "b")));
+    /// ```
+    pub unsafe fn decrease_key(&mut self, handle: Handle<T>, value: T) {
+        let node = handle.node;
+        // SAFETY: The caller guarantees that `node` is a live node of this heap, so all of its
+        // links point at live nodes of this heap too.
+        unsafe {
+            assert!(
+                value <= (*node.as_ptr()).value,
+                "decrease_key must not increase the value in PairingHeap"
+            );
+            (*node.as_ptr()).value = value;
+
+            let prev = match (*node.as_ptr()).prev {
+                Some(prev) => prev,
+                // Only the root has no predecessor, and it stays the minimum.
+                None => return,
+            };
+            // Cut the subtree of `node` out of the heap and merge it with the root again.
+            let sibling = (*node.as_ptr()).sibling.take();
+            if (*prev.as_ptr()).child == Some(node) {
+                (*prev.as_ptr()).child = sibling;
+            } else {
+                (*prev.as_ptr()).sibling = sibling;
+            }
+            if let Some(sibling) = sibling {
+                (*sibling.as_ptr()).prev = Some(prev);
+            }
+            (*node.as_ptr()).prev = None;
+
+            let root = self.root.expect("the heap holds the node");
+            self.root = Some(PairingHeapNode::meld(root, node));
+        }
+    }
+}
+
+impl<T: Ord> PairingHeapNode<T> {
+    /// Makes the greater of two roots the leftmost child of the other and returns the new root.
+    ///
+    /// # Safety
+    ///
+    /// Both nodes must be live roots of distinct trees, without siblings or predecessors.
+    unsafe fn meld(left: NonNull<Self>, right: NonNull<Self>) -> NonNull<Self> {
+        // SAFETY: The caller guarantees that both are live roots, and a root is linked to
+        // nothing but its own children.
+        unsafe {
+            let (parent, child) = if (*left.as_ptr()).value <= (*right.as_ptr()).value {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            let first = (*parent.as_ptr()).child.replace(child);
+            if let Some(first) = first {
+                (*first.as_ptr()).prev = Some(child);
+            }
+            (*child.as_ptr()).sibling = first;
+            (*child.as_ptr()).prev = Some(parent);
+            parent
+        }
+    }
+
+    /// Merges the list of siblings starting at `first` into a single tree and returns its root.
+    ///
+    /// # Safety
+    ///
+    /// `first` must start a list of live siblings that nothing else links to.
+    unsafe fn pair_up(first: Link<T>) -> Link<T> {
+        // SAFETY: The caller hands over the whole list. Every node is detached before it is
+        // melded, and the pairs are kept on a stack threaded through their sibling links.
+        unsafe {
+            let mut pairs: Link<T> = None;
+            let mut current = first;
+            while let Some(left) = current {
+                let right = (*left.as_ptr()).sibling.take();
+                (*left.as_ptr()).prev = None;
+                let pair = match right {
+                    Some(right) => {
+                        current = (*right.as_ptr()).sibling.take();
+                        (*right.as_ptr()).prev = None;
+                        Self::meld(left, right)
+                    }
+                    None => {
+                        current = None;
+                        left
+                    }
+                };
+                (*pair.as_ptr()).sibling = pairs;
+                pairs = Some(pair);
+            }
+
+            // The stack holds the pairs from right to left, which is the order to merge them in.
+            let mut root = None;
+            while let Some(pair) = pairs {
+                pairs = (*pair.as_ptr()).sibling.take();
+                root = Some(match root {
+                    Some(root) => Self::meld(pair, root),
+                    None => pair,
+                });
+            }
+            root
+        }
+    }
+}
+
+impl<T> Drop for PairingHeap<T> {
+    fn drop(&mut self) {
+        // Free the nodes through a stack threaded through their sibling links, so that deep
+        // trees do not overflow the call stack.
+        let mut stack = self.root.take();
+        while let Some(node) = stack {
+            // SAFETY: Every node on the stack is owned by the heap and is freed exactly once,
+            // after its children have been moved onto the stack.
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            stack = node.sibling;
+            if let Some(first) = node.child {
+                let mut last = first;
+                // SAFETY: The children of `node` are live and only reachable through it.
+                unsafe {
+                    while let Some(next) = (*last.as_ptr()).sibling {
+                        last = next;
+                    }
+                    (*last.as_ptr()).sibling = stack;
+                }
+                stack = Some(first);
+            }
+        }
+    }
+}
+
+impl<T> Default for PairingHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> Clone for PairingHeap<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for PairingHeap<T> {
+    /// Formats the elements of the heap as a list, in no particular order.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: Ord> Extend<T> for PairingHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for PairingHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PairingHeap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> core::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Handle").field(&self.node).finish()
+    }
+}
+
+/// An iterator over the elements of a [`PairingHeap`], in no particular order.
+///
+/// This `struct` is created by [`PairingHeap::iter`].
+pub struct Iter<'a, T> {
+    stack: Vec<NonNull<PairingHeapNode<T>>>,
+    len: usize,
+    marker: PhantomData<&'a PairingHeapNode<T>>,
+}
+
+// SAFETY: The iterator only hands out shared references, like `&PairingHeap<T>` would.
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+
+// SAFETY: As above.
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // SAFETY: The iterator borrows the heap, so its nodes stay alive and unchanged for `'a`.
+        let node = unsafe { &*node.as_ptr() };
+        self.stack.extend(node.sibling);
+        self.stack.extend(node.child);
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeSet, rc::Rc};
+
+    /// Pops every element of `heap`, checking them against the sorted `reference`.
+    fn drain_against(mut heap: PairingHeap<u64>, mut reference: Vec<u64>) {
+        reference.sort_unstable();
+        for expected in reference {
+            assert_eq!(heap.pop_min(), Some(expected));
+        }
+        assert_eq!(heap.pop_min(), None);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn randomized_against_sorted() {
+        let mut rng = Rng::new(449);
+        let mut heaps: Vec<PairingHeap<u64>> = (0..4).map(|_| PairingHeap::new()).collect();
+        let mut references: Vec<Vec<u64>> = (0..4).map(|_| Vec::new()).collect();
+
+        for _ in 0..5_000 {
+            let i = rng.below(4) as usize;
+            match rng.below(10) {
+                0..=4 => {
+                    let value = rng.below(1_000);
+                    heaps[i].push(value);
+                    references[i].push(value);
+                }
+                5..=8 => {
+                    let reference = &mut references[i];
+                    let min = (0..reference.len()).min_by_key(|&index| reference[index]);
+                    let expected = min.map(|index| reference.swap_remove(index));
+                    assert_eq!(heaps[i].pop_min(), expected);
+                }
+                _ => {
+                    let j = (i + 1 + rng.below(3) as usize) % 4;
+                    let other = core::mem::take(&mut heaps[j]);
+                    heaps[i] = core::mem::take(&mut heaps[i]).merge(other);
+                    let other = core::mem::take(&mut references[j]);
+                    references[i].extend(other);
+                }
+            }
+
+            assert_eq!(heaps[i].len(), references[i].len());
+            assert_eq!(heaps[i].peek_min(), references[i].iter().min());
+            assert_eq!(heaps[i].iter().len(), references[i].len());
+        }
+
+        for (heap, reference) in heaps.into_iter().zip(references) {
+            let mut elements: Vec<u64> = heap.iter().copied().collect();
+            let mut sorted = reference.clone();
+            elements.sort_unstable();
+            sorted.sort_unstable();
+            assert_eq!(elements, sorted);
+            drain_against(heap, reference);
+        }
+    }
+
+    #[test]
+    fn decrease_key_against_sorted() {
+        let mut rng = Rng::new(4490);
+        let mut heap = PairingHeap::new();
+        let mut reference = BTreeSet::new();
+        let mut handles: Vec<Option<Handle<(u64, usize)>>> = Vec::new();
+
+        for _ in 0..5_000 {
+            match rng.below(4) {
+                0 | 1 => {
+                    let entry = (rng.below(10_000), handles.len());
+                    handles.push(Some(heap.push_handle(entry)));
+                    reference.insert(entry);
+                }
+                2 => {
+                    let popped = heap.pop_min();
+                    let expected = reference.iter().next().copied();
+                    assert_eq!(popped, expected);
+                    if let Some((value, id)) = expected {
+                        reference.remove(&(value, id));
+                        handles[id] = None;
+                    }
+                }
+                _ => {
+                    let live: Vec<_> = reference.iter().copied().collect();
+                    if !live.is_empty() {
+                        let (value, id) = live[rng.below(live.len() as u64) as usize];
+                        let lowered = value - rng.below(value + 1);
+                        let handle = handles[id].expect("the element is in the heap");
+                        // SAFETY: The handle was pushed onto `heap`, and its element has not
+                        // been popped since, as it is still in the reference.
+                        unsafe { heap.decrease_key(handle, (lowered, id)) };
+                        reference.remove(&(value, id));
+                        reference.insert((lowered, id));
+                    }
+                }
+            }
+            assert_eq!(heap.len(), reference.len());
+            assert_eq!(heap.peek_min(), reference.iter().next());
+        }
+        while let Some(expected) = reference.iter().next().copied() {
+            reference.remove(&expected);
+            assert_eq!(heap.pop_min(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn decrease_key_across_merges() {
+        let mut left = PairingHeap::new();
+        let mut right = PairingHeap::new();
+        left.extend([10, 20, 30]);
+        let handle = right.push_handle(40);
+        right.extend([50, 60]);
+        let mut heap = left.merge(right);
+
+        // SAFETY: `handle` was pushed onto `right`, which has been merged into `heap`.
+        unsafe { heap.decrease_key(handle, 5) };
+        assert_eq!(heap.pop_min(), Some(5));
+        drain_against(heap, alloc::vec![10, 20, 30, 50, 60]);
+    }
+
+    #[test]
+    #[should_panic(expected = "decrease_key must not increase the value in PairingHeap")]
+    fn decrease_key_panics_on_increase() {
+        let mut heap = PairingHeap::new();
+        let handle = heap.push_handle(3);
+        // SAFETY: `handle` was pushed onto `heap` and has not been popped.
+        unsafe { heap.decrease_key(handle, 4) };
+    }
+
+    #[test]
+    fn degenerate_trees() {
+        // Pushing in descending order builds a path, and ascending order a root with a long list
+        // of children. Neither may overflow the stack when popping or dropping.
+        let mut descending: PairingHeap<u32> = (0..100_000).rev().collect();
+        let mut ascending: PairingHeap<u32> = (0..100_000).collect();
+        for expected in 0..10 {
+            assert_eq!(descending.pop_min(), Some(expected));
+            assert_eq!(ascending.pop_min(), Some(expected));
+        }
+        drop(descending);
+
+        let mut handles = Vec::new();
+        let mut path = PairingHeap::new();
+        for value in (0..100_000u32).rev() {
+            handles.push(path.push_handle(value + 100_000));
+        }
+        for (offset, handle) in handles.into_iter().enumerate() {
+            // SAFETY: Every handle was pushed onto `path`, and nothing has been popped.
+            unsafe { path.decrease_key(handle, offset as u32) };
+        }
+        assert_eq!(path.pop_min(), Some(0));
+        drop(path);
+        assert_eq!(ascending.len(), 99_990);
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let counter = Rc::new(());
+        {
+            let mut heap: PairingHeap<(u32, Rc<()>)> =
+                (0..10).map(|key| (key % 4, Rc::clone(&counter))).collect();
+            drop(heap.pop_min());
+            let clone = heap.clone();
+            assert_eq!(Rc::strong_count(&counter), 19);
+            heap = heap.merge(clone);
+            drop(heap.pop_min());
+            assert_eq!(Rc::strong_count(&counter), 18);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
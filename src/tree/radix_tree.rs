@@ -0,0 +1,717 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+/// A key that can be stored in a [`RadixTree`] by its bits.
+///
+/// [`to_bits`](Self::to_bits) must be injective and agree with [`Ord`]: comparing the bits of
+/// two keys as unsigned integers must give the same result as comparing the keys. It is
+/// implemented for every primitive integer type, with the sign bit of signed integers flipped so
+/// that negative numbers come first.
+pub trait RadixKey: Copy + Ord {
+    /// Returns the bits of the key, aligned to the most significant end of a `u128`.
+    fn to_bits(self) -> u128;
+}
+
+macro_rules! unsigned_radix_keys {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                fn to_bits(self) -> u128 {
+                    (self as u128) << (128 - <$t>::BITS)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! signed_radix_keys {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                fn to_bits(self) -> u128 {
+                    ((self as $u) ^ (1 << (<$u>::BITS - 1))).to_bits()
+                }
+            }
+        )*
+    };
+}
+
+unsigned_radix_keys!(u8, u16, u32, u64, u128, usize);
+signed_radix_keys!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+/// A map from integer keys to values, organised by the bits of the keys rather than by
+/// comparing them.
+///
+/// The tree branches on single bits, and paths through which only one key passes are compressed
+/// away, so every branch has two children and the tree holds `2 * n - 1` nodes for `n` keys no
+/// matter how sparse they are. Lookups and insertions visit at most one node per key bit and
+/// never rebalance. Since a `0` bit sorts before a `1` bit, iterating the tree yields the keys in
+/// ascending order.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::radix_tree::RadixTree;
+///
+/// let mut ports = RadixTree::new();
+/// ports.insert(443u16, "https");
+/// ports.insert(22, "ssh");
+/// ports.insert(80, "http");
+///
+/// assert_eq!(ports.get(80), Some(&"http"));
+/// assert_eq!(ports.remove(22), Some("ssh"));
+/// assert!(ports.range(..=100).eq([(80, &"http")]));
+/// assert!(ports.keys().eq([80, 443]));
+/// ```
+#[derive(Clone)]
+pub struct RadixTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+type Link<K, V> = Option<Box<RadixTreeNode<K, V>>>;
+
+#[derive(Clone)]
+enum RadixTreeNode<K, V> {
+    Leaf {
+        key: K,
+        value: V,
+    },
+    /// Every key below the branch starts with `prefix` and differs at bit `bit`, counting from
+    /// the most significant. The keys with a `0` there are in the first child. Both children are
+    /// always present; they are only optional so that a child can be moved out while splitting
+    /// or collapsing the branch.
+    Branch {
+        bit: u32,
+        prefix: u128,
+        children: [Link<K, V>; 2],
+    },
+}
+
+impl<K, V> RadixTreeNode<K, V> {
+    fn child(children: &[Link<K, V>; 2], side: usize) -> &Self {
+        children[side]
+            .as_deref()
+            .expect("Every branch of a RadixTree has two children")
+    }
+
+    /// Returns the link to the child that `bits` lead to from the branch at `link`.
+    fn descend(link: &mut Link<K, V>, bits: u128) -> &mut Link<K, V> {
+        match link.as_deref_mut() {
+            Some(Self::Branch { bit, children, .. }) => &mut children[direction(bits, *bit)],
+            _ => panic!("Only the branches of a RadixTree have children"),
+        }
+    }
+}
+
+/// Returns whether bit `bit` of `bits`, counting from the most significant, is set.
+fn direction(bits: u128, bit: u32) -> usize {
+    ((bits << bit) >> 127) as usize
+}
+
+/// Returns the bits of `bits` above bit `bit`, with everything else cleared.
+fn prefix(bits: u128, bit: u32) -> u128 {
+    bits.checked_shr(128 - bit)
+        .map_or(0, |high| high << (128 - bit))
+}
+
+impl<K, V> RadixTree<K, V> {
+    /// Constructs an empty tree.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of entries in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every entry from the tree.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over the entries of the tree in ascending order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: self.root.as_deref().into_iter().collect(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys of the tree in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_
+    where
+        K: Copy,
+    {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values of the tree in ascending order of their keys.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_
+    where
+        K: Copy,
+    {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<K: RadixKey, V> RadixTree<K, V> {
+    /// Inserts a key-value pair into the tree. If the key was already present, its value is
+    /// replaced and the old value returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let bits = key.to_bits();
+
+        // Find the first bit at which the key differs from the keys already in the tree. Every
+        // key in a subtree shares its prefix, so following the bits of the key down to any leaf
+        // finds one that agrees with it for as long as any key does.
+        let mut node = match self.root.as_deref() {
+            Some(root) => root,
+            None => {
+                self.root = Some(Box::new(RadixTreeNode::Leaf { key, value }));
+                self.len = 1;
+                return None;
+            }
+        };
+        let critical = loop {
+            match node {
+                RadixTreeNode::Leaf { key: existing, .. } => {
+                    break (existing.to_bits() ^ bits).leading_zeros();
+                }
+                RadixTreeNode::Branch {
+                    bit,
+                    prefix: common,
+                    children,
+                } => {
+                    let differs = (prefix(bits, *bit) ^ common).leading_zeros();
+                    if differs < *bit {
+                        break differs;
+                    }
+                    node = RadixTreeNode::child(children, direction(bits, *bit));
+                }
+            }
+        };
+
+        // Descend to the node the key belongs at or above: the leaf holding it if it is present,
+        // and otherwise the first node that does not branch above the critical bit.
+        let mut link = &mut self.root;
+        while matches!(
+            link.as_deref(),
+            Some(RadixTreeNode::Branch { bit, .. }) if *bit < critical
+        ) {
+            link = RadixTreeNode::descend(link, bits);
+        }
+
+        if critical == 128 {
+            if let Some(RadixTreeNode::Leaf { value: old, .. }) = link.as_deref_mut() {
+                return Some(core::mem::replace(old, value));
+            }
+        }
+
+        let leaf = Some(Box::new(RadixTreeNode::Leaf { key, value }));
+        let existing = link.take();
+        let children = match direction(bits, critical) {
+            0 => [leaf, existing],
+            _ => [existing, leaf],
+        };
+        *link = Some(Box::new(RadixTreeNode::Branch {
+            bit: critical,
+            prefix: prefix(bits, critical),
+            children,
+        }));
+        self.len += 1;
+        None
+    }
+
+    fn leaf(&self, key: K) -> Option<&RadixTreeNode<K, V>> {
+        let bits = key.to_bits();
+        let mut node = self.root.as_deref()?;
+        while let RadixTreeNode::Branch { bit, children, .. } = node {
+            node = RadixTreeNode::child(children, direction(bits, *bit));
+        }
+        match node {
+            RadixTreeNode::Leaf { key: existing, .. } if *existing == key => Some(node),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get(&self, key: K) -> Option<&V> {
+        match self.leaf(key)? {
+            RadixTreeNode::Leaf { value, .. } => Some(value),
+            RadixTreeNode::Branch { .. } => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let bits = key.to_bits();
+        let mut node = self.root.as_deref_mut()?;
+        loop {
+            match node {
+                RadixTreeNode::Branch { bit, children, .. } => {
+                    node = children[direction(bits, *bit)]
+                        .as_deref_mut()
+                        .expect("Every branch of a RadixTree has two children");
+                }
+                RadixTreeNode::Leaf {
+                    key: existing,
+                    value,
+                } => return (*existing == key).then_some(value),
+            }
+        }
+    }
+
+    /// Returns `true` if the tree contains the key.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.leaf(key).is_some()
+    }
+
+    /// Removes a key from the tree, returning its value if it was present.
+    ///
+    /// The branch above the removed leaf is replaced by its other child, so the tree stays
+    /// compressed.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let bits = key.to_bits();
+        let is_leaf =
+            |link: &Link<K, V>| matches!(link.as_deref(), Some(RadixTreeNode::Leaf { .. }));
+
+        // Stop at the root if it is a leaf, and otherwise at the branch right above the leaf
+        // that the key would be in.
+        let mut link = &mut self.root;
+        while matches!(
+            link.as_deref(),
+            Some(RadixTreeNode::Branch { bit, children, .. })
+                if !is_leaf(&children[direction(bits, *bit)])
+        ) {
+            link = RadixTreeNode::descend(link, bits);
+        }
+
+        let removed = match link.as_deref_mut()? {
+            RadixTreeNode::Leaf { key: existing, .. } => {
+                if *existing != key {
+                    return None;
+                }
+                link.take()
+            }
+            RadixTreeNode::Branch { bit, children, .. } => {
+                let side = direction(bits, *bit);
+                match children[side].as_deref() {
+                    Some(RadixTreeNode::Leaf { key: existing, .. }) if *existing == key => {}
+                    _ => return None,
+                }
+                let removed = children[side].take();
+                *link = children[1 - side].take();
+                removed
+            }
+        };
+        self.len -= 1;
+        match removed.map(|node| *node) {
+            Some(RadixTreeNode::Leaf { value, .. }) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the entries whose keys lie in `range`, in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if they are equal and both
+    /// excluded.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let bounds = (range.start_bound(), range.end_bound());
+        match bounds {
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+                panic!("range start and end are equal and excluded in RadixTree")
+            }
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if s > e =>
+            {
+                panic!("range start is greater than range end in RadixTree")
+            }
+            _ => {}
+        }
+
+        let mut stack = Vec::new();
+        let (start, inclusive) = match range.start_bound() {
+            Bound::Included(start) => (start.to_bits(), true),
+            Bound::Excluded(start) => (start.to_bits(), false),
+            Bound::Unbounded => (0, true),
+        };
+
+        // Walk towards the start of the range, leaving behind the subtrees that lie after it.
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = None;
+            match current {
+                RadixTreeNode::Leaf { key, .. } => {
+                    let bits = key.to_bits();
+                    if bits > start || (inclusive && bits == start) {
+                        stack.push(current);
+                    }
+                }
+                RadixTreeNode::Branch {
+                    bit,
+                    prefix: common,
+                    children,
+                } => match prefix(start, *bit).cmp(common) {
+                    Ordering::Less => stack.push(current),
+                    Ordering::Greater => {}
+                    Ordering::Equal => {
+                        if direction(start, *bit) == 0 {
+                            stack.push(RadixTreeNode::child(children, 1));
+                            node = Some(RadixTreeNode::child(children, 0));
+                        } else {
+                            node = Some(RadixTreeNode::child(children, 1));
+                        }
+                    }
+                },
+            }
+        }
+
+        Range {
+            stack,
+            end: match range.end_bound() {
+                Bound::Included(end) => Bound::Included(*end),
+                Bound::Excluded(end) => Bound::Excluded(*end),
+                Bound::Unbounded => Bound::Unbounded,
+            },
+        }
+    }
+}
+
+/// Pops the stack until it yields the next leaf, pushing the children of every branch on the way.
+fn next_leaf<'a, K: Copy, V>(stack: &mut Vec<&'a RadixTreeNode<K, V>>) -> Option<(K, &'a V)> {
+    loop {
+        match stack.pop()? {
+            RadixTreeNode::Leaf { key, value } => return Some((*key, value)),
+            RadixTreeNode::Branch { children, .. } => {
+                stack.push(RadixTreeNode::child(children, 1));
+                stack.push(RadixTreeNode::child(children, 0));
+            }
+        }
+    }
+}
+
+impl<K, V> Default for RadixTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Copy + core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for RadixTree<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Copy + PartialEq, V: PartialEq> PartialEq for RadixTree<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Copy + Eq, V: Eq> Eq for RadixTree<K, V> {}
+
+impl<K: RadixKey, V> Extend<(K, V)> for RadixTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: RadixKey, V> FromIterator<(K, V)> for RadixTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: RadixKey, V> core::ops::Index<K> for RadixTree<K, V> {
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the tree.
+    fn index(&self, key: K) -> &Self::Output {
+        self.get(key).expect("Key is not present in the RadixTree")
+    }
+}
+
+impl<'a, K: Copy, V> IntoIterator for &'a RadixTree<K, V> {
+    type Item = (K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`RadixTree`] in ascending order of their keys.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a RadixTreeNode<K, V>>,
+    len: usize,
+}
+
+impl<'a, K: Copy, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = next_leaf(&mut self.stack)?;
+        self.len -= 1;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K: Copy, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Copy + core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`RadixTree`] whose keys lie in a range, created by
+/// [`RadixTree::range`].
+pub struct Range<'a, K, V> {
+    stack: Vec<&'a RadixTreeNode<K, V>>,
+    end: Bound<K>,
+}
+
+impl<'a, K: Ord + Copy, V> Iterator for Range<'a, K, V> {
+    type Item = (K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = next_leaf(&mut self.stack)?;
+        let within = match self.end {
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+            Bound::Unbounded => true,
+        };
+        if within {
+            Some((key, value))
+        } else {
+            self.stack.clear();
+            None
+        }
+    }
+}
+
+impl<K: Clone, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            end: self.end.clone(),
+        }
+    }
+}
+
+impl<K: Ord + Copy + core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Range<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeMap, format};
+
+    /// Checks that the branches are compressed and ordered, and returns the number of leaves.
+    fn check<K: RadixKey, V>(node: &RadixTreeNode<K, V>, above: Option<(u32, u128)>) -> usize {
+        match node {
+            RadixTreeNode::Leaf { key, .. } => {
+                if let Some((bit, common)) = above {
+                    assert_eq!(prefix(key.to_bits(), bit), common);
+                }
+                1
+            }
+            RadixTreeNode::Branch {
+                bit,
+                prefix: common,
+                children,
+            } => {
+                if let Some((above, outer)) = above {
+                    assert!(*bit > above);
+                    assert_eq!(prefix(*common, above), outer);
+                }
+                assert_eq!(prefix(*common, *bit), *common);
+                let zero = check(RadixTreeNode::child(children, 0), Some((*bit, *common)));
+                let one = check(RadixTreeNode::child(children, 1), Some((*bit, *common)));
+                zero + one
+            }
+        }
+    }
+
+    fn check_tree<K: RadixKey, V>(tree: &RadixTree<K, V>) {
+        let leaves = tree.root.as_deref().map_or(0, |root| check(root, None));
+        assert_eq!(leaves, tree.len());
+        assert!(tree.keys().zip(tree.keys().skip(1)).all(|(a, b)| a < b));
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut rng = Rng::new(452);
+        for sparse in [false, true] {
+            let mut tree = RadixTree::new();
+            let mut model = BTreeMap::new();
+            let key = |rng: &mut Rng| {
+                if sparse {
+                    rng.next_u64()
+                } else {
+                    rng.below(512) << 20
+                }
+            };
+            for step in 0..4000u64 {
+                match rng.below(4) {
+                    0 | 1 => {
+                        let key = key(&mut rng);
+                        assert_eq!(tree.insert(key, step), model.insert(key, step));
+                    }
+                    2 => {
+                        let key = key(&mut rng);
+                        assert_eq!(tree.remove(key), model.remove(&key));
+                    }
+                    _ => {
+                        let (a, b) = (key(&mut rng), key(&mut rng));
+                        let (start, end) = (a.min(b), a.max(b));
+                        assert!(tree
+                            .range(start..end)
+                            .eq(model.range(start..end).map(|(k, v)| (*k, v))));
+                        assert!(tree
+                            .range(start..=end)
+                            .eq(model.range(start..=end).map(|(k, v)| (*k, v))));
+                        assert!(tree
+                            .range((Bound::Excluded(start), Bound::Unbounded))
+                            .eq(model
+                                .range((Bound::Excluded(start), Bound::Unbounded))
+                                .map(|(k, v)| (*k, v))));
+                        assert_eq!(tree.get(start), model.get(&start));
+                    }
+                }
+                assert_eq!(tree.len(), model.len());
+            }
+            check_tree(&tree);
+            assert!(tree.iter().eq(model.iter().map(|(k, v)| (*k, v))));
+        }
+    }
+
+    #[test]
+    fn adjacent_keys() {
+        let mut tree: RadixTree<u64, u64> = (0..256).map(|key| (key, key * 2)).collect();
+        check_tree(&tree);
+        assert!(tree.keys().eq(0..256));
+        assert_eq!(tree.get(255), Some(&510));
+        assert_eq!(tree.get(256), None);
+        for key in (0..256).step_by(2) {
+            assert_eq!(tree.remove(key), Some(key * 2));
+        }
+        check_tree(&tree);
+        assert!(tree.keys().eq((1..256).step_by(2)));
+        assert!(tree
+            .range(10..=20)
+            .map(|(key, _)| key)
+            .eq([11, 13, 15, 17, 19]));
+    }
+
+    #[test]
+    fn keys_differing_in_the_top_bit() {
+        let mut tree = RadixTree::new();
+        tree.insert(1u64 << 63, 'b');
+        tree.insert(0, 'a');
+        tree.insert(u64::MAX, 'c');
+        check_tree(&tree);
+        assert!(tree
+            .iter()
+            .eq([(0, &'a'), (1 << 63, &'b'), (u64::MAX, &'c')]));
+        assert!(tree.range(1..).map(|(key, _)| key).eq([1 << 63, u64::MAX]));
+        assert!(tree.range(..1 << 63).map(|(key, _)| key).eq([0]));
+        assert_eq!(tree.remove(0), Some('a'));
+        assert_eq!(tree.remove(u64::MAX), Some('c'));
+        assert_eq!(tree.remove(1), None);
+        assert_eq!(tree[1 << 63], 'b');
+        assert_eq!(tree.remove(1 << 63), Some('b'));
+        assert!(tree.is_empty());
+        assert!(tree.root.is_none());
+    }
+
+    #[test]
+    fn other_key_types() {
+        let signed: RadixTree<i64, ()> = [-3, i64::MIN, 7, 0, i64::MAX, -1]
+            .iter()
+            .map(|&key| (key, ()))
+            .collect();
+        assert!(signed.keys().eq([i64::MIN, -3, -1, 0, 7, i64::MAX]));
+        assert!(signed.range(-2..=0).map(|(key, _)| key).eq([-1, 0]));
+
+        let mut small: RadixTree<u8, u8> = RadixTree::new();
+        for key in (0..=255).rev() {
+            small.insert(key, key);
+        }
+        check_tree(&small);
+        assert!(small.keys().eq(0..=255));
+
+        let mut wide = RadixTree::new();
+        wide.insert(u128::MAX, 1);
+        wide.insert(u128::MAX - 1, 2);
+        wide.insert(1u128 << 100, 3);
+        assert_eq!(wide.insert(u128::MAX - 1, 4), Some(2));
+        check_tree(&wide);
+        assert!(wide.values().eq([&3, &4, &1]));
+        if let Some(value) = wide.get_mut(1 << 100) {
+            *value = 5;
+        }
+        assert_eq!(wide.get(1 << 100), Some(&5));
+        assert!(!wide.contains_key(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Key is not present in the RadixTree")]
+    fn index_missing_key() {
+        let tree: RadixTree<u32, u32> = RadixTree::new();
+        let _ = tree[1];
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end in RadixTree")]
+    fn inverted_range() {
+        let tree: RadixTree<u32, u32> = RadixTree::new();
+        #[allow(clippy::reversed_empty_ranges)]
+        let _ = tree.range(5..1);
+    }
+
+    #[test]
+    fn debug_iterators() {
+        let tree: RadixTree<u32, char> = [(3, 'c'), (1, 'a'), (2, 'b')].into_iter().collect();
+        let mut iter = tree.iter();
+        iter.next();
+        assert_eq!(format!("{:?}", iter), "[(2, 'b'), (3, 'c')]");
+        assert_eq!(format!("{:?}", tree.range(..3)), "[(1, 'a'), (2, 'b')]");
+    }
+}
@@ -0,0 +1,494 @@
+use crate::tree::binary_tree::{self, BinaryTree};
+use alloc::vec::Vec;
+use core::ops::{Bound, Range};
+
+/// A map from non-overlapping half-open ranges of keys to values.
+///
+/// The ranges are kept in a [`BinaryTree`] keyed by their starts, so finding the range that
+/// covers a key takes `O(log(n))` time. A range covers its start but not its end, so `0..10` and
+/// `10..20` are adjacent rather than overlapping.
+///
+/// Inserting a range overwrites whatever it overlaps: ranges that stick out on either side are
+/// truncated to the part outside the new one, a range that contains the new one is split in two
+/// around it, and ranges entirely inside it are removed. Values are cloned when a range is
+/// split and keys are cloned whenever a range boundary is, which is why both need to be
+/// [`Clone`]. Empty ranges, such as `3..3` or the inverted `5..2`, cover no keys and are
+/// ignored.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::range_map::RangeMap;
+///
+/// let mut tenants = RangeMap::new();
+/// tenants.insert(0..256, "acme");
+/// tenants.insert(64..128, "globex");
+///
+/// assert_eq!(tenants.get(&63), Some(&"acme"));
+/// assert_eq!(tenants.get(&64), Some(&"globex"));
+/// assert_eq!(tenants.get(&128), Some(&"acme"));
+/// assert!(tenants.iter().eq([(0..64, &"acme"), (64..128, &"globex"), (128..256, &"acme")]));
+///
+/// tenants.remove_range(100..200);
+/// assert!(tenants.gaps(&(0..300)).eq([100..200, 256..300]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeMap<K, V> {
+    segments: BinaryTree<K, Segment<K, V>>,
+}
+
+/// The part of a range other than its start, which is the key it is stored under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment<K, V> {
+    end: K,
+    value: V,
+}
+
+impl<K, V> RangeMap<K, V> {
+    /// Constructs an empty map.
+    pub fn new() -> Self {
+        Self {
+            segments: BinaryTree::new(),
+        }
+    }
+
+    /// Returns the number of ranges in the map.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` if the map holds no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Removes every range from the map.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<K: Ord + Clone, V> RangeMap<K, V> {
+    /// Returns an iterator over the ranges and their values in ascending order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            segments: self.segments.iter(),
+        }
+    }
+
+    /// Returns the start and the rest of the range that covers `key`.
+    fn covering(&self, key: &K) -> Option<(&K, &Segment<K, V>)> {
+        self.segments
+            .floor_key_value(key)
+            .filter(|(_, segment)| *key < segment.end)
+    }
+
+    /// Returns a reference to the value of the range that covers `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.covering(key).map(|(_, segment)| &segment.value)
+    }
+
+    /// Returns the range that covers `key`, together with its value.
+    pub fn get_key_value(&self, key: &K) -> Option<(Range<K>, &V)> {
+        self.covering(key)
+            .map(|(start, segment)| (start.clone()..segment.end.clone(), &segment.value))
+    }
+
+    /// Returns `true` if some range covers `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.covering(key).is_some()
+    }
+
+    /// Returns an iterator over the parts of `range` that no range in the map covers, in
+    /// ascending order.
+    pub fn gaps(&self, range: &Range<K>) -> Gaps<'_, K, V> {
+        let end = core::cmp::max(&range.start, &range.end);
+        let cursor = match self.covering(&range.start) {
+            Some((_, segment)) => segment.end.clone(),
+            None => range.start.clone(),
+        };
+        Gaps {
+            segments: self
+                .segments
+                .range((Bound::Included(&range.start), Bound::Excluded(end))),
+            cursor,
+            end: range.end.clone(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> RangeMap<K, V> {
+    /// Maps every key in `range` to `value`, overwriting the parts of any ranges it overlaps.
+    /// Does nothing if `range` is empty.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        self.remove_range(range.clone());
+        let Range { start, end } = range;
+        self.segments.insert(start, Segment { end, value });
+    }
+
+    /// Unmaps every key in `range`, truncating or splitting the ranges it overlaps.
+    pub fn remove_range(&mut self, range: Range<K>) {
+        if range.start >= range.end {
+            return;
+        }
+
+        // A range that starts before `range` may reach into it, and even past its end.
+        let before = self
+            .segments
+            .range((Bound::Unbounded, Bound::Excluded(&range.start)))
+            .next_back()
+            .filter(|(_, segment)| segment.end > range.start)
+            .map(|(start, _)| start.clone());
+        if let Some(before) = before {
+            let segment = self
+                .segments
+                .get_mut(&before)
+                .expect("The range should still be in the RangeMap");
+            let after = (segment.end > range.end).then(|| Segment {
+                end: segment.end.clone(),
+                value: segment.value.clone(),
+            });
+            segment.end = range.start.clone();
+            if let Some(after) = after {
+                // The split range covered all of `range`, so it cannot overlap anything else.
+                self.segments.insert(range.end, after);
+                return;
+            }
+        }
+
+        // Every range that starts inside `range` goes, except for the part of the last one that
+        // reaches past its end.
+        let inside: Vec<K> = self
+            .segments
+            .range((Bound::Included(&range.start), Bound::Excluded(&range.end)))
+            .map(|(start, _)| start.clone())
+            .collect();
+        for start in inside {
+            let segment = self
+                .segments
+                .remove(&start)
+                .expect("The range should still be in the RangeMap");
+            if segment.end > range.end {
+                self.segments.insert(range.end.clone(), segment);
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> RangeMap<K, V> {
+    /// Like [`insert`](Self::insert), but afterwards merges `range` with the ranges immediately
+    /// before and after it if they have the same value, so that runs of equal values inserted
+    /// this way are stored as single ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::range_map::RangeMap;
+    ///
+    /// let mut map = RangeMap::new();
+    /// map.insert_coalescing(0..5, 'a');
+    /// map.insert_coalescing(10..15, 'a');
+    /// map.insert_coalescing(5..10, 'a');
+    /// assert!(map.iter().eq([(0..15, &'a')]));
+    /// ```
+    pub fn insert_coalescing(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        self.remove_range(range.clone());
+        let Range { mut start, mut end } = range;
+
+        let before = self
+            .segments
+            .range((Bound::Unbounded, Bound::Excluded(&start)))
+            .next_back()
+            .filter(|(_, segment)| segment.end == start && segment.value == value)
+            .map(|(start, _)| start.clone());
+        if let Some(before) = before {
+            self.segments.remove(&before);
+            start = before;
+        }
+        if matches!(self.segments.get(&end), Some(segment) if segment.value == value) {
+            if let Some(after) = self.segments.remove(&end) {
+                end = after.end;
+            }
+        }
+        self.segments.insert(start, Segment { end, value });
+    }
+}
+
+impl<K, V> Default for RangeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Extend<(Range<K>, V)> for RangeMap<K, V> {
+    /// Inserts the ranges in order, so later ranges overwrite earlier ones where they overlap.
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> FromIterator<(Range<K>, V)> for RangeMap<K, V> {
+    /// Builds a map by inserting the ranges in order, so later ranges overwrite earlier ones
+    /// where they overlap.
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K: Ord + Clone, V> IntoIterator for &'a RangeMap<K, V> {
+    type Item = (Range<K>, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the ranges of a [`RangeMap`] and their values in ascending order.
+///
+/// This `struct` is created by [`RangeMap::iter`].
+pub struct Iter<'a, K, V> {
+    segments: binary_tree::Iter<'a, K, Segment<K, V>>,
+}
+
+impl<'a, K: Clone, V> Iterator for Iter<'a, K, V> {
+    type Item = (Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, segment) = self.segments.next()?;
+        Some((start.clone()..segment.end.clone(), &segment.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.segments.size_hint()
+    }
+}
+
+impl<K: Clone, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (start, segment) = self.segments.next_back()?;
+        Some((start.clone()..segment.end.clone(), &segment.value))
+    }
+}
+
+impl<K: Clone, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            segments: self.segments.clone(),
+        }
+    }
+}
+
+impl<K, V> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Iter")
+            .field("len", &self.segments.len())
+            .finish()
+    }
+}
+
+/// An iterator over the parts of a range that no range of a [`RangeMap`] covers.
+///
+/// This `struct` is created by [`RangeMap::gaps`].
+pub struct Gaps<'a, K, V> {
+    /// The ranges that start inside the queried range and have not been passed yet.
+    segments: binary_tree::Range<'a, K, Segment<K, V>>,
+    /// The first key that has not been reported or found to be covered yet.
+    cursor: K,
+    end: K,
+}
+
+impl<K: Ord + Clone, V> Iterator for Gaps<'_, K, V> {
+    type Item = Range<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cursor < self.end {
+            match self.segments.next() {
+                Some((start, segment)) if *start > self.cursor => {
+                    let gap_start = core::mem::replace(&mut self.cursor, segment.end.clone());
+                    return Some(gap_start..start.clone());
+                }
+                Some((_, segment)) => {
+                    if segment.end > self.cursor {
+                        self.cursor = segment.end.clone();
+                    }
+                }
+                None => {
+                    let gap_start = core::mem::replace(&mut self.cursor, self.end.clone());
+                    return Some(gap_start..self.end.clone());
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<K: Clone, V> Clone for Gaps<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            segments: self.segments.clone(),
+            cursor: self.cursor.clone(),
+            end: self.end.clone(),
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V> core::fmt::Debug for Gaps<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Gaps")
+            .field("cursor", &self.cursor)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use core::iter::once;
+
+    fn entries<K: Ord + Clone, V: Clone>(map: &RangeMap<K, V>) -> Vec<(Range<K>, V)> {
+        map.iter()
+            .map(|(range, value)| (range, value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_inserts() {
+        let mut map = RangeMap::new();
+        map.insert(0..10, 'a');
+        map.insert(3..5, 'b');
+        assert_eq!(entries(&map), [(0..3, 'a'), (3..5, 'b'), (5..10, 'a')]);
+
+        map.insert(4..12, 'c');
+        assert_eq!(entries(&map), [(0..3, 'a'), (3..4, 'b'), (4..12, 'c')]);
+
+        map.insert(2..3, 'd');
+        assert_eq!(
+            entries(&map),
+            [(0..2, 'a'), (2..3, 'd'), (3..4, 'b'), (4..12, 'c')]
+        );
+
+        map.insert(1..20, 'e');
+        assert_eq!(entries(&map), [(0..1, 'a'), (1..20, 'e')]);
+
+        map.insert(5..5, 'f');
+        #[allow(clippy::reversed_empty_ranges)]
+        map.insert(9..6, 'f');
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn boundaries() {
+        let map: RangeMap<i32, &str> = [(10..20, "x"), (20..30, "y")].into_iter().collect();
+        assert_eq!(map.get(&9), None);
+        assert_eq!(map.get(&10), Some(&"x"));
+        assert_eq!(map.get(&19), Some(&"x"));
+        assert_eq!(map.get(&20), Some(&"y"));
+        assert_eq!(map.get(&29), Some(&"y"));
+        assert_eq!(map.get(&30), None);
+        assert_eq!(map.get_key_value(&25), Some((20..30, &"y")));
+        assert!(!map.contains_key(&i32::MIN));
+    }
+
+    #[test]
+    fn removing_ranges() {
+        let mut map: RangeMap<u32, u32> =
+            [(0..10, 1), (10..20, 2), (30..40, 3)].into_iter().collect();
+        map.remove_range(5..35);
+        assert_eq!(entries(&map), [(0..5, 1), (35..40, 3)]);
+        map.remove_range(1..2);
+        assert_eq!(entries(&map), [(0..1, 1), (2..5, 1), (35..40, 3)]);
+        map.remove_range(0..100);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn gap_enumeration() {
+        let map: RangeMap<u32, ()> = [(10..20, ()), (20..25, ()), (30..40, ())]
+            .into_iter()
+            .collect();
+        assert!(map.gaps(&(0..50)).eq([0..10, 25..30, 40..50]));
+        assert!(map.gaps(&(15..35)).eq(once(25..30)));
+        assert!(map.gaps(&(12..18)).eq([]));
+        assert!(map.gaps(&(25..30)).eq(once(25..30)));
+        assert!(map.gaps(&(20..30)).eq(once(25..30)));
+        assert!(map.gaps(&(5..5)).eq([]));
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 8..3;
+        assert!(map.gaps(&inverted).eq([]));
+        assert!(RangeMap::<u32, ()>::new().gaps(&(1..4)).eq(once(1..4)));
+    }
+
+    #[test]
+    fn coalescing() {
+        let mut map = RangeMap::new();
+        map.insert_coalescing(0..5, 'a');
+        map.insert_coalescing(5..10, 'b');
+        map.insert_coalescing(10..15, 'a');
+        assert_eq!(map.len(), 3);
+        map.insert_coalescing(3..12, 'a');
+        assert_eq!(entries(&map), [(0..15, 'a')]);
+
+        // Only touching ranges are merged, not ones with a gap between them.
+        map.insert_coalescing(16..20, 'a');
+        assert_eq!(entries(&map), [(0..15, 'a'), (16..20, 'a')]);
+
+        // Splitting a range and filling the hole with the same value restores it.
+        map.insert(5..7, 'c');
+        map.insert_coalescing(5..7, 'a');
+        assert_eq!(entries(&map), [(0..15, 'a'), (16..20, 'a')]);
+    }
+
+    #[test]
+    fn matches_model() {
+        const UNIVERSE: u64 = 64;
+        let mut rng = Rng::new(458);
+        let mut map = RangeMap::new();
+        let mut model = [None; UNIVERSE as usize];
+        for step in 0..3_000 {
+            let start = rng.below(UNIVERSE);
+            let end = start + rng.below(UNIVERSE - start + 1);
+            let value = rng.below(3);
+            match rng.below(4) {
+                0 => {
+                    map.remove_range(start..end);
+                    model[start as usize..end as usize].fill(None);
+                }
+                1 => {
+                    map.insert_coalescing(start..end, value);
+                    model[start as usize..end as usize].fill(Some(value));
+                }
+                _ => {
+                    map.insert(start..end, value);
+                    model[start as usize..end as usize].fill(Some(value));
+                }
+            }
+
+            for key in 0..UNIVERSE {
+                assert_eq!(map.get(&key).copied(), model[key as usize], "step {step}");
+            }
+            let gaps: Vec<u64> = map.gaps(&(start..UNIVERSE)).flatten().collect();
+            let expected: Vec<u64> = (start..UNIVERSE)
+                .filter(|&key| model[key as usize].is_none())
+                .collect();
+            assert_eq!(gaps, expected);
+            let ranges: Vec<_> = map.iter().map(|(range, _)| range).collect();
+            assert!(ranges.iter().all(|range| range.start < range.end));
+            assert!(ranges.windows(2).all(|pair| pair[0].end <= pair[1].start));
+        }
+    }
+}
@@ -0,0 +1,510 @@
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+
+/// A red-black tree containing key-value pairs where the keys can be ordered.
+///
+/// This offers the same interface as [`BinaryTree`](crate::tree::binary_tree::BinaryTree), but
+/// keeps itself balanced by colouring every node red or black instead of tracking subtree
+/// heights. The balance it maintains is looser (the longest path may be up to twice as long as
+/// the shortest), which lets a removal get away with at most three rotations, where an AVL tree
+/// may need one at every level. This makes it a better fit for workloads with many removals, at
+/// the price of slightly deeper lookups.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::red_black_tree::RedBlackTree;
+///
+/// let mut tree = RedBlackTree::new();
+/// tree.insert(0, "John");
+/// tree.insert(42, "Neo");
+/// tree.insert(2, "Alice");
+///
+/// assert_eq!(tree.get(&0), Some(&"John"));
+/// assert_eq!(tree.get(&42), Some(&"Neo"));
+/// assert_eq!(tree.get(&2), Some(&"Alice"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RedBlackTree<K, V> {
+    root: NodeChild<K, V>,
+    len: usize,
+}
+
+type NodeChild<K, V> = Option<Box<RedBlackTreeNode<K, V>>>;
+
+#[derive(Debug, Clone, Default)]
+struct RedBlackTreeNode<K, V> {
+    key: K,
+    value: V,
+    red: bool,
+    children: (NodeChild<K, V>, NodeChild<K, V>),
+}
+
+impl<K, V> RedBlackTree<K, V> {
+    /// Constructs an empty tree
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the tree with constant-time complexity.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every element from the tree.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+}
+
+impl<K, V> RedBlackTree<K, V>
+where
+    K: Ord,
+{
+    /// Inserts a key-value pair into the [`RedBlackTree`].
+    ///
+    /// Returns [`None`] if the key did not exist, otherwise updates
+    /// the value and returns [`Some`] with the old value.
+    ///
+    /// # Note
+    ///
+    /// Like with `HashMap` the key does not get updated.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let result = RedBlackTreeNode::insert(&mut self.root, key, value);
+        if let Some(root) = self.root.as_mut() {
+            root.red = false;
+        }
+
+        if result.is_none() {
+            self.len += 1;
+        }
+
+        result
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return Some(&current.value),
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => current.children.1.as_deref(),
+            };
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref_mut();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Equal => return Some(&mut current.value),
+                Ordering::Less => current.children.0.as_deref_mut(),
+                Ordering::Greater => current.children.1.as_deref_mut(),
+            };
+        }
+        None
+    }
+
+    /// Removes a key from the tree, returning its value if the key was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::red_black_tree::RedBlackTree;
+    ///
+    /// let mut tree = RedBlackTree::new();
+    /// tree.insert(String::from("a"), 1);
+    /// assert_eq!(tree.remove("a"), Some(1));
+    /// assert_eq!(tree.remove("a"), None);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let ((_, value), _) = RedBlackTreeNode::remove(&mut self.root, key)?;
+        RedBlackTreeNode::set_red(&mut self.root, false);
+        self.len -= 1;
+        Some(value)
+    }
+}
+
+impl<K, V> RedBlackTreeNode<K, V> {
+    fn is_red(link: &NodeChild<K, V>) -> bool {
+        matches!(link, Some(node) if node.red)
+    }
+
+    /// Returns `true` if the node held by `link` is red and has a red child.
+    fn has_red_violation(link: &NodeChild<K, V>) -> bool {
+        match link {
+            Some(node) => {
+                node.red && (Self::is_red(&node.children.0) || Self::is_red(&node.children.1))
+            }
+            None => false,
+        }
+    }
+
+    fn set_red(link: &mut NodeChild<K, V>, red: bool) {
+        if let Some(node) = link {
+            node.red = red;
+        }
+    }
+
+    /// Rotates the subtree rooted at `node` to the left, making its right child the new root.
+    fn rotate_left(node: &mut Box<Self>) {
+        if let Some(mut right) = node.children.1.take() {
+            node.children.1 = right.children.0.take();
+            core::mem::swap(node, &mut right);
+            node.children.0 = Some(right);
+        }
+    }
+
+    /// Rotates the subtree rooted at `node` to the right, making its left child the new root.
+    fn rotate_right(node: &mut Box<Self>) {
+        if let Some(mut left) = node.children.0.take() {
+            node.children.0 = left.children.1.take();
+            core::mem::swap(node, &mut left);
+            node.children.1 = Some(left);
+        }
+    }
+
+    /// Resolves a red node with a red child directly below `node`, which may leave `node` itself
+    /// red and in violation with its parent.
+    fn fix_up(node: &mut Box<Self>) {
+        let left_violation = Self::has_red_violation(&node.children.0);
+        let right_violation = Self::has_red_violation(&node.children.1);
+        if !left_violation && !right_violation {
+            return;
+        }
+
+        if Self::is_red(&node.children.0) && Self::is_red(&node.children.1) {
+            // Both children are red: push the red up a level instead of rotating.
+            node.red = true;
+            Self::set_red(&mut node.children.0, false);
+            Self::set_red(&mut node.children.1, false);
+            return;
+        }
+
+        if left_violation {
+            if let Some(left) = node.children.0.as_mut() {
+                if Self::is_red(&left.children.1) {
+                    Self::rotate_left(left);
+                }
+            }
+            Self::rotate_right(node);
+            Self::set_red(&mut node.children.1, true);
+        } else {
+            if let Some(right) = node.children.1.as_mut() {
+                if Self::is_red(&right.children.0) {
+                    Self::rotate_right(right);
+                }
+            }
+            Self::rotate_left(node);
+            Self::set_red(&mut node.children.0, true);
+        }
+        node.red = false;
+    }
+
+    /// Restores the black heights below `node` after its left subtree lost one black node.
+    /// Returns `true` if the whole subtree rooted at `node` is now one black node short.
+    fn fix_left_short(node: &mut Box<Self>) -> bool {
+        if Self::is_red(&node.children.1) {
+            // A red sibling means a black parent: rotate it up so that the short side gets a
+            // black sibling, then fix that side, which absorbs the deficit.
+            Self::rotate_left(node);
+            node.red = false;
+            let left = node
+                .children
+                .0
+                .as_mut()
+                .expect("The old root is the left child after a rotation");
+            left.red = true;
+            Self::fix_left_short(left);
+            return false;
+        }
+
+        let sibling = node
+            .children
+            .1
+            .as_mut()
+            .expect("The sibling of a short subtree has a black node to spare");
+        if !Self::is_red(&sibling.children.0) && !Self::is_red(&sibling.children.1) {
+            // Shorten the sibling to match, and make up for both with the parent if it is red.
+            sibling.red = true;
+            let short = !node.red;
+            node.red = false;
+            return short;
+        }
+
+        if !Self::is_red(&sibling.children.1) {
+            Self::rotate_right(sibling);
+            sibling.red = false;
+            Self::set_red(&mut sibling.children.1, true);
+        }
+        let red = node.red;
+        Self::rotate_left(node);
+        node.red = red;
+        Self::set_red(&mut node.children.0, false);
+        Self::set_red(&mut node.children.1, false);
+        false
+    }
+
+    /// Restores the black heights below `node` after its right subtree lost one black node.
+    /// Returns `true` if the whole subtree rooted at `node` is now one black node short.
+    fn fix_right_short(node: &mut Box<Self>) -> bool {
+        if Self::is_red(&node.children.0) {
+            Self::rotate_right(node);
+            node.red = false;
+            let right = node
+                .children
+                .1
+                .as_mut()
+                .expect("The old root is the right child after a rotation");
+            right.red = true;
+            Self::fix_right_short(right);
+            return false;
+        }
+
+        let sibling = node
+            .children
+            .0
+            .as_mut()
+            .expect("The sibling of a short subtree has a black node to spare");
+        if !Self::is_red(&sibling.children.0) && !Self::is_red(&sibling.children.1) {
+            sibling.red = true;
+            let short = !node.red;
+            node.red = false;
+            return short;
+        }
+
+        if !Self::is_red(&sibling.children.0) {
+            Self::rotate_left(sibling);
+            sibling.red = false;
+            Self::set_red(&mut sibling.children.0, true);
+        }
+        let red = node.red;
+        Self::rotate_right(node);
+        node.red = red;
+        Self::set_red(&mut node.children.0, false);
+        Self::set_red(&mut node.children.1, false);
+        false
+    }
+
+    /// Replaces the node held by `link`, which has at most one child, with that child. Returns
+    /// the entry of the node and whether the subtree lost a black node.
+    fn unlink(link: &mut NodeChild<K, V>) -> ((K, V), bool) {
+        let mut node = link.take().expect("Only occupied links are unlinked");
+        *link = node.children.0.take().or_else(|| node.children.1.take());
+        // The only child of a node is a red leaf, which can take over the colour of a black node.
+        let short = !node.red && !Self::is_red(link);
+        Self::set_red(link, false);
+        ((node.key, node.value), short)
+    }
+
+    /// Removes the entry with the smallest key from the non-empty subtree held by `link`.
+    fn remove_min(link: &mut NodeChild<K, V>) -> ((K, V), bool) {
+        let node = link
+            .as_mut()
+            .expect("Only non-empty subtrees have a minimum");
+        if node.children.0.is_none() {
+            return Self::unlink(link);
+        }
+        let (entry, short) = Self::remove_min(&mut node.children.0);
+        (entry, short && Self::fix_left_short(node))
+    }
+
+    /// Removes `key` from the subtree held by `link`, returning its entry and whether the
+    /// subtree lost a black node.
+    fn remove<Q>(link: &mut NodeChild<K, V>, key: &Q) -> Option<((K, V), bool)>
+    where
+        K: core::borrow::Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = link.as_mut()?;
+        match key.cmp(node.key.borrow()) {
+            Ordering::Less => {
+                let (entry, short) = Self::remove(&mut node.children.0, key)?;
+                Some((entry, short && Self::fix_left_short(node)))
+            }
+            Ordering::Greater => {
+                let (entry, short) = Self::remove(&mut node.children.1, key)?;
+                Some((entry, short && Self::fix_right_short(node)))
+            }
+            Ordering::Equal if node.children.0.is_some() && node.children.1.is_some() => {
+                // Take the place of the successor, and remove that instead.
+                let ((key, value), short) = Self::remove_min(&mut node.children.1);
+                let key = core::mem::replace(&mut node.key, key);
+                let value = core::mem::replace(&mut node.value, value);
+                Some(((key, value), short && Self::fix_right_short(node)))
+            }
+            Ordering::Equal => Some(Self::unlink(link)),
+        }
+    }
+}
+
+impl<K, V> RedBlackTreeNode<K, V>
+where
+    K: Ord,
+{
+    fn insert(link: &mut NodeChild<K, V>, key: K, value: V) -> Option<V> {
+        let node = match link {
+            Some(node) => node,
+            None => {
+                *link = Some(Box::new(RedBlackTreeNode {
+                    key,
+                    value,
+                    red: true,
+                    children: (None, None),
+                }));
+                return None;
+            }
+        };
+
+        let child = match key.cmp(&node.key) {
+            Ordering::Less => &mut node.children.0,
+            Ordering::Greater => &mut node.children.1,
+            Ordering::Equal => return Some(core::mem::replace(&mut node.value, value)),
+        };
+
+        let result = Self::insert(child, key, value);
+        if result.is_none() {
+            Self::fix_up(node);
+        }
+        result
+    }
+}
+
+impl<K, Q, V> core::ops::Index<&Q> for RedBlackTree<K, V>
+where
+    K: Ord + core::borrow::Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the red-black tree.
+    fn index(&self, index: &Q) -> &Self::Output {
+        self.get(index)
+            .expect("Key is not present in the red-black tree")
+    }
+}
+
+impl<K, Q, V> core::ops::IndexMut<&Q> for RedBlackTree<K, V>
+where
+    K: Ord + core::borrow::Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    /// Returns a mutable reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the red-black tree.
+    fn index_mut(&mut self, index: &Q) -> &mut Self::Output {
+        self.get_mut(index)
+            .expect("Key is not present in the red-black tree")
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for RedBlackTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for RedBlackTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the red-black invariants, returning the black height of the subtree.
+    fn assert_red_black<K: Ord, V>(link: &NodeChild<K, V>) -> usize {
+        let node = match link {
+            Some(node) => node,
+            None => return 1,
+        };
+        if let Some(left) = &node.children.0 {
+            assert!(left.key < node.key);
+        }
+        if let Some(right) = &node.children.1 {
+            assert!(right.key > node.key);
+        }
+        assert!(
+            !RedBlackTreeNode::has_red_violation(link),
+            "red node has a red child"
+        );
+
+        let left = assert_red_black(&node.children.0);
+        let right = assert_red_black(&node.children.1);
+        assert_eq!(left, right, "black heights differ");
+        left + usize::from(!node.red)
+    }
+
+    fn assert_invariants<K: Ord, V>(tree: &RedBlackTree<K, V>) {
+        assert!(!RedBlackTreeNode::is_red(&tree.root), "root is red");
+        assert_red_black(&tree.root);
+    }
+
+    crate::tree::map_tests!(RedBlackTree, assert_invariants);
+
+    #[test]
+    fn sequential_inserts() {
+        let mut tree = RedBlackTree::new();
+        for key in 0..1_024 {
+            tree.insert(key, key);
+            assert_invariants(&tree);
+        }
+
+        for key in 0..1_024 {
+            assert_eq!(tree[&key], key);
+        }
+    }
+
+    #[test]
+    fn remove_everything() {
+        let mut tree = RedBlackTree::new();
+        for key in 0..1_024 {
+            tree.insert(key, key);
+        }
+
+        // Stepping by a number coprime to the length visits every key in a scattered order.
+        for step in 0..1_024 {
+            let key = step * 389 % 1_024;
+            tree[&key] += 1;
+            assert_eq!(tree.remove(&key), Some(key + 1));
+            assert_eq!(tree.remove(&key), None);
+            assert_eq!(tree.len(), 1_023 - step);
+            assert_invariants(&tree);
+        }
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+use crate::monoid::Monoid;
+use alloc::vec::Vec;
+use core::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+/// A fixed-length sequence of values that answers range aggregate queries and point updates in
+/// `O(log(n))` time.
+///
+/// The values are combined with the operation of the [`Monoid`] `M`, which only needs to be
+/// associative, so order-sensitive operations work too. The tree is stored in a single array of
+/// `2 * n` slots: the values themselves occupy the second half, and slot `i` of the first half
+/// holds the combination of slots `2 * i` and `2 * i + 1`. Queries walk up from both ends of the
+/// range without recursing.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::monoid::{Min, Sum};
+/// use collect_me::tree::segment_tree::SegmentTree;
+///
+/// let prices = [7, 3, 9, 4, 8];
+/// let mut cheapest: SegmentTree<u32, Min> = SegmentTree::from_slice(&prices);
+/// let mut total: SegmentTree<u32, Sum> = SegmentTree::from_slice(&prices);
+///
+/// assert_eq!(cheapest.query(2..), 4);
+/// assert_eq!(total.query(..3), 19);
+///
+/// cheapest.update(4, 1);
+/// total.update(4, 1);
+/// assert_eq!(cheapest.query(2..), 1);
+/// assert_eq!(total.query(..), 24);
+/// ```
+pub struct SegmentTree<T, M> {
+    /// Slot zero is unused, and slots `len..2 * len` hold the values.
+    tree: Vec<T>,
+    len: usize,
+    monoid: PhantomData<M>,
+}
+
+impl<T, M> SegmentTree<T, M>
+where
+    M: Monoid<T>,
+{
+    /// Constructs a tree of `len` identity elements.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            tree: (0..2 * len).map(|_| M::identity()).collect(),
+            len,
+            monoid: PhantomData,
+        }
+    }
+
+    /// Builds a tree over `values` in linear time.
+    pub fn from_slice(values: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        let len = values.len();
+        let mut tree = Vec::with_capacity(2 * len);
+        tree.extend((0..len).map(|_| M::identity()));
+        tree.extend_from_slice(values);
+        for i in (1..len).rev() {
+            tree[i] = M::combine(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        Self {
+            tree,
+            len,
+            monoid: PhantomData,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every value, leaving a tree of length zero.
+    pub fn clear(&mut self) {
+        self.tree.clear();
+        self.len = 0;
+    }
+
+    /// Returns the value at `index`, or [`None`] if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            self.tree.get(self.len + index)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn update(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "Index out of bounds in SegmentTree");
+        let mut i = self.len + index;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = M::combine(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    /// Returns the combination of the values in `range`, in order, or the identity if the range
+    /// is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or its start is greater than its end.
+    pub fn query<R>(&self, range: R) -> T
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end,
+            "range start is greater than range end in SegmentTree"
+        );
+        assert!(end <= self.len, "range end out of bounds in SegmentTree");
+
+        // Both ends climb towards the root, collecting the slots that stick out of the part of
+        // the range covered by the level above. Keeping separate results for the left and right
+        // end preserves the order of the values.
+        let (mut left, mut right) = (M::identity(), M::identity());
+        let (mut low, mut high) = (start + self.len, end + self.len);
+        while low < high {
+            if low % 2 == 1 {
+                left = M::combine(&left, &self.tree[low]);
+                low += 1;
+            }
+            if high % 2 == 1 {
+                high -= 1;
+                right = M::combine(&self.tree[high], &right);
+            }
+            low /= 2;
+            high /= 2;
+        }
+        M::combine(&left, &right)
+    }
+}
+
+impl<T, M> core::ops::Index<usize> for SegmentTree<T, M>
+where
+    M: Monoid<T>,
+{
+    type Output = T;
+
+    /// Returns a reference to the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Index out of bounds in SegmentTree")
+    }
+}
+
+impl<T: Clone, M> Clone for SegmentTree<T, M> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            len: self.len,
+            monoid: PhantomData,
+        }
+    }
+}
+
+impl<T: core::fmt::Debug, M> core::fmt::Debug for SegmentTree<T, M> {
+    /// Formats the values of the tree as a list.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(&self.tree[self.len..]).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::{Min, Sum};
+    use crate::test_util::Rng;
+    use alloc::format;
+
+    /// Composition of affine maps `x -> a * x + b` modulo a prime, which is associative but not
+    /// commutative.
+    struct Affine;
+
+    const PRIME: u64 = 1_000_003;
+
+    impl Monoid<(u64, u64)> for Affine {
+        fn identity() -> (u64, u64) {
+            (1, 0)
+        }
+
+        /// Applies `f` first and `g` second.
+        fn combine(f: &(u64, u64), g: &(u64, u64)) -> (u64, u64) {
+            (g.0 * f.0 % PRIME, (g.0 * f.1 + g.1) % PRIME)
+        }
+    }
+
+    fn randomized_against_brute_force<M, F>(seed: u64, mut random_value: F)
+    where
+        M: Monoid<u64>,
+        F: FnMut(&mut Rng) -> u64,
+    {
+        let mut rng = Rng::new(seed);
+        for len in [0, 1, 2, 3, 17, 64, 100] {
+            let mut reference: Vec<u64> = (0..len).map(|_| random_value(&mut rng)).collect();
+            let mut tree: SegmentTree<u64, M> = SegmentTree::from_slice(&reference);
+            let brute =
+                |values: &[u64]| values.iter().fold(M::identity(), |a, b| M::combine(&a, b));
+
+            for _ in 0..500 {
+                if len > 0 {
+                    let index = rng.below(len as u64) as usize;
+                    let value = random_value(&mut rng);
+                    tree.update(index, value);
+                    reference[index] = value;
+                }
+
+                let (a, b) = (rng.below(len as u64 + 1), rng.below(len as u64 + 1));
+                let (start, end) = (a.min(b) as usize, a.max(b) as usize);
+                assert_eq!(tree.query(start..end), brute(&reference[start..end]));
+                assert_eq!(tree.query(start..start), M::identity());
+            }
+            assert_eq!(tree.query(..), brute(&reference));
+            assert!((0..len).all(|index| tree[index] == reference[index]));
+        }
+    }
+
+    #[test]
+    fn sum_against_brute_force() {
+        randomized_against_brute_force::<Sum, _>(442, |rng| rng.below(1_000));
+    }
+
+    #[test]
+    fn min_against_brute_force() {
+        randomized_against_brute_force::<Min, _>(4420, |rng| rng.below(1_000));
+    }
+
+    #[test]
+    fn order_is_preserved() {
+        let mut rng = Rng::new(4421);
+        let maps: Vec<(u64, u64)> = (0..37)
+            .map(|_| (rng.below(PRIME), rng.below(PRIME)))
+            .collect();
+        let tree: SegmentTree<_, Affine> = SegmentTree::from_slice(&maps);
+
+        for start in 0..=maps.len() {
+            for end in start..=maps.len() {
+                let expected = maps[start..end]
+                    .iter()
+                    .fold(Affine::identity(), |f, g| Affine::combine(&f, g));
+                assert_eq!(tree.query(start..end), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let tree: SegmentTree<u32, Sum> = SegmentTree::from_slice(&[1, 2, 3, 4]);
+        assert_eq!(tree.query(1..=2), 5);
+        assert_eq!(tree.query((Bound::Excluded(0), Bound::Unbounded)), 9);
+        assert_eq!(tree.query(4..), 0);
+        assert_eq!(tree.get(4), None);
+        assert_eq!(format!("{:?}", tree), "[1, 2, 3, 4]");
+
+        let empty: SegmentTree<u32, Min> = SegmentTree::with_len(0);
+        assert_eq!(empty.query(..), u32::MAX);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "range end out of bounds in SegmentTree")]
+    fn query_out_of_bounds() {
+        let tree: SegmentTree<u32, Sum> = SegmentTree::with_len(4);
+        let _ = tree.query(2..5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds in SegmentTree")]
+    fn update_out_of_bounds() {
+        let mut tree: SegmentTree<u32, Sum> = SegmentTree::with_len(4);
+        tree.update(4, 1);
+    }
+}
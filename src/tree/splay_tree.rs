@@ -0,0 +1,645 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{borrow::Borrow, cmp::Ordering};
+
+/// A self-adjusting binary search tree containing key-value pairs where the keys can be ordered.
+///
+/// Every access splays the accessed key to the root with a series of rotations, so keys that are
+/// looked up often stay close to the top of the tree. Any sequence of operations takes amortized
+/// `O(log(n))` time per operation, and workloads where a few keys receive most of the lookups run
+/// considerably faster than on a statically balanced tree.
+///
+/// Since lookups restructure the tree, [`SplayTree::get`] takes `&mut self`. Use
+/// [`SplayTree::peek`] to look up a key through a shared reference without splaying.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::splay_tree::SplayTree;
+///
+/// let mut tree = SplayTree::new();
+/// tree.insert(0, "John");
+/// tree.insert(42, "Neo");
+/// tree.insert(2, "Alice");
+///
+/// assert_eq!(tree.get(&0), Some(&"John"));
+/// assert_eq!(tree.peek(&42), Some(&"Neo"));
+/// assert_eq!(tree.get(&2), Some(&"Alice"));
+/// ```
+pub struct SplayTree<K, V> {
+    root: NodeChild<K, V>,
+    len: usize,
+}
+
+type NodeChild<K, V> = Option<Box<SplayTreeNode<K, V>>>;
+
+struct SplayTreeNode<K, V> {
+    key: K,
+    value: V,
+    children: (NodeChild<K, V>, NodeChild<K, V>),
+}
+
+impl<K, V> SplayTree<K, V> {
+    /// Constructs an empty tree.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every element from the tree.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over the entries of the tree, sorted by key. Iterating does not splay.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left_spine(self.root.as_deref());
+        iter
+    }
+}
+
+impl<K, V> SplayTree<K, V>
+where
+    K: Ord,
+{
+    /// Inserts a key-value pair into the [`SplayTree`] and splays it to the root.
+    ///
+    /// Returns [`None`] if the key did not exist, otherwise updates
+    /// the value and returns [`Some`] with the old value.
+    ///
+    /// # Note
+    ///
+    /// Like with `HashMap` the key does not get updated.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut node = Box::new(SplayTreeNode {
+            key,
+            value,
+            children: (None, None),
+        });
+
+        SplayTreeNode::splay(&mut self.root, &node.key);
+        if let Some(root) = self.root.as_mut() {
+            let ordering = node.key.cmp(&root.key);
+            if ordering == Ordering::Equal {
+                return Some(core::mem::replace(&mut root.value, node.value));
+            }
+            let mut root = self.root.take().expect("The root was just matched");
+            if ordering == Ordering::Less {
+                node.children.0 = root.children.0.take();
+                node.children.1 = Some(root);
+            } else {
+                node.children.1 = root.children.1.take();
+                node.children.0 = Some(root);
+            }
+        }
+
+        self.root = Some(node);
+        self.len += 1;
+        None
+    }
+
+    /// Returns a reference to the value corresponding to the key, splaying it to the root.
+    ///
+    /// If the key is not present, the last node visited while searching for it is splayed
+    /// instead.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, splaying it to the
+    /// root.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.splay(key);
+        self.root
+            .as_mut()
+            .filter(|root| root.key.borrow() == key)
+            .map(|root| &mut root.value)
+    }
+
+    /// Returns a reference to the value corresponding to the key without restructuring the tree.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                Ordering::Less => current.children.0.as_deref(),
+                Ordering::Greater => current.children.1.as_deref(),
+                Ordering::Equal => return Some(&current.value),
+            };
+        }
+        None
+    }
+
+    /// Returns `true` if the tree contains a value for the key, splaying it to the root.
+    pub fn contains_key<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes a key from the tree, returning its value if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the tree, returning the stored key and value if it was present.
+    ///
+    /// The key is splayed to the root first. Its left subtree is then splayed around the same
+    /// key, which brings its largest key to the top with no right child, so the right subtree can
+    /// be attached there.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.splay(key);
+        let root = self.root.as_mut()?;
+        if root.key.borrow() != key {
+            return None;
+        }
+        // Splay the left subtree while it is still attached, so that a panicking comparison
+        // leaves the tree whole.
+        SplayTreeNode::splay(&mut root.children.0, key);
+
+        let mut root = self.root.take()?;
+        let (left, right) = (root.children.0.take(), root.children.1.take());
+        self.root = match left {
+            None => right,
+            Some(mut left) => {
+                left.children.1 = right;
+                Some(left)
+            }
+        };
+        self.len -= 1;
+        Some((root.key, root.value))
+    }
+
+    fn splay<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        SplayTreeNode::splay(&mut self.root, key);
+    }
+}
+
+impl<K, V> SplayTreeNode<K, V>
+where
+    K: Ord,
+{
+    /// Splays the node with `key`, or the last node on the search path for it, to the top of the
+    /// subtree held by `link`.
+    ///
+    /// This is the top-down splay by Sleator and Tarjan. Nodes passed on the way down are split
+    /// off into a tree of smaller keys and a tree of larger keys, rotating whenever the search
+    /// goes the same direction twice. Once the target is reached, both trees are hung below it.
+    fn splay<Q>(link: &mut NodeChild<K, V>, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = link.take();
+        let mut splay = Splay {
+            link,
+            node,
+            smaller: Vec::new(),
+            larger: Vec::new(),
+        };
+
+        // Every comparison happens while all the nodes are held by `splay`, so that it can put
+        // the tree back together if one of them panics.
+        while let Some(node) = splay.node.as_mut() {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => break,
+                Ordering::Less => {
+                    let zig_zig = match node.children.0.as_deref() {
+                        Some(child) => key < child.key.borrow(),
+                        None => break,
+                    };
+                    if zig_zig {
+                        let mut child = node.children.0.take().expect("The child was compared");
+                        node.children.0 = child.children.1.take();
+                        core::mem::swap(node, &mut child);
+                        node.children.1 = Some(child);
+                        if node.children.0.is_none() {
+                            break;
+                        }
+                    }
+                    let child = node.children.0.take().expect("The child was checked");
+                    splay.larger.push(core::mem::replace(node, child));
+                }
+                Ordering::Greater => {
+                    let zig_zig = match node.children.1.as_deref() {
+                        Some(child) => key > child.key.borrow(),
+                        None => break,
+                    };
+                    if zig_zig {
+                        let mut child = node.children.1.take().expect("The child was compared");
+                        node.children.1 = child.children.0.take();
+                        core::mem::swap(node, &mut child);
+                        node.children.0 = Some(child);
+                        if node.children.1.is_none() {
+                            break;
+                        }
+                    }
+                    let child = node.children.1.take().expect("The child was checked");
+                    splay.smaller.push(core::mem::replace(node, child));
+                }
+            }
+        }
+    }
+}
+
+/// A splay in progress. Dropping it, whether the splay is done or a comparison panicked, hangs
+/// the nodes split off on the way down back below the current node and stores the result in
+/// `link`.
+struct Splay<'a, K, V> {
+    link: &'a mut NodeChild<K, V>,
+    node: NodeChild<K, V>,
+    /// Each node becomes the right child of the one before it.
+    smaller: Vec<Box<SplayTreeNode<K, V>>>,
+    /// Each node becomes the left child of the one before it.
+    larger: Vec<Box<SplayTreeNode<K, V>>>,
+}
+
+impl<K, V> Drop for Splay<'_, K, V> {
+    fn drop(&mut self) {
+        let mut node = match self.node.take() {
+            Some(node) => node,
+            None => return,
+        };
+        let mut left = node.children.0.take();
+        for mut parent in self.smaller.drain(..).rev() {
+            parent.children.1 = left;
+            left = Some(parent);
+        }
+        let mut right = node.children.1.take();
+        for mut parent in self.larger.drain(..).rev() {
+            parent.children.0 = right;
+            right = Some(parent);
+        }
+        node.children = (left, right);
+        *self.link = Some(node);
+    }
+}
+
+impl<K, V> Drop for SplayTree<K, V> {
+    /// Frees the nodes without recursing, since a splay tree can temporarily degrade into a
+    /// path. Left children are rotated up until there are none, like `BinaryTree` does.
+    fn drop(&mut self) {
+        let mut link = self.root.take();
+        while let Some(mut node) = link {
+            link = if let Some(mut left) = node.children.0.take() {
+                node.children.0 = left.children.1.take();
+                left.children.1 = Some(node);
+                Some(left)
+            } else {
+                node.children.1.take()
+            };
+        }
+    }
+}
+
+impl<K, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for SplayTree<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SplayTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SplayTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K, V, Q> core::ops::Index<&Q> for SplayTree<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key without splaying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the splay tree.
+    fn index(&self, index: &Q) -> &Self::Output {
+        self.peek(index)
+            .expect("Key is not present in the splay tree")
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SplayTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`SplayTree`], sorted by key.
+///
+/// This `struct` is created by [`SplayTree::iter`].
+pub struct Iter<'a, K, V> {
+    /// The nodes whose entry and right subtree are still to be visited, the next one on top.
+    stack: Vec<&'a SplayTreeNode<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut node: Option<&'a SplayTreeNode<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.children.0.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.children.1.as_deref());
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::collections::BTreeMap;
+
+    fn root_key<K: Copy, V>(tree: &SplayTree<K, V>) -> Option<K> {
+        tree.root.as_ref().map(|root| root.key)
+    }
+
+    /// Checks that the keys are in search tree order and that the length adds up, without
+    /// recursing since the tree may be a long path.
+    fn assert_splay_tree<K: Ord, V>(tree: &SplayTree<K, V>) {
+        let mut count = 0;
+        let mut pending = Vec::new();
+        pending.extend(tree.root.as_deref().map(|root| (root, None, None)));
+        while let Some((node, low, high)) = pending.pop() {
+            count += 1;
+            if let Some(low) = low {
+                assert!(low < &node.key);
+            }
+            if let Some(high) = high {
+                assert!(&node.key < high);
+            }
+            if let Some(left) = node.children.0.as_deref() {
+                pending.push((left, low, Some(&node.key)));
+            }
+            if let Some(right) = node.children.1.as_deref() {
+                pending.push((right, Some(&node.key), high));
+            }
+        }
+        assert_eq!(count, tree.len());
+    }
+
+    #[test]
+    fn empty() {
+        let mut tree: SplayTree<i32, i32> = SplayTree::new();
+        assert_eq!(tree.get(&0), None);
+        assert_eq!(tree.remove(&0), None);
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter().count(), 0);
+    }
+
+    #[test]
+    fn replace() {
+        let mut tree = SplayTree::new();
+        assert_eq!(tree.insert(3, "Hello"), None);
+        assert_eq!(tree.insert(3, "World"), Some("Hello"));
+        assert_eq!(tree.get(&3), Some(&"World"));
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn accessed_key_becomes_root() {
+        let mut rng = Rng::new(439);
+        let mut tree: SplayTree<u64, u64> = (0..1_000).map(|key| (key * 3, key)).collect();
+        for _ in 0..1_000 {
+            let key = rng.below(1_000) * 3;
+            assert_eq!(tree.get(&key), Some(&(key / 3)));
+            assert_eq!(root_key(&tree), Some(key));
+
+            let missing = key + 1;
+            assert_eq!(tree.get(&missing), None);
+            // The search path of a missing key ends at one of its neighbours.
+            let root = root_key(&tree).expect("Tree should not be empty");
+            assert!(root == key || root == key + 3);
+        }
+        assert_splay_tree(&tree);
+
+        tree.insert(1, 1);
+        assert_eq!(root_key(&tree), Some(1));
+        *tree.get_mut(&600).expect("Key should be present") += 1;
+        assert_eq!(root_key(&tree), Some(600));
+        assert_eq!(tree[&600], 201);
+    }
+
+    #[test]
+    fn peek_does_not_splay() {
+        let mut tree: SplayTree<u32, u32> = (0..100).map(|key| (key, key)).collect();
+        assert_eq!(root_key(&tree), Some(99));
+        assert_eq!(tree.peek(&0), Some(&0));
+        assert_eq!(tree.peek(&100), None);
+        assert_eq!(tree[&50], 50);
+        assert_eq!(root_key(&tree), Some(99));
+        assert!(tree.contains_key(&50));
+        assert_eq!(root_key(&tree), Some(50));
+    }
+
+    #[test]
+    fn randomized_against_btree_map() {
+        let mut rng = Rng::new(4390);
+        let mut tree = SplayTree::new();
+        let mut reference = BTreeMap::new();
+
+        for step in 0..20_000 {
+            let key = rng.below(1_000);
+            match rng.below(4) {
+                0 => assert_eq!(tree.remove(&key), reference.remove(&key)),
+                1 => assert_eq!(tree.get(&key), reference.get(&key)),
+                2 => assert_eq!(tree.peek(&key), reference.get(&key)),
+                _ => {
+                    let value = rng.next_u64();
+                    assert_eq!(tree.insert(key, value), reference.insert(key, value));
+                }
+            }
+            assert_eq!(tree.len(), reference.len());
+            if step % 1_000 == 0 {
+                assert_splay_tree(&tree);
+            }
+        }
+
+        assert_splay_tree(&tree);
+        assert!(tree.iter().eq(reference.iter()));
+    }
+
+    #[test]
+    fn panicking_comparison_keeps_entries() {
+        extern crate std;
+        use core::cell::Cell;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        /// A key that panics once it has been compared `fuse` times, if it has a fuse.
+        #[derive(Debug)]
+        struct Fused<'a> {
+            key: u32,
+            fuse: Option<&'a Cell<usize>>,
+        }
+
+        impl PartialEq for Fused<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for Fused<'_> {}
+
+        impl PartialOrd for Fused<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Fused<'_> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                if let Some(fuse) = self.fuse.or(other.fuse) {
+                    assert!(fuse.get() > 0, "Fuse blew");
+                    fuse.set(fuse.get() - 1);
+                }
+                self.key.cmp(&other.key)
+            }
+        }
+
+        for after in 0..16 {
+            for operation in 0..3 {
+                let fuse = Cell::new(after);
+                let mut tree = SplayTree::new();
+                for key in (0..64).map(|key| key * 37 % 64 * 2) {
+                    tree.insert(Fused { key, fuse: None }, key);
+                }
+
+                let key = 2 * after as u32 + (operation == 0) as u32;
+                let probe = Fused {
+                    key,
+                    fuse: Some(&fuse),
+                };
+                let outcome = catch_unwind(AssertUnwindSafe(|| match operation {
+                    0 => {
+                        tree.insert(probe, key);
+                    }
+                    1 => {
+                        tree.get(&probe);
+                    }
+                    _ => {
+                        tree.remove(&probe);
+                    }
+                }));
+                fuse.set(usize::MAX);
+
+                let mut expected: Vec<u32> = (0..64).map(|key| key * 2).collect();
+                if outcome.is_ok() {
+                    match operation {
+                        0 => expected.insert(after + 1, key),
+                        2 => {
+                            expected.remove(after);
+                        }
+                        _ => {}
+                    }
+                }
+                assert_splay_tree(&tree);
+                assert!(tree
+                    .iter()
+                    .map(|(key, value)| (key.key, *value))
+                    .eq(expected.iter().map(|key| (*key, *key))));
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_paths() {
+        // Inserting in order leaves the tree as a single path, which every operation must cope
+        // with without recursing.
+        let mut tree: SplayTree<u32, ()> = (0..100_000).map(|key| (key, ())).collect();
+        assert_eq!(tree.iter().len(), 100_000);
+        assert!(tree.iter().map(|(key, _)| *key).eq(0..100_000));
+        assert!(tree.contains_key(&0));
+        assert_eq!(tree.remove(&50_000), Some(()));
+        assert_splay_tree(&tree);
+    }
+}
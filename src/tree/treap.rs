@@ -0,0 +1,649 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::borrow::Borrow;
+
+/// A randomized binary search tree containing key-value pairs where the keys can be ordered.
+///
+/// Every node draws a random priority when it is inserted. Nodes are ordered by key from left to
+/// right like in any binary search tree, and by priority from top to bottom like in a heap. The
+/// shape of the tree therefore only depends on the priorities, which makes its expected depth
+/// logarithmic no matter in which order the keys arrive.
+///
+/// Because a treap can be cut in two and glued back together cheaply, [`Treap::split`] and
+/// [`Treap::merge`] run in expected `O(log(n))` time too.
+///
+/// The priorities come from a small pseudo-random number generator stored in the tree.
+/// [`Treap::new`] seeds it with a fixed value, so the shape of a tree is reproducible; use
+/// [`Treap::with_seed`] to pick another seed.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::treap::Treap;
+///
+/// let mut tree = Treap::new();
+/// tree.insert(0, "John");
+/// tree.insert(42, "Neo");
+/// tree.insert(2, "Alice");
+///
+/// assert_eq!(tree.get(&0), Some(&"John"));
+/// assert_eq!(tree.get(&42), Some(&"Neo"));
+/// assert_eq!(tree.get(&2), Some(&"Alice"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Treap<K, V> {
+    root: NodeChild<K, V>,
+    len: usize,
+    /// The state of the generator that hands out priorities.
+    seed: u64,
+}
+
+type NodeChild<K, V> = Option<Box<TreapNode<K, V>>>;
+
+/// The seed used by [`Treap::new`].
+const DEFAULT_SEED: u64 = 0x5eed_7ea9_5eed_7ea9;
+
+#[derive(Debug, Clone)]
+struct TreapNode<K, V> {
+    key: K,
+    value: V,
+    /// No node has a higher priority than its parent.
+    priority: u64,
+    /// The number of nodes in the subtree rooted at this node, including itself.
+    size: usize,
+    children: (NodeChild<K, V>, NodeChild<K, V>),
+}
+
+impl<K, V> Treap<K, V> {
+    /// Constructs an empty tree with the default seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Constructs an empty tree whose priorities are generated from `seed`.
+    ///
+    /// Trees with the same seed that see the same sequence of insertions have the same shape.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            seed,
+        }
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every element from the tree. The priorities of later insertions carry on from the
+    /// current state of the generator.
+    pub fn clear(&mut self) {
+        *self = Self::with_seed(self.seed);
+    }
+
+    /// Returns the number of nodes on the longest path from the root down to a leaf.
+    ///
+    /// The height is not stored, so this visits every node.
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut stack: Vec<(&TreapNode<K, V>, usize)> =
+            self.root.iter().map(|n| (&**n, 1)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            height = height.max(depth);
+            for child in [&node.children.0, &node.children.1].into_iter().flatten() {
+                stack.push((child, depth + 1));
+            }
+        }
+        height
+    }
+
+    /// Returns an iterator over the entries of the tree, sorted by key.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left_spine(self.root.as_deref());
+        iter
+    }
+
+    /// Returns the next priority, using the SplitMix64 generator.
+    fn next_priority(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Concatenates two trees, where every key in `left` must be less than every key in `right`.
+    ///
+    /// The merged tree keeps the seed of `left`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the largest key in `left` is not less than the smallest key in `right`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::treap::Treap;
+    ///
+    /// let low: Treap<_, _> = (0..5).map(|key| (key, ())).collect();
+    /// let high: Treap<_, _> = (5..10).map(|key| (key, ())).collect();
+    ///
+    /// let tree = Treap::merge(low, high);
+    /// assert_eq!(tree.len(), 10);
+    /// assert!(tree.iter().map(|(key, _)| *key).eq(0..10));
+    /// ```
+    pub fn merge(mut left: Self, mut right: Self) -> Self
+    where
+        K: Ord,
+    {
+        if let (Some(last), Some(first)) = (left.last_key(), right.first_key()) {
+            assert!(
+                last < first,
+                "every key of the left treap must be less than every key of the right treap"
+            );
+        }
+
+        let root = TreapNode::merge(left.root.take(), right.root.take());
+        Self {
+            root,
+            len: left.len + right.len,
+            seed: left.seed,
+        }
+    }
+
+    fn first_key(&self) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.children.0.as_deref() {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    fn last_key(&self) -> Option<&K> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.children.1.as_deref() {
+            node = right;
+        }
+        Some(&node.key)
+    }
+}
+
+impl<K, V> Treap<K, V>
+where
+    K: Ord,
+{
+    /// Inserts a key-value pair into the [`Treap`].
+    ///
+    /// Returns [`None`] if the key did not exist, otherwise updates
+    /// the value and returns [`Some`] with the old value.
+    ///
+    /// # Note
+    ///
+    /// Like with `HashMap` the key does not get updated.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(core::mem::replace(old, value));
+        }
+
+        // Walk down until the new node outranks the subtree in its way, then cut that subtree
+        // around the key and hang both halves below the new node.
+        let priority = self.next_priority();
+        let mut link = &mut self.root;
+        while matches!(link, Some(node) if node.priority >= priority) {
+            if let Some(node) = link {
+                node.size += 1;
+                link = if key < node.key {
+                    &mut node.children.0
+                } else {
+                    &mut node.children.1
+                };
+            }
+        }
+
+        let children = TreapNode::split(link.take(), &key);
+        let mut node = Box::new(TreapNode {
+            key,
+            value,
+            priority,
+            size: 1,
+            children,
+        });
+        node.update();
+        *link = Some(node);
+        self.len += 1;
+        None
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                core::cmp::Ordering::Less => current.children.0.as_deref(),
+                core::cmp::Ordering::Greater => current.children.1.as_deref(),
+                core::cmp::Ordering::Equal => return Some(&current.value),
+            };
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root.as_deref_mut();
+        while let Some(current) = node {
+            node = match key.cmp(current.key.borrow()) {
+                core::cmp::Ordering::Less => current.children.0.as_deref_mut(),
+                core::cmp::Ordering::Greater => current.children.1.as_deref_mut(),
+                core::cmp::Ordering::Equal => return Some(&mut current.value),
+            };
+        }
+        None
+    }
+
+    /// Returns `true` if the tree contains a value for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Removes a key from the tree, returning its value if it was present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes a key from the tree, returning the stored key and value if it was present.
+    ///
+    /// The children of the removed node are merged to take its place.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if !self.contains_key(key) {
+            return None;
+        }
+
+        let mut link = &mut self.root;
+        while matches!(link, Some(node) if node.key.borrow() != key) {
+            if let Some(node) = link {
+                node.size -= 1;
+                link = if key < node.key.borrow() {
+                    &mut node.children.0
+                } else {
+                    &mut node.children.1
+                };
+            }
+        }
+
+        let mut node = link.take()?;
+        *link = TreapNode::merge(node.children.0.take(), node.children.1.take());
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    /// Splits the tree in two around `key`. The first tree holds every entry with a key less than
+    /// `key`, and the second one every other entry.
+    ///
+    /// Both trees keep generating priorities from where this tree left off, the second one with
+    /// a seed derived from it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::tree::treap::Treap;
+    ///
+    /// let tree: Treap<_, _> = (1..=5).map(|key| (key, ())).collect();
+    /// let (low, high) = tree.split(&3);
+    ///
+    /// assert!(low.iter().map(|(key, _)| *key).eq(1..3));
+    /// assert!(high.iter().map(|(key, _)| *key).eq(3..=5));
+    /// ```
+    pub fn split<Q>(mut self, key: &Q) -> (Self, Self)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (left, right) = TreapNode::split(self.root.take(), key);
+        let size = |link: &NodeChild<K, V>| link.as_ref().map_or(0, |node| node.size);
+        let right_seed = self.next_priority();
+        (
+            Self {
+                len: size(&left),
+                root: left,
+                seed: self.seed,
+            },
+            Self {
+                len: size(&right),
+                root: right,
+                seed: right_seed,
+            },
+        )
+    }
+}
+
+impl<K, V> TreapNode<K, V> {
+    /// Recomputes the size of this node from its children.
+    fn update(&mut self) {
+        let size = |link: &NodeChild<K, V>| link.as_ref().map_or(0, |node| node.size);
+        self.size = 1 + size(&self.children.0) + size(&self.children.1);
+    }
+
+    /// Concatenates two subtrees, where every key in `left` is less than every key in `right`.
+    /// Recursion only follows the right spine of `left` and the left spine of `right`, so its
+    /// expected depth is logarithmic.
+    fn merge(left: NodeChild<K, V>, right: NodeChild<K, V>) -> NodeChild<K, V> {
+        match (left, right) {
+            (None, link) | (link, None) => link,
+            (Some(mut left), Some(mut right)) => {
+                if left.priority >= right.priority {
+                    left.children.1 = Self::merge(left.children.1.take(), Some(right));
+                    left.update();
+                    Some(left)
+                } else {
+                    right.children.0 = Self::merge(Some(left), right.children.0.take());
+                    right.update();
+                    Some(right)
+                }
+            }
+        }
+    }
+
+    /// Splits a subtree into the nodes with keys less than `key` and all other nodes.
+    fn split<Q>(link: NodeChild<K, V>, key: &Q) -> (NodeChild<K, V>, NodeChild<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match link {
+            None => (None, None),
+            Some(mut node) => {
+                if node.key.borrow() < key {
+                    let (middle, right) = Self::split(node.children.1.take(), key);
+                    node.children.1 = middle;
+                    node.update();
+                    (Some(node), right)
+                } else {
+                    let (left, middle) = Self::split(node.children.0.take(), key);
+                    node.children.0 = middle;
+                    node.update();
+                    (left, Some(node))
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for Treap<K, V> {
+    /// Frees the nodes without recursing, rotating left children up until there are none, like
+    /// `BinaryTree` does.
+    fn drop(&mut self) {
+        let mut link = self.root.take();
+        while let Some(mut node) = link {
+            link = if let Some(mut left) = node.children.0.take() {
+                node.children.0 = left.children.1.take();
+                left.children.1 = Some(node);
+                Some(left)
+            } else {
+                node.children.1.take()
+            };
+        }
+    }
+}
+
+impl<K, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for Treap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for Treap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K, V, Q> core::ops::Index<&Q> for Treap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the treap.
+    fn index(&self, index: &Q) -> &Self::Output {
+        self.get(index).expect("Key is not present in the treap")
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Treap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`Treap`], sorted by key.
+///
+/// This `struct` is created by [`Treap::iter`].
+pub struct Iter<'a, K, V> {
+    /// The nodes whose entry and right subtree are still to be visited, the next one on top.
+    stack: Vec<&'a TreapNode<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut node: Option<&'a TreapNode<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.children.0.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.children.1.as_deref());
+        self.len -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::collections::BTreeMap;
+
+    /// Checks that keys are in search tree order, priorities in heap order and that the sizes
+    /// add up.
+    fn assert_treap<K: Ord, V>(tree: &Treap<K, V>) {
+        fn check<K: Ord, V>(node: &TreapNode<K, V>, low: Option<&K>, high: Option<&K>) -> usize {
+            if let Some(low) = low {
+                assert!(*low < node.key);
+            }
+            if let Some(high) = high {
+                assert!(node.key < *high);
+            }
+            let mut size = 1;
+            if let Some(left) = &node.children.0 {
+                assert!(left.priority <= node.priority);
+                size += check(left, low, Some(&node.key));
+            }
+            if let Some(right) = &node.children.1 {
+                assert!(right.priority <= node.priority);
+                size += check(right, Some(&node.key), high);
+            }
+            assert_eq!(node.size, size);
+            size
+        }
+
+        let size = tree.root.as_ref().map_or(0, |root| check(root, None, None));
+        assert_eq!(size, tree.len());
+    }
+
+    crate::tree::map_tests!(Treap, assert_treap);
+
+    #[test]
+    fn randomized_against_btree_map() {
+        let mut rng = Rng::new(438);
+        let mut tree = Treap::with_seed(438);
+        let mut reference = BTreeMap::new();
+
+        for step in 0..10_000 {
+            let key = rng.below(1_000);
+            if rng.below(3) == 0 {
+                assert_eq!(tree.remove(&key), reference.remove(&key));
+            } else {
+                let value = rng.next_u64();
+                assert_eq!(tree.insert(key, value), reference.insert(key, value));
+            }
+            assert_eq!(tree.len(), reference.len());
+            if step % 500 == 0 {
+                assert_treap(&tree);
+            }
+        }
+
+        assert_treap(&tree);
+        assert!(tree.iter().eq(reference.iter()));
+    }
+
+    #[test]
+    fn sequential_inserts_stay_shallow() {
+        for seed in [1, 2, 3] {
+            let mut tree = Treap::with_seed(seed);
+            let n = 10_000u32;
+            for key in 0..n {
+                tree.insert(key, ());
+            }
+            assert_treap(&tree);
+
+            // The expected depth is about `2 ln(n)`, so four times `log2(n)` leaves plenty of
+            // slack while still ruling out the linear depth of an unbalanced tree.
+            let log2 = (u32::BITS - n.leading_zeros()) as usize;
+            assert!(tree.height() <= 4 * log2, "height {}", tree.height());
+        }
+    }
+
+    #[test]
+    fn same_seed_same_shape() {
+        let build = |seed| {
+            let mut tree = Treap::with_seed(seed);
+            for key in [5, 3, 8, 1, 4, 7, 9, 2, 6] {
+                tree.insert(key, ());
+            }
+            tree
+        };
+
+        let priorities = |tree: &Treap<i32, ()>| {
+            let mut stack: Vec<&TreapNode<i32, ()>> = tree.root.iter().map(|n| &**n).collect();
+            let mut priorities = Vec::new();
+            while let Some(node) = stack.pop() {
+                priorities.push((node.key, node.priority));
+                stack.extend(node.children.0.as_deref());
+                stack.extend(node.children.1.as_deref());
+            }
+            priorities
+        };
+
+        assert_eq!(priorities(&build(7)), priorities(&build(7)));
+        assert_ne!(priorities(&build(7)), priorities(&build(8)));
+    }
+
+    #[test]
+    fn split_and_merge() {
+        let mut rng = Rng::new(4380);
+        let tree: Treap<u64, u64> = (0..500).map(|key| (key * 2, key)).collect();
+
+        for _ in 0..50 {
+            let at = rng.below(1_002);
+            let (low, high) = tree.clone().split(&at);
+            assert_treap(&low);
+            assert_treap(&high);
+            assert!(low.iter().all(|(key, _)| *key < at));
+            assert!(high.iter().all(|(key, _)| *key >= at));
+            assert_eq!(low.len() + high.len(), tree.len());
+
+            let merged = Treap::merge(low, high);
+            assert_treap(&merged);
+            assert!(merged.iter().eq(tree.iter()));
+        }
+
+        let (empty, all) = tree.clone().split(&0);
+        assert!(empty.is_empty());
+        assert_eq!(all.len(), 500);
+        let merged = Treap::merge(Treap::new(), all);
+        assert_eq!(merged.len(), 500);
+    }
+
+    #[test]
+    #[should_panic(expected = "every key of the left treap must be less than every key")]
+    fn merge_overlapping() {
+        let low: Treap<_, _> = (0..5).map(|key| (key, ())).collect();
+        let high: Treap<_, _> = (4..10).map(|key| (key, ())).collect();
+        let _ = Treap::merge(low, high);
+    }
+}
@@ -0,0 +1,534 @@
+use alloc::{string::String, vec::Vec};
+
+/// A prefix tree mapping string keys to values.
+///
+/// Every edge of the tree is labelled with a byte, and the key of an entry is spelled out by the
+/// edges on the path from the root down to its node. Keys that share a prefix share the nodes
+/// for it, which makes enumerating every key with a given prefix and finding the longest key
+/// that prefixes a query cheap. Lookups take time proportional to the length of the key,
+/// independently of how many entries the trie holds.
+///
+/// The children of a node are kept in a small vector sorted by byte and found by binary search,
+/// so iteration yields the keys in lexicographic byte order.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::tree::trie::Trie;
+///
+/// let mut routes = Trie::new();
+/// routes.insert("/", "index");
+/// routes.insert("/api", "api");
+/// routes.insert("/api/users", "users");
+///
+/// assert_eq!(routes.get("/api"), Some(&"api"));
+/// assert_eq!(routes.longest_prefix_match("/api/users/42"), Some(("/api/users", &"users")));
+/// assert_eq!(routes.longest_prefix_match("/about"), Some(("/", &"index")));
+///
+/// let api: Vec<String> = routes.iter_prefix("/api").map(|(key, _)| key).collect();
+/// assert_eq!(api, ["/api", "/api/users"]);
+/// ```
+pub struct Trie<V> {
+    root: TrieNode<V>,
+    len: usize,
+}
+
+struct TrieNode<V> {
+    value: Option<V>,
+    /// The children of this node together with the bytes labelling their edges, sorted by byte.
+    children: Vec<(u8, TrieNode<V>)>,
+}
+
+impl<V> Default for TrieNode<V> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<V> TrieNode<V> {
+    fn child(&self, byte: u8) -> Option<&Self> {
+        let index = self.child_index(byte).ok()?;
+        Some(&self.children[index].1)
+    }
+
+    fn child_mut(&mut self, byte: u8) -> Option<&mut Self> {
+        let index = self.child_index(byte).ok()?;
+        Some(&mut self.children[index].1)
+    }
+
+    fn child_index(&self, byte: u8) -> Result<usize, usize> {
+        self.children
+            .binary_search_by_key(&byte, |(label, _)| *label)
+    }
+
+    /// Frees the subtree rooted at `node` without recursing, so that long keys cannot overflow
+    /// the stack.
+    fn free(node: Self) {
+        let mut pending = alloc::vec![node];
+        while let Some(mut node) = pending.pop() {
+            pending.extend(node.children.drain(..).map(|(_, child)| child));
+        }
+    }
+
+    /// Copies the subtree rooted at `node` without recursing, so that long keys cannot overflow
+    /// the stack.
+    fn clone_subtree(node: &Self) -> Self
+    where
+        V: Clone,
+    {
+        // Each frame holds a node being copied, the label of the edge leading to it, and the
+        // copies of the children finished so far.
+        let mut stack = alloc::vec![(0, node, Vec::with_capacity(node.children.len()))];
+        loop {
+            let (_, source, copies) = stack.last().expect("The stack holds the node being copied");
+            let source: &Self = source;
+            if let Some((label, child)) = source.children.get(copies.len()) {
+                stack.push((*label, child, Vec::with_capacity(child.children.len())));
+                continue;
+            }
+
+            let (label, source, children) =
+                stack.pop().expect("The stack holds the node being copied");
+            let copy = Self {
+                value: source.value.clone(),
+                children,
+            };
+            match stack.last_mut() {
+                Some((_, _, copies)) => copies.push((label, copy)),
+                None => return copy,
+            }
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Constructs an empty trie.
+    pub fn new() -> Self {
+        Self {
+            root: TrieNode::default(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of entries in the trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a key-value pair into the trie.
+    ///
+    /// Returns [`None`] if the key was not present, otherwise replaces the value and returns
+    /// [`Some`] with the old one.
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for &byte in key.as_bytes() {
+            let index = match node.child_index(byte) {
+                Ok(index) => index,
+                Err(index) => {
+                    node.children.insert(index, (byte, TrieNode::default()));
+                    index
+                }
+            };
+            node = &mut node.children[index].1;
+        }
+
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.node(key)?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for &byte in key.as_bytes() {
+            node = node.child_mut(byte)?;
+        }
+        node.value.as_mut()
+    }
+
+    /// Returns `true` if the trie contains a value for the key.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes a key from the trie, returning its value if it was present.
+    ///
+    /// Nodes that no longer lead to any entry are freed, so removing every key returns the trie
+    /// to its initial state.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let bytes = key.as_bytes();
+
+        // Find the deepest node on the path that has to stay because it holds a value of its own
+        // or leads to other entries. Everything below it exists only for the key being removed.
+        let mut keep = 0;
+        let mut node = &self.root;
+        for (depth, &byte) in bytes.iter().enumerate() {
+            if node.value.is_some() || node.children.len() > 1 {
+                keep = depth;
+            }
+            node = node.child(byte)?;
+        }
+        node.value.as_ref()?;
+        let prune = !bytes.is_empty() && node.children.is_empty();
+
+        let mut node = &mut self.root;
+        for &byte in &bytes[..keep] {
+            node = node.child_mut(byte)?;
+        }
+        let value = if prune {
+            let index = node.child_index(*bytes.get(keep)?).ok()?;
+            let (_, mut branch) = node.children.remove(index);
+            let mut leaf = &mut branch;
+            for &byte in &bytes[keep + 1..] {
+                leaf = leaf.child_mut(byte)?;
+            }
+            let value = leaf.value.take();
+            TrieNode::free(branch);
+            value
+        } else {
+            for &byte in &bytes[keep..] {
+                node = node.child_mut(byte)?;
+            }
+            node.value.take()
+        };
+
+        self.len -= 1;
+        value
+    }
+
+    /// Returns the longest key in the trie that is a prefix of `query`, along with its value.
+    pub fn longest_prefix_match<'q>(&self, query: &'q str) -> Option<(&'q str, &V)> {
+        let mut best = self.root.value.as_ref().map(|value| (0, value));
+        let mut node = &self.root;
+        for (depth, &byte) in query.as_bytes().iter().enumerate() {
+            node = match node.child(byte) {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(value) = &node.value {
+                best = Some((depth + 1, value));
+            }
+        }
+
+        // The matched bytes spell out a whole key, which is valid UTF-8 on its own, so they end
+        // on a character boundary of `query`.
+        best.map(|(len, value)| (&query[..len], value))
+    }
+
+    /// Returns an iterator over every entry whose key starts with `prefix`, in lexicographic
+    /// order.
+    pub fn iter_prefix(&self, prefix: &str) -> IterPrefix<'_, V> {
+        let mut pending = Vec::new();
+        if let Some(node) = self.node(prefix) {
+            pending.push((prefix.len(), None, node));
+        }
+
+        IterPrefix {
+            key: prefix.as_bytes().to_vec(),
+            pending,
+        }
+    }
+
+    /// Returns an iterator over every entry of the trie, in lexicographic order.
+    pub fn iter(&self) -> IterPrefix<'_, V> {
+        self.iter_prefix("")
+    }
+
+    /// Removes every entry from the trie.
+    pub fn clear(&mut self) {
+        TrieNode::free(core::mem::take(&mut self.root));
+        self.len = 0;
+    }
+
+    fn node(&self, key: &str) -> Option<&TrieNode<V>> {
+        let mut node = &self.root;
+        for &byte in key.as_bytes() {
+            node = node.child(byte)?;
+        }
+        Some(node)
+    }
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone> Clone for Trie<V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: TrieNode::clone_subtree(&self.root),
+            len: self.len,
+        }
+    }
+}
+
+impl<V> Drop for Trie<V> {
+    fn drop(&mut self) {
+        TrieNode::free(core::mem::take(&mut self.root));
+    }
+}
+
+impl<V: core::fmt::Debug> core::fmt::Debug for Trie<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V: PartialEq> PartialEq for Trie<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<V: Eq> Eq for Trie<V> {}
+
+impl<'k, V> FromIterator<(&'k str, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (&'k str, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        for (key, value) in iter {
+            trie.insert(key, value);
+        }
+        trie
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Trie<V> {
+    type Item = (String, &'a V);
+    type IntoIter = IterPrefix<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`Trie`] that share a prefix, in lexicographic order.
+///
+/// This `struct` is created by [`Trie::iter_prefix`] and [`Trie::iter`].
+pub struct IterPrefix<'a, V> {
+    /// The key of the node visited last.
+    key: Vec<u8>,
+    /// The nodes still to visit, each with the length its parent's key and the byte leading to it.
+    pending: Vec<(usize, Option<u8>, &'a TrieNode<V>)>,
+}
+
+impl<'a, V> Iterator for IterPrefix<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((len, byte, node)) = self.pending.pop() {
+            self.key.truncate(len);
+            self.key.extend(byte);
+
+            let len = self.key.len();
+            self.pending.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|(byte, child)| (len, Some(*byte), child)),
+            );
+
+            if let Some(value) = &node.value {
+                let key = String::from_utf8(self.key.clone())
+                    .expect("Keys should be valid UTF-8 since they are inserted as strings");
+                return Some((key, value));
+            }
+        }
+        None
+    }
+}
+
+impl<V> Clone for IterPrefix<'_, V> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<V: core::fmt::Debug> core::fmt::Debug for IterPrefix<'_, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeMap, format, vec};
+
+    /// Counts every node of the trie, including the root.
+    fn count_nodes<V>(trie: &Trie<V>) -> usize {
+        let mut pending = vec![&trie.root];
+        let mut count = 0;
+        while let Some(node) = pending.pop() {
+            count += 1;
+            pending.extend(node.children.iter().map(|(_, child)| child));
+        }
+        count
+    }
+
+    #[test]
+    fn keys_that_prefix_other_keys() {
+        let mut trie = Trie::new();
+        for (value, key) in ["a", "ab", "abc", "b", "abd"].into_iter().enumerate() {
+            assert_eq!(trie.insert(key, value), None);
+        }
+        assert_eq!(trie.len(), 5);
+        assert_eq!(trie.get("ab"), Some(&1));
+        assert_eq!(trie.get("abc"), Some(&2));
+        assert_eq!(trie.get("abcd"), None);
+        assert_eq!(trie.get(""), None);
+
+        let keys: Vec<String> = trie.iter_prefix("ab").map(|(key, _)| key).collect();
+        assert_eq!(keys, ["ab", "abc", "abd"]);
+        assert_eq!(trie.iter_prefix("abc").count(), 1);
+        assert_eq!(trie.iter_prefix("c").count(), 0);
+
+        assert_eq!(trie.longest_prefix_match("abcdef"), Some(("abc", &2)));
+        assert_eq!(trie.longest_prefix_match("abx"), Some(("ab", &1)));
+        assert_eq!(trie.longest_prefix_match("x"), None);
+
+        *trie.get_mut("ab").expect("Key should be present") = 10;
+        assert_eq!(trie.insert("ab", 11), Some(10));
+        assert_eq!(trie.len(), 5);
+    }
+
+    #[test]
+    fn empty_key() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.longest_prefix_match("anything"), None);
+        trie.insert("", 0);
+        trie.insert("key", 1);
+
+        assert_eq!(trie.get(""), Some(&0));
+        assert_eq!(trie.longest_prefix_match("anything"), Some(("", &0)));
+        assert_eq!(trie.longest_prefix_match(""), Some(("", &0)));
+        assert_eq!(format!("{:?}", trie), r#"{"": 0, "key": 1}"#);
+
+        assert_eq!(trie.remove(""), Some(0));
+        assert_eq!(trie.remove(""), None);
+        assert_eq!(trie.get("key"), Some(&1));
+        assert_eq!(trie.len(), 1);
+    }
+
+    #[test]
+    fn removal_prunes_empty_branches() {
+        let mut trie = Trie::new();
+        trie.insert("team", 1);
+        assert_eq!(count_nodes(&trie), 5);
+        trie.insert("tea", 2);
+        trie.insert("ten", 3);
+        trie.insert("teammate", 4);
+        assert_eq!(count_nodes(&trie), 10);
+
+        // Removing a key that other keys pass through keeps every node.
+        assert_eq!(trie.remove("team"), Some(1));
+        assert_eq!(count_nodes(&trie), 10);
+        // Removing a leaf frees the nodes down from the last one that is still needed.
+        assert_eq!(trie.remove("teammate"), Some(4));
+        assert_eq!(count_nodes(&trie), 5);
+        assert_eq!(trie.remove("te"), None);
+        assert_eq!(trie.remove("tea"), Some(2));
+        assert_eq!(count_nodes(&trie), 4);
+        assert_eq!(trie.remove("ten"), Some(3));
+        assert_eq!(count_nodes(&trie), 1);
+        assert!(trie.is_empty());
+        assert!(trie.root.children.is_empty());
+    }
+
+    #[test]
+    fn randomized_against_btree() {
+        let mut rng = Rng::new(436);
+        let mut trie = Trie::new();
+        let mut reference = BTreeMap::new();
+
+        fn random_key(rng: &mut Rng) -> String {
+            let len = rng.below(5) as usize;
+            (0..len)
+                .map(|_| ['a', 'b', 'c', 'é'][rng.below(4) as usize])
+                .collect()
+        }
+
+        for _ in 0..5_000 {
+            let key = random_key(&mut rng);
+            if rng.below(3) == 0 {
+                assert_eq!(trie.remove(&key), reference.remove(&key));
+            } else {
+                let value = rng.next_u64();
+                assert_eq!(
+                    trie.insert(&key, value),
+                    reference.insert(key.clone(), value)
+                );
+            }
+            assert_eq!(trie.len(), reference.len());
+
+            let probe = random_key(&mut rng);
+            assert_eq!(trie.get(&probe), reference.get(&probe));
+            let expected = reference
+                .iter()
+                .filter(|(key, _)| probe.starts_with(key.as_str()))
+                .max_by_key(|(key, _)| key.len())
+                .map(|(key, value)| (key.as_str(), value));
+            let found = trie
+                .longest_prefix_match(&probe)
+                .map(|(key, value)| (String::from(key), value));
+            assert_eq!(
+                found.as_ref().map(|(key, value)| (key.as_str(), *value)),
+                expected
+            );
+            let with_prefix = reference
+                .range(probe.clone()..)
+                .take_while(|(key, _)| key.starts_with(&probe))
+                .map(|(key, value)| (key.clone(), value));
+            assert!(trie.iter_prefix(&probe).eq(with_prefix));
+        }
+
+        assert!(trie
+            .iter()
+            .eq(reference.iter().map(|(key, value)| (key.clone(), value))));
+        for key in reference.keys() {
+            trie.remove(key);
+        }
+        assert_eq!(count_nodes(&trie), 1);
+    }
+
+    #[test]
+    fn long_keys() {
+        let key: String = "a".repeat(100_000);
+        let mut trie = Trie::new();
+        trie.insert(&key, 1);
+        trie.insert(&key[..50_000], 2);
+        assert_eq!(
+            trie.longest_prefix_match(&key[..70_000]),
+            Some((&key[..50_000], &2))
+        );
+        assert_eq!(trie.remove(&key), Some(1));
+        assert_eq!(count_nodes(&trie), 50_001);
+
+        trie.insert(&key, 3);
+        let copy = trie.clone();
+        drop(trie);
+        assert_eq!(copy.get(&key), Some(&3));
+        assert_eq!(copy.get(&key[..50_000]), Some(&2));
+        assert_eq!(count_nodes(&copy), 100_001);
+    }
+}
@@ -0,0 +1,387 @@
+use alloc::vec::Vec;
+
+const BITS: usize = usize::BITS as usize;
+
+/// A growable vector of booleans, packed into machine words.
+///
+/// Each bit takes up a single bit of memory, so a [`BitVec`] is eight times smaller than a
+/// `Vec<bool>` of the same length. Bit `i` lives in word `i / usize::BITS` at position
+/// `i % usize::BITS`. The unused bits of the last word are always kept cleared, so they never
+/// show up in [`count_ones`](BitVec::count_ones) or comparisons.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::bit_vec::BitVec;
+///
+/// let mut visited = BitVec::repeat(false, 1_000);
+/// visited.set(7, true);
+/// visited.set(999, true);
+///
+/// assert_eq!(visited.get(7), Some(true));
+/// assert_eq!(visited.get(8), Some(false));
+/// assert_eq!(visited.get(1_000), None);
+/// assert_eq!(visited.count_ones(), 2);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<usize>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Constructs an empty bit vector.
+    pub fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Constructs an empty bit vector with room for at least `capacity` bits.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(words_for(capacity)),
+            len: 0,
+        }
+    }
+
+    /// Constructs a bit vector holding `len` copies of `bit`.
+    pub fn repeat(bit: bool, len: usize) -> Self {
+        let fill = if bit { usize::MAX } else { 0 };
+        let mut bits = Self {
+            words: alloc::vec![fill; words_for(len)],
+            len,
+        };
+        bits.clear_unused();
+        bits
+    }
+
+    /// Returns the number of bits in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector holds no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a bit to the end of the vector.
+    pub fn push(&mut self, bit: bool) {
+        if self.len == self.words.len() * BITS {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, bit);
+    }
+
+    /// Removes the last bit and returns it, or [`None`] if the vector is empty.
+    pub fn pop(&mut self) -> Option<bool> {
+        let bit = self.get(self.len.checked_sub(1)?)?;
+        self.truncate(self.len - 1);
+        Some(bit)
+    }
+
+    /// Returns the bit at `index`, or [`None`] if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.words[index / BITS] & (1 << (index % BITS)) != 0)
+    }
+
+    /// Sets the bit at `index` to `bit`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len, "Bit index out of bounds in BitVec");
+        let mask = 1 << (index % BITS);
+        if bit {
+            self.words[index / BITS] |= mask;
+        } else {
+            self.words[index / BITS] &= !mask;
+        }
+    }
+
+    /// Shortens the vector to `len` bits. Does nothing if it is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+            self.words.truncate(words_for(len));
+            self.clear_unused();
+        }
+    }
+
+    /// Removes every bit from the vector.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns the number of bits that are set.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns an iterator over the bits of the vector.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            bits: self,
+            range: 0..self.len,
+        }
+    }
+
+    /// Sets every bit to the logical and of itself and the bit at the same index in `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::vec::bit_vec::BitVec;
+    ///
+    /// let mut a: BitVec = [true, true, false].into_iter().collect();
+    /// let b: BitVec = [true, false, false].into_iter().collect();
+    /// a.and_assign(&b);
+    /// assert!(a.iter().eq([true, false, false]));
+    /// ```
+    pub fn and_assign(&mut self, other: &BitVec) {
+        self.zip_words(other, |a, b| a & b);
+    }
+
+    /// Sets every bit to the logical or of itself and the bit at the same index in `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn or_assign(&mut self, other: &BitVec) {
+        self.zip_words(other, |a, b| a | b);
+    }
+
+    /// Sets every bit to the exclusive or of itself and the bit at the same index in `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn xor_assign(&mut self, other: &BitVec) {
+        self.zip_words(other, |a, b| a ^ b);
+    }
+
+    /// Combines the words of both vectors pairwise. `f` must map cleared unused bits onto
+    /// cleared bits, which holds for every bitwise operation that maps `(0, 0)` onto `0`.
+    fn zip_words<F>(&mut self, other: &BitVec, mut f: F)
+    where
+        F: FnMut(usize, usize) -> usize,
+    {
+        assert_eq!(self.len, other.len, "BitVec lengths differ");
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word = f(*word, *other);
+        }
+    }
+
+    /// Clears the bits of the last word that lie beyond the end of the vector.
+    fn clear_unused(&mut self) {
+        let used = self.len % BITS;
+        if used != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+}
+
+/// Returns the number of words needed to hold `len` bits.
+fn words_for(len: usize) -> usize {
+    let (full, rest) = (len / BITS, len % BITS);
+    full + usize::from(rest > 0)
+}
+
+impl core::fmt::Debug for BitVec {
+    /// Formats the bits as a string of `0`s and `1`s, starting with the bit at index zero.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("BitVec(")?;
+        for bit in self {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = Self::new();
+        bits.extend(iter);
+        bits
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type Item = bool;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the bits of a [`BitVec`].
+///
+/// This `struct` is created by [`BitVec::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    bits: &'a BitVec,
+    range: core::ops::Range<usize>,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().and_then(|index| self.bits.get(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range
+            .next_back()
+            .and_then(|index| self.bits.get(index))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::format;
+
+    #[test]
+    fn randomized_against_vec() {
+        let mut rng = Rng::new(422);
+        let mut bits = BitVec::new();
+        let mut reference = Vec::new();
+
+        for _ in 0..5_000 {
+            match rng.below(10) {
+                0 => assert_eq!(bits.pop(), reference.pop()),
+                1 if !reference.is_empty() => {
+                    let index = rng.below(reference.len() as u64) as usize;
+                    let bit = rng.below(2) == 0;
+                    bits.set(index, bit);
+                    reference[index] = bit;
+                }
+                2 => {
+                    let len = rng.below(reference.len() as u64 + 2) as usize;
+                    bits.truncate(len);
+                    reference.truncate(len);
+                }
+                _ => {
+                    let bit = rng.below(3) != 0;
+                    bits.push(bit);
+                    reference.push(bit);
+                }
+            }
+
+            assert_eq!(bits.len(), reference.len());
+            assert_eq!(
+                bits.count_ones(),
+                reference.iter().filter(|bit| **bit).count()
+            );
+            let probe = rng.below(reference.len() as u64 + 2) as usize;
+            assert_eq!(bits.get(probe), reference.get(probe).copied());
+        }
+        assert!(bits.iter().eq(reference.iter().copied()));
+        assert!(bits.iter().rev().eq(reference.iter().rev().copied()));
+    }
+
+    #[test]
+    fn unused_bits_stay_cleared() {
+        let mut bits = BitVec::repeat(true, 70);
+        assert_eq!(bits.count_ones(), 70);
+
+        bits.truncate(65);
+        assert_eq!(bits.count_ones(), 65);
+        assert_eq!(bits, BitVec::repeat(true, 65));
+
+        // Growing again must not resurrect the bits that were cut off.
+        bits.push(false);
+        bits.push(false);
+        assert_eq!(bits.count_ones(), 65);
+        assert_eq!(bits.pop(), Some(false));
+        assert_eq!(bits.pop(), Some(false));
+        assert_eq!(bits, BitVec::repeat(true, 65));
+
+        let mut cleared = BitVec::repeat(true, 3);
+        cleared.clear();
+        cleared.extend([false, false]);
+        assert_eq!(cleared, BitVec::repeat(false, 2));
+        assert_eq!(cleared.count_ones(), 0);
+    }
+
+    #[test]
+    fn bulk_operations() {
+        let n = 3 * BITS + 5;
+        let evens: BitVec = (0..n).map(|i| i % 2 == 0).collect();
+        let threes: BitVec = (0..n).map(|i| i % 3 == 0).collect();
+
+        let mut and = evens.clone();
+        and.and_assign(&threes);
+        assert!(and.iter().eq((0..n).map(|i| i % 6 == 0)));
+
+        let mut or = evens.clone();
+        or.or_assign(&threes);
+        assert!(or.iter().eq((0..n).map(|i| i % 2 == 0 || i % 3 == 0)));
+
+        let mut xor = evens.clone();
+        xor.xor_assign(&threes);
+        assert!(xor.iter().eq((0..n).map(|i| (i % 2 == 0) != (i % 3 == 0))));
+
+        let mut all = BitVec::repeat(true, n);
+        all.xor_assign(&BitVec::repeat(false, n));
+        assert_eq!(all.count_ones(), n);
+        all.xor_assign(&BitVec::repeat(true, n));
+        assert_eq!(all, BitVec::repeat(false, n));
+    }
+
+    #[test]
+    #[should_panic(expected = "BitVec lengths differ")]
+    fn bulk_operation_length_mismatch() {
+        let mut a = BitVec::repeat(false, 10);
+        a.or_assign(&BitVec::repeat(true, 11));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit index out of bounds in BitVec")]
+    fn set_out_of_bounds() {
+        let mut bits = BitVec::repeat(false, 64);
+        bits.set(64, true);
+    }
+
+    #[test]
+    fn debug() {
+        let bits: BitVec = [true, false, true, true].into_iter().collect();
+        assert_eq!(format!("{:?}", bits), "BitVec(1011)");
+        assert_eq!(format!("{:?}", BitVec::new()), "BitVec()");
+    }
+}
@@ -0,0 +1,387 @@
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+const WORD_BITS: usize = usize::BITS as usize;
+
+/// Returns the number of words a [`FixedBitSet`] needs to hold `bits` bits.
+///
+/// Stable Rust cannot yet compute an array length from a const parameter, so the word count is a
+/// second parameter of the set, which this function fills in.
+pub const fn words(bits: usize) -> usize {
+    let (full, rest) = (bits / WORD_BITS, bits % WORD_BITS);
+    full + (rest > 0) as usize
+}
+
+/// A set of the integers `0..BITS`, stored inline as `WORDS` machine words.
+///
+/// The set never allocates, so it works without an allocator and can live in a `static`. `WORDS`
+/// must be [`words(BITS)`](words); any other value fails to compile as soon as a set is
+/// constructed. Integer `i` is bit `i % usize::BITS` of word `i / usize::BITS`, and the unused
+/// bits of the last word are always kept cleared, including by [`!`](Not).
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::fixed_bit_set::{words, FixedBitSet};
+///
+/// type Peripherals = FixedBitSet<256, { words(256) }>;
+///
+/// static NONE: Peripherals = Peripherals::new();
+///
+/// let mut enabled = NONE;
+/// assert!(enabled.insert(3));
+/// assert!(enabled.insert(200));
+/// assert!(!enabled.insert(3));
+///
+/// let mut wanted = Peripherals::new();
+/// wanted.insert(200);
+/// wanted.insert(7);
+/// assert!((enabled & wanted).iter().eq([200]));
+/// assert!((enabled | wanted).iter().eq([3, 7, 200]));
+/// assert_eq!((!enabled).len(), 254);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedBitSet<const BITS: usize, const WORDS: usize> {
+    words: [usize; WORDS],
+}
+
+impl<const BITS: usize, const WORDS: usize> FixedBitSet<BITS, WORDS> {
+    const WORDS_MATCH_BITS: () = assert!(
+        WORDS == words(BITS),
+        "the word count of a FixedBitSet must be words(BITS)"
+    );
+
+    /// Constructs an empty set.
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::WORDS_MATCH_BITS;
+        Self { words: [0; WORDS] }
+    }
+
+    /// Returns the number of integers the set can hold, which is `BITS`.
+    pub const fn capacity(&self) -> usize {
+        BITS
+    }
+
+    /// Returns the number of integers in the set.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the word and the mask of the bit for `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn locate(index: usize) -> (usize, usize) {
+        assert!(index < BITS, "Bit index out of bounds in FixedBitSet");
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+
+    /// Returns `true` if `index` is in the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than `BITS`.
+    pub fn contains(&self, index: usize) -> bool {
+        let (word, mask) = Self::locate(index);
+        self.words[word] & mask != 0
+    }
+
+    /// Adds `index` to the set, returning `true` if it was not already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than `BITS`.
+    pub fn insert(&mut self, index: usize) -> bool {
+        let (word, mask) = Self::locate(index);
+        let absent = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        absent
+    }
+
+    /// Removes `index` from the set, returning `true` if it was present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than `BITS`.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let (word, mask) = Self::locate(index);
+        let present = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        present
+    }
+
+    /// Removes every integer from the set.
+    pub fn clear(&mut self) {
+        self.words = [0; WORDS];
+    }
+
+    /// Returns an iterator over the integers in the set in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            words: &self.words,
+            base: 0,
+            current: self.words.first().copied().unwrap_or(0),
+        }
+    }
+
+    /// Clears the bits of the last word that lie beyond `BITS`.
+    fn clear_unused(&mut self) {
+        let used = BITS % WORD_BITS;
+        if used != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << used) - 1;
+            }
+        }
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Default for FixedBitSet<BITS, WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> core::fmt::Debug for FixedBitSet<BITS, WORDS> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Extend<usize> for FixedBitSet<BITS, WORDS> {
+    /// Adds every integer of `iter` to the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of them is not less than `BITS`.
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for index in iter {
+            self.insert(index);
+        }
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> FromIterator<usize> for FixedBitSet<BITS, WORDS> {
+    /// Builds a set of the integers of `iter`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of them is not less than `BITS`.
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, const BITS: usize, const WORDS: usize> IntoIterator for &'a FixedBitSet<BITS, WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+macro_rules! bitwise_ops {
+    ($($op:ident :: $method:ident, $assign:ident :: $assign_method:ident => $symbol:tt;)*) => {
+        $(
+            impl<const BITS: usize, const WORDS: usize> $assign for FixedBitSet<BITS, WORDS> {
+                fn $assign_method(&mut self, other: Self) {
+                    for (word, other) in self.words.iter_mut().zip(other.words) {
+                        *word = *word $symbol other;
+                    }
+                }
+            }
+
+            impl<const BITS: usize, const WORDS: usize> $op for FixedBitSet<BITS, WORDS> {
+                type Output = Self;
+
+                fn $method(mut self, other: Self) -> Self {
+                    self.$assign_method(other);
+                    self
+                }
+            }
+        )*
+    };
+}
+
+// Each of these maps two cleared bits onto a cleared bit, so the unused bits stay cleared.
+bitwise_ops! {
+    BitAnd::bitand, BitAndAssign::bitand_assign => &;
+    BitOr::bitor, BitOrAssign::bitor_assign => |;
+    BitXor::bitxor, BitXorAssign::bitxor_assign => ^;
+}
+
+impl<const BITS: usize, const WORDS: usize> Not for FixedBitSet<BITS, WORDS> {
+    type Output = Self;
+
+    /// Returns the complement of the set within `0..BITS`.
+    fn not(mut self) -> Self {
+        for word in &mut self.words {
+            *word = !*word;
+        }
+        self.clear_unused();
+        self
+    }
+}
+
+/// An iterator over the integers in a [`FixedBitSet`] in ascending order.
+///
+/// This `struct` is created by [`FixedBitSet::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    /// The words that have not been reached yet, starting with the current one.
+    words: &'a [usize],
+    /// The integer that bit zero of the current word stands for.
+    base: usize,
+    /// The bits of the current word that have not been yielded yet.
+    current: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.words = self.words.get(1..).filter(|rest| !rest.is_empty())?;
+            self.base += WORD_BITS;
+            self.current = self.words[0];
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.base + bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest: usize = self
+            .words
+            .iter()
+            .skip(1)
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        let len = self.current.count_ones() as usize + rest;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeSet, vec::Vec};
+
+    type Small = FixedBitSet<70, { words(70) }>;
+
+    fn unused_bits<const BITS: usize, const WORDS: usize>(set: &FixedBitSet<BITS, WORDS>) -> usize {
+        let last = set.words.last().copied().unwrap_or(0);
+        match BITS % WORD_BITS {
+            0 => 0,
+            used => last >> used,
+        }
+    }
+
+    #[test]
+    fn word_counts() {
+        assert_eq!(words(0), 0);
+        assert_eq!(words(1), 1);
+        assert_eq!(words(WORD_BITS), 1);
+        assert_eq!(words(WORD_BITS + 1), 2);
+    }
+
+    #[test]
+    fn unused_bits_stay_cleared() {
+        let mut set: Small = (60..70).collect();
+        assert_eq!(unused_bits(&set), 0);
+
+        let complement = !set;
+        assert_eq!(unused_bits(&complement), 0);
+        assert_eq!(complement.len(), 60);
+        assert!(complement.iter().eq(0..60));
+
+        set ^= complement;
+        assert_eq!(unused_bits(&set), 0);
+        assert_eq!(set.len(), 70);
+        assert!((!set).is_empty());
+
+        set = !Small::new() | !Small::new();
+        assert_eq!(unused_bits(&set), 0);
+        assert_eq!(set.iter().last(), Some(69));
+        assert_eq!(!(!set), set);
+    }
+
+    #[test]
+    fn exact_multiple_of_word_size() {
+        type Full = FixedBitSet<{ 2 * WORD_BITS }, 2>;
+        let set = !Full::new();
+        assert_eq!(set.len(), 2 * WORD_BITS);
+        assert_eq!(set.iter().len(), 2 * WORD_BITS);
+        assert!(set.contains(2 * WORD_BITS - 1));
+
+        let empty: FixedBitSet<0, 0> = FixedBitSet::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().next(), None);
+        assert!((!empty).is_empty());
+    }
+
+    #[test]
+    fn operators() {
+        let a: Small = [1, 5, 64, 69].into_iter().collect();
+        let b: Small = [5, 6, 69].into_iter().collect();
+        assert!((a & b).iter().eq([5, 69]));
+        assert!((a | b).iter().eq([1, 5, 6, 64, 69]));
+        assert!((a ^ b).iter().eq([1, 6, 64]));
+
+        let mut c = a;
+        c &= b;
+        assert_eq!(c, a & b);
+        c |= a;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit index out of bounds in FixedBitSet")]
+    fn out_of_range_insert() {
+        Small::new().insert(70);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit index out of bounds in FixedBitSet")]
+    fn out_of_range_contains() {
+        Small::new().contains(usize::MAX);
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = Rng::new(457);
+        let mut set: FixedBitSet<300, { words(300) }> = FixedBitSet::new();
+        let mut model = BTreeSet::new();
+        for _ in 0..5_000 {
+            let index = rng.below(300) as usize;
+            match rng.below(50) {
+                0 => {
+                    set.clear();
+                    model.clear();
+                }
+                1..=24 => assert_eq!(set.insert(index), model.insert(index)),
+                _ => assert_eq!(set.remove(index), model.remove(&index)),
+            }
+            assert_eq!(set.contains(index), model.contains(&index));
+            assert_eq!(set.len(), model.len());
+        }
+        assert!(set.iter().eq(model.iter().copied()));
+        let complement: Vec<_> = (0..300).filter(|index| !model.contains(index)).collect();
+        assert!((!set).iter().eq(complement));
+        assert_eq!(unused_bits(&!set), 0);
+    }
+}
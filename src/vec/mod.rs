@@ -0,0 +1,16 @@
+/// Bit vectors packed into machine words
+pub mod bit_vec;
+/// Fixed-size bit sets stored inline
+pub mod fixed_bit_set;
+/// Generational-index storage with stable keys
+pub mod slot_map;
+/// Maps stored as sorted vectors of entries
+pub mod sorted_map;
+/// Sorted vectors with binary-search lookup
+pub mod sorted_vec;
+/// Sparse sets of small integer keys
+pub mod sparse_set;
+/// Vectors whose indices survive removals
+pub mod stable_vec;
+/// Double-ended queues with inline storage
+pub mod stack_deque;
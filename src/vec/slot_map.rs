@@ -0,0 +1,507 @@
+use alloc::vec::Vec;
+use core::{iter::Enumerate, slice};
+
+/// A handle to a value in a [`SlotMap`].
+///
+/// A key is the index of a slot together with the generation the slot was in when the value was
+/// inserted. Removing the value moves the slot to a new generation, so the key stops matching
+/// even after the slot is reused for another value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key {
+    index: usize,
+    generation: u32,
+}
+
+impl Key {
+    /// Returns the index of the slot the key refers to.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the generation of the slot that the key is valid for.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// A vector of values addressed by generational [`Key`]s.
+///
+/// Inserting a value returns a key that stays valid until the value is removed, no matter what
+/// else is inserted or removed in the meantime. Once it is removed, the key is rejected by every
+/// method, even if its slot has since been reused: each slot counts the values it has held, and a
+/// key only matches the generation it was issued for. Freed slots are reused before the vector
+/// grows, so lookups, insertions and removals are all `O(1)`.
+///
+/// A slot whose generation counter would overflow is retired rather than reused, so keys are
+/// never repeated.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::slot_map::SlotMap;
+///
+/// let mut entities = SlotMap::new();
+/// let player = entities.insert("player");
+/// let enemy = entities.insert("enemy");
+///
+/// assert_eq!(entities.remove(enemy), Some("enemy"));
+/// let bullet = entities.insert("bullet");
+///
+/// // The bullet reuses the enemy's slot, but the old key no longer matches it.
+/// assert_eq!(bullet.index(), enemy.index());
+/// assert_eq!(entities.get(enemy), None);
+/// assert_eq!(entities.get(bullet), Some(&"bullet"));
+/// assert_eq!(entities[player], "player");
+/// ```
+#[derive(Clone)]
+pub struct SlotMap<V> {
+    slots: Vec<Slot<V>>,
+    /// The most recently freed slot, which links to the one freed before it.
+    free: Option<usize>,
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Slot<V> {
+    generation: u32,
+    entry: Entry<V>,
+}
+
+#[derive(Clone)]
+enum Entry<V> {
+    Occupied(V),
+    Vacant { next_free: Option<usize> },
+}
+
+impl<V> Slot<V> {
+    fn value(&self, key: Key) -> Option<&V> {
+        match &self.entry {
+            Entry::Occupied(value) if self.generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    fn value_mut(&mut self, key: Key) -> Option<&mut V> {
+        match &mut self.entry {
+            Entry::Occupied(value) if self.generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<V> SlotMap<V> {
+    /// Constructs an empty slot map.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Constructs an empty slot map with room for `capacity` values before it reallocates.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the slot map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the slot map holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a value and returns the key that refers to it.
+    pub fn insert(&mut self, value: V) -> Key {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Inserts the value returned by `f`, which is given the key that will refer to it. This lets
+    /// a value hold its own key.
+    pub fn insert_with_key<F>(&mut self, f: F) -> Key
+    where
+        F: FnOnce(Key) -> V,
+    {
+        let key = match self.free {
+            Some(index) => Key {
+                index,
+                generation: self.slots[index].generation,
+            },
+            None => Key {
+                index: self.slots.len(),
+                generation: 0,
+            },
+        };
+        let value = f(key);
+
+        match self.slots.get_mut(key.index) {
+            Some(slot) => {
+                if let Entry::Vacant { next_free } = slot.entry {
+                    self.free = next_free;
+                }
+                slot.entry = Entry::Occupied(value);
+            }
+            None => self.slots.push(Slot {
+                generation: 0,
+                entry: Entry::Occupied(value),
+            }),
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Returns a reference to the value that `key` refers to, or `None` if it has been removed.
+    pub fn get(&self, key: Key) -> Option<&V> {
+        self.slots.get(key.index)?.value(key)
+    }
+
+    /// Returns a mutable reference to the value that `key` refers to, or `None` if it has been
+    /// removed.
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut V> {
+        self.slots.get_mut(key.index)?.value_mut(key)
+    }
+
+    /// Returns `true` if `key` refers to a value in the slot map.
+    pub fn contains_key(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes the value that `key` refers to and returns it, or `None` if it was already
+    /// removed. The key, and every other copy of it, is invalidated.
+    pub fn remove(&mut self, key: Key) -> Option<V> {
+        self.get(key)?;
+        Some(self.vacate(key.index))
+    }
+
+    /// Frees the occupied slot at `index`, returning its value.
+    fn vacate(&mut self, index: usize) -> V {
+        let slot = &mut self.slots[index];
+        let next_free = match slot.generation.checked_add(1) {
+            Some(generation) => {
+                slot.generation = generation;
+                let next_free = self.free;
+                self.free = Some(index);
+                next_free
+            }
+            // The slot has run out of generations, so it is left out of the free list for good.
+            None => None,
+        };
+        self.len -= 1;
+        match core::mem::replace(&mut slot.entry, Entry::Vacant { next_free }) {
+            Entry::Occupied(value) => value,
+            Entry::Vacant { .. } => unreachable!("only occupied slots are vacated"),
+        }
+    }
+
+    /// Keeps only the values for which `f` returns `true`, removing the rest and invalidating
+    /// their keys.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Key, &mut V) -> bool,
+    {
+        for index in 0..self.slots.len() {
+            let slot = &mut self.slots[index];
+            let key = Key {
+                index,
+                generation: slot.generation,
+            };
+            if let Entry::Occupied(value) = &mut slot.entry {
+                if !f(key, value) {
+                    self.vacate(index);
+                }
+            }
+        }
+    }
+
+    /// Removes every value from the slot map, invalidating all of their keys. The slots are kept
+    /// for reuse.
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    /// Returns an iterator over the keys and values in the slot map, in order of their slots.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys and mutable references to the values in the slot map, in
+    /// order of their slots.
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the keys in the slot map, in order of their slots.
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values in the slot map, in order of their slots.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Returns an iterator over mutable references to the values in the slot map, in order of
+    /// their slots.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+impl<V> Default for SlotMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: core::fmt::Debug> core::fmt::Debug for SlotMap<V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V> core::ops::Index<Key> for SlotMap<V> {
+    type Output = V;
+
+    /// Returns a reference to the value that `key` refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value has been removed.
+    fn index(&self, key: Key) -> &Self::Output {
+        self.get(key).expect("Key is not present in the SlotMap")
+    }
+}
+
+impl<V> core::ops::IndexMut<Key> for SlotMap<V> {
+    /// Returns a mutable reference to the value that `key` refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value has been removed.
+    fn index_mut(&mut self, key: Key) -> &mut Self::Output {
+        self.get_mut(key)
+            .expect("Key is not present in the SlotMap")
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SlotMap<V> {
+    type Item = (Key, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut SlotMap<V> {
+    type Item = (Key, &'a mut V);
+    type IntoIter = IterMut<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the keys and values of a [`SlotMap`], skipping vacant slots.
+///
+/// This `struct` is created by [`SlotMap::iter`].
+pub struct Iter<'a, V> {
+    slots: Enumerate<slice::Iter<'a, Slot<V>>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Key, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Entry::Occupied(value) = &slot.entry {
+                self.len -= 1;
+                let key = Key {
+                    index,
+                    generation: slot.generation,
+                };
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
+
+impl<V> Clone for Iter<'_, V> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<V> core::fmt::Debug for Iter<'_, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Iter").field("len", &self.len).finish()
+    }
+}
+
+/// An iterator over the keys and mutable references to the values of a [`SlotMap`], skipping
+/// vacant slots.
+///
+/// This `struct` is created by [`SlotMap::iter_mut`].
+pub struct IterMut<'a, V> {
+    slots: Enumerate<slice::IterMut<'a, Slot<V>>>,
+    len: usize,
+}
+
+impl<'a, V> Iterator for IterMut<'a, V> {
+    type Item = (Key, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Entry::Occupied(value) = &mut slot.entry {
+                self.len -= 1;
+                let key = Key {
+                    index,
+                    generation: slot.generation,
+                };
+                return Some((key, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<V> ExactSizeIterator for IterMut<'_, V> {}
+
+impl<V> core::fmt::Debug for IterMut<'_, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IterMut").field("len", &self.len).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn stale_keys_are_rejected() {
+        let mut map = SlotMap::new();
+        let first = map.insert(1);
+        assert_eq!(map.remove(first), Some(1));
+        assert_eq!(map.remove(first), None);
+
+        let second = map.insert(2);
+        assert_eq!(second.index(), first.index());
+        assert_ne!(second, first);
+        assert_eq!(map.get(first), None);
+        assert_eq!(map.get_mut(first), None);
+        assert!(!map.contains_key(first));
+        assert_eq!(map.remove(first), None);
+        assert_eq!(map[second], 2);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn iteration_skips_vacant_slots() {
+        let mut map = SlotMap::new();
+        let keys: Vec<_> = (0..6).map(|value| map.insert(value)).collect();
+        for &key in keys.iter().step_by(2) {
+            map.remove(key);
+        }
+        assert!(map.values().eq(&[1, 3, 5]));
+        assert!(map.keys().eq([keys[1], keys[3], keys[5]]));
+        assert_eq!(map.iter().len(), 3);
+
+        for (_, value) in &mut map {
+            *value *= 10;
+        }
+        assert!(map.values().eq(&[10, 30, 50]));
+    }
+
+    #[test]
+    fn retain_and_clear_invalidate_keys() {
+        let mut map = SlotMap::new();
+        let keys: Vec<_> = (0..10).map(|value| map.insert(value)).collect();
+        map.retain(|_, value| *value % 3 == 0);
+        assert!(map.values().eq(&[0, 3, 6, 9]));
+        assert_eq!(map.get(keys[1]), None);
+        assert_eq!(map.get(keys[3]), Some(&3));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(keys.iter().all(|&key| !map.contains_key(key)));
+
+        // Every freed slot is reused before the map grows.
+        let reused: Vec<_> = (0..10).map(|value| map.insert(value)).collect();
+        assert!(reused.iter().all(|key| key.index() < 10));
+        assert!(keys.iter().all(|&key| !map.contains_key(key)));
+    }
+
+    #[test]
+    fn exhausted_slots_are_retired() {
+        let mut map = SlotMap::new();
+        let key = map.insert('a');
+        map.slots[key.index()].generation = u32::MAX;
+        let last = Key {
+            generation: u32::MAX,
+            ..key
+        };
+        assert_eq!(map.remove(last), Some('a'));
+
+        let next = map.insert('b');
+        assert_ne!(next.index(), key.index());
+        assert_eq!(map.get(last), None);
+    }
+
+    #[test]
+    fn insert_with_key() {
+        let mut map = SlotMap::new();
+        let key = map.insert_with_key(|key| key);
+        assert_eq!(map[key], key);
+    }
+
+    #[test]
+    fn matches_model() {
+        let mut rng = Rng::new(454);
+        let mut map = SlotMap::new();
+        let mut live: Vec<(Key, u64)> = Vec::new();
+        let mut dead: Vec<Key> = Vec::new();
+        for step in 0..5_000 {
+            if live.is_empty() || rng.below(3) > 0 {
+                let key = map.insert(step);
+                live.push((key, step));
+            } else {
+                let (key, value) = live.swap_remove(rng.below(live.len() as u64) as usize);
+                assert_eq!(map.remove(key), Some(value));
+                dead.push(key);
+            }
+            assert_eq!(map.len(), live.len());
+        }
+        for (key, value) in &live {
+            assert_eq!(map.get(*key), Some(value));
+        }
+        assert!(dead.iter().all(|&key| map.get(key).is_none()));
+        let mut expected = live.clone();
+        expected.sort_by_key(|(key, _)| key.index());
+        assert!(map.iter().map(|(key, value)| (key, *value)).eq(expected));
+    }
+}
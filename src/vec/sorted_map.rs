@@ -0,0 +1,497 @@
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+    slice,
+};
+
+/// A map that keeps its entries in a vector sorted by key, offering binary-search lookups over a
+/// flat buffer.
+///
+/// Like [`SortedVec`](crate::vec::sorted_vec::SortedVec), this tends to beat a tree for small to
+/// medium sized maps that are read far more often than they are written, both in memory use and
+/// in scan speed. Insertions and removals shift the entries after them, so they take linear time.
+/// Building a map with [`FromIterator`] sorts the entries only once.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::sorted_map::SortedMap;
+///
+/// let mut stock: SortedMap<&str, u32> = [("pear", 3), ("apple", 5), ("fig", 0)]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(stock.insert("fig", 7), Some(0));
+/// *stock.get_or_insert_with("kiwi", || 0) += 2;
+///
+/// assert_eq!(stock.get("apple"), Some(&5));
+/// assert_eq!(stock.range("b".."l"), [("fig", 7), ("kiwi", 2)]);
+/// assert!(stock.keys().eq(["apple", "fig", "kiwi", "pear"].iter()));
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct SortedMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> SortedMap<K, V> {
+    /// Constructs an empty map.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Constructs an empty map with room for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries in ascending order of their keys.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.entries
+    }
+
+    /// Returns an iterator over the entries in ascending order of their keys.
+    pub fn iter(&self) -> slice::Iter<'_, (K, V)> {
+        self.entries.iter()
+    }
+
+    /// Returns an iterator over the keys in ascending order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator over the values in ascending order of their keys.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator over mutable references to the values in ascending order of their
+    /// keys.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            entries: self.entries.iter_mut(),
+        }
+    }
+
+    /// Removes every entry from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<K: Ord, V> SortedMap<K, V> {
+    /// Builds a map out of `entries`, sorting them once. If a key occurs more than once, the
+    /// last occurrence wins.
+    pub fn from_unsorted(mut entries: Vec<(K, V)>) -> Self {
+        // The sort is stable, so duplicates stay in their original order. Keeping the first of
+        // each run of duplicates in place but moving the value of the later one into it leaves
+        // the last occurrence.
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|later, earlier| {
+            let duplicate = later.0 == earlier.0;
+            if duplicate {
+                core::mem::swap(later, earlier);
+            }
+            duplicate
+        });
+        Self { entries }
+    }
+
+    /// Binary searches for the entry with key `key`.
+    ///
+    /// Returns [`Ok`] with its index if there is one, otherwise [`Err`] with the index at which
+    /// it could be inserted to keep the map sorted.
+    pub fn binary_search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    /// Inserts a key-value pair into the map. If the key was already present, its value is
+    /// replaced and the old value returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.binary_search(&key) {
+            Ok(index) => Some(core::mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the value of `key`, or [`None`] if it is not present.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.binary_search(key).ok()?;
+        Some(&self.entries[index].1)
+    }
+
+    /// Returns a mutable reference to the value of `key`, or [`None`] if it is not present.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.binary_search(key).ok()?;
+        Some(&mut self.entries[index].1)
+    }
+
+    /// Returns a mutable reference to the value of `key`, inserting the value returned by
+    /// `default` first if the key is not present.
+    pub fn get_or_insert_with<F>(&mut self, key: K, default: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        let index = match self.binary_search(&key) {
+            Ok(index) => index,
+            Err(index) => {
+                self.entries.insert(index, (key, default()));
+                index
+            }
+        };
+        &mut self.entries[index].1
+    }
+
+    /// Returns `true` if the map contains `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.binary_search(key).is_ok()
+    }
+
+    /// Removes `key` from the map and returns its value, or [`None`] if it was not present.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes `key` from the map and returns the stored key and its value, or [`None`] if it
+    /// was not present.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.binary_search(key).ok()?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Returns the entries whose keys fall within `range`, in ascending order.
+    ///
+    /// An empty or inverted range yields an empty slice.
+    pub fn range<Q, R>(&self, range: R) -> &[(K, V)]
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self
+                .entries
+                .partition_point(|(key, _)| key.borrow() < start),
+            Bound::Excluded(start) => self
+                .entries
+                .partition_point(|(key, _)| key.borrow() <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.entries.partition_point(|(key, _)| key.borrow() <= end),
+            Bound::Excluded(end) => self.entries.partition_point(|(key, _)| key.borrow() < end),
+            Bound::Unbounded => self.entries.len(),
+        };
+        &self.entries[start..end.max(start)]
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for SortedMap<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(key, value)| (key, value)))
+            .finish()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SortedMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SortedMap<K, V> {
+    /// Collects the entries and sorts them once. If a key occurs more than once, the last
+    /// occurrence wins.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_unsorted(iter.into_iter().collect())
+    }
+}
+
+impl<K: Ord, V> From<Vec<(K, V)>> for SortedMap<K, V> {
+    fn from(entries: Vec<(K, V)>) -> Self {
+        Self::from_unsorted(entries)
+    }
+}
+
+impl<K, V> From<SortedMap<K, V>> for Vec<(K, V)> {
+    fn from(map: SortedMap<K, V>) -> Self {
+        map.entries
+    }
+}
+
+impl<K, V, Q> core::ops::Index<&Q> for SortedMap<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Output = V;
+
+    /// Returns a reference to the value of `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not present in the map.
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("Key is not present in the SortedMap")
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SortedMap<K, V> {
+    type Item = &'a (K, V);
+    type IntoIter = slice::Iter<'a, (K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> IntoIterator for SortedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = alloc::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// An iterator over the keys of a [`SortedMap`] in ascending order.
+///
+/// This `struct` is created by [`SortedMap::keys`].
+#[derive(Debug, Clone)]
+pub struct Keys<'a, K, V> {
+    entries: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// An iterator over the values of a [`SortedMap`] in ascending order of their keys.
+///
+/// This `struct` is created by [`SortedMap::values`].
+#[derive(Debug, Clone)]
+pub struct Values<'a, K, V> {
+    entries: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// An iterator over mutable references to the values of a [`SortedMap`] in ascending order of
+/// their keys.
+///
+/// This `struct` is created by [`SortedMap::values_mut`].
+#[derive(Debug)]
+pub struct ValuesMut<'a, K, V> {
+    entries: slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for ValuesMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeMap, format, string::String, vec};
+
+    #[test]
+    fn randomized_against_btree_map() {
+        let mut rng = Rng::new(450);
+        let mut map = SortedMap::new();
+        let mut reference = BTreeMap::new();
+
+        for _ in 0..5_000 {
+            let key = rng.below(300);
+            let value = rng.next_u64();
+            match rng.below(5) {
+                0 | 1 => assert_eq!(map.insert(key, value), reference.insert(key, value)),
+                2 => assert_eq!(map.remove(&key), reference.remove(&key)),
+                3 => {
+                    *map.get_or_insert_with(key, || value) ^= 1;
+                    *reference.entry(key).or_insert(value) ^= 1;
+                }
+                _ => {
+                    if let Some(value) = map.get_mut(&key) {
+                        *value = value.wrapping_add(1);
+                    }
+                    if let Some(value) = reference.get_mut(&key) {
+                        *value = value.wrapping_add(1);
+                    }
+                }
+            }
+
+            let probe = rng.below(300);
+            assert_eq!(map.get(&probe), reference.get(&probe));
+            assert_eq!(map.contains_key(&probe), reference.contains_key(&probe));
+            let (a, b) = (rng.below(300), rng.below(300));
+            let (low, high) = (a.min(b), a.max(b));
+            assert!(map
+                .range(low..=high)
+                .iter()
+                .map(|(key, value)| (key, value))
+                .eq(reference.range(low..=high)));
+            assert_eq!(map.len(), reference.len());
+        }
+
+        assert!(map.keys().eq(reference.keys()));
+        assert!(map.values().rev().eq(reference.values().rev()));
+        assert_eq!(Vec::from(map), reference.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn from_iter_keeps_last_duplicate() {
+        let map: SortedMap<i32, char> = vec![(2, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (2, 'e')]
+            .into_iter()
+            .collect();
+        assert_eq!(map.as_slice(), [(1, 'b'), (2, 'e'), (3, 'd')]);
+
+        let map = SortedMap::from_unsorted(vec![(5, 1), (5, 2), (5, 3)]);
+        assert_eq!(map.as_slice(), [(5, 3)]);
+        assert!(SortedMap::<i32, i32>::from_unsorted(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn range_bounds() {
+        let map: SortedMap<i32, ()> = (1..=5).map(|key| (key * 10, ())).collect();
+        let keys = |entries: &[(i32, ())]| entries.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(..)), [10, 20, 30, 40, 50]);
+        assert_eq!(keys(map.range(20..=40)), [20, 30, 40]);
+        assert_eq!(keys(map.range(20..40)), [20, 30]);
+        assert_eq!(
+            keys(map.range((Bound::Excluded(20), Bound::Unbounded))),
+            [30, 40, 50]
+        );
+        assert_eq!(keys(map.range(..=10)), [10]);
+        assert!(map
+            .range((Bound::Included(40), Bound::Excluded(20)))
+            .is_empty());
+        assert!(map.range(30..30).is_empty());
+    }
+
+    #[test]
+    fn borrowed_keys() {
+        let mut map: SortedMap<String, usize> = SortedMap::new();
+        for word in ["delta", "alpha", "charlie", "bravo"] {
+            map.insert(word.into(), word.len());
+        }
+        assert_eq!(map["charlie"], 7);
+        assert_eq!(map.remove_entry("alpha"), Some(("alpha".into(), 5)));
+        assert_eq!(
+            map.range::<str, _>((Bound::Included("b"), Bound::Excluded("d")))
+                .len(),
+            2
+        );
+        for value in map.values_mut() {
+            *value *= 2;
+        }
+        assert_eq!(
+            format!("{:?}", map),
+            r#"{"bravo": 10, "charlie": 14, "delta": 10}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Key is not present in the SortedMap")]
+    fn index_missing_key() {
+        let map: SortedMap<i32, i32> = SortedMap::new();
+        let _ = map[&1];
+    }
+}
@@ -0,0 +1,314 @@
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+    slice,
+};
+
+/// A vector that keeps its elements sorted, offering binary-search lookups over a flat buffer.
+///
+/// For small to medium sized lookup tables this tends to beat a tree, since the elements sit next
+/// to each other in memory. Insertions and removals shift the elements after them, so they take
+/// linear time.
+///
+/// Duplicates are allowed: an element that compares equal to ones already present is inserted
+/// right after them, so equal elements stay adjacent in the order they were inserted.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::sorted_vec::SortedVec;
+///
+/// let mut primes = SortedVec::from_unsorted(vec![7, 2, 5]);
+/// assert_eq!(primes.insert(3), 1);
+///
+/// assert_eq!(primes.as_slice(), [2, 3, 5, 7]);
+/// assert!(primes.contains(&5));
+/// assert_eq!(primes.range(3..7), [3, 5]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SortedVec<T> {
+    items: Vec<T>,
+}
+
+impl<T> SortedVec<T> {
+    /// Constructs an empty vector.
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// Constructs an empty vector with room for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the vector is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the elements in ascending order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns an iterator over the elements in ascending order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Removes every element from the vector.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl<T: Ord> SortedVec<T> {
+    /// Builds a sorted vector out of `items`, sorting them once.
+    ///
+    /// The sort is stable, so equal elements keep their relative order.
+    pub fn from_unsorted(mut items: Vec<T>) -> Self {
+        items.sort();
+        Self { items }
+    }
+
+    /// Inserts an element after every element that is less than or equal to it, and returns the
+    /// index it ended up at.
+    pub fn insert(&mut self, value: T) -> usize {
+        let index = self.items.partition_point(|item| *item <= value);
+        self.items.insert(index, value);
+        index
+    }
+
+    /// Binary searches for an element equal to `value`.
+    ///
+    /// Returns [`Ok`] with the index of the first such element if there is one, otherwise [`Err`]
+    /// with the index at which it could be inserted to keep the vector sorted.
+    pub fn binary_search<Q>(&self, value: &Q) -> Result<usize, usize>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.items.partition_point(|item| item.borrow() < value);
+        match self.items.get(index) {
+            Some(item) if item.borrow() == value => Ok(index),
+            _ => Err(index),
+        }
+    }
+
+    /// Returns `true` if the vector contains an element equal to `value`.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.binary_search(value).is_ok()
+    }
+
+    /// Removes the first element equal to `value` and returns it, or [`None`] if there is none.
+    pub fn remove<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.binary_search(value).ok()?;
+        Some(self.items.remove(index))
+    }
+
+    /// Returns the elements that fall within `range`, in ascending order.
+    ///
+    /// An empty or inverted range yields an empty slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::vec::sorted_vec::SortedVec;
+    ///
+    /// let words = SortedVec::from_unsorted(vec!["pear", "apple", "fig", "kiwi"]);
+    /// assert_eq!(words.range("b".."l"), ["fig", "kiwi"]);
+    /// assert_eq!(words.range(.."fig"), ["apple"]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> &[T]
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.items.partition_point(|item| item.borrow() < start),
+            Bound::Excluded(start) => self.items.partition_point(|item| item.borrow() <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.items.partition_point(|item| item.borrow() <= end),
+            Bound::Excluded(end) => self.items.partition_point(|item| item.borrow() < end),
+            Bound::Unbounded => self.items.len(),
+        };
+        &self.items[start..end.max(start)]
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedVec<T> {
+    /// Collects the elements and sorts them once.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_unsorted(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for SortedVec<T> {
+    fn from(items: Vec<T>) -> Self {
+        Self::from_unsorted(items)
+    }
+}
+
+impl<T> From<SortedVec<T>> for Vec<T> {
+    fn from(sorted: SortedVec<T>) -> Self {
+        sorted.items
+    }
+}
+
+impl<T> AsRef<[T]> for SortedVec<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedVec<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for SortedVec<T> {
+    type Item = T;
+    type IntoIter = alloc::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{collections::BTreeMap, string::String, vec};
+
+    #[test]
+    fn randomized_against_btree() {
+        let mut rng = Rng::new(420);
+        let mut sorted = SortedVec::new();
+        // Counts how often each value is present, since duplicates are allowed.
+        let mut reference: BTreeMap<u64, usize> = BTreeMap::new();
+
+        for _ in 0..5_000 {
+            let value = rng.below(200);
+            if rng.below(3) == 0 {
+                let removed = sorted.remove(&value);
+                match reference.get_mut(&value) {
+                    Some(count) => {
+                        assert_eq!(removed, Some(value));
+                        *count -= 1;
+                        if *count == 0 {
+                            reference.remove(&value);
+                        }
+                    }
+                    None => assert_eq!(removed, None),
+                }
+            } else {
+                let index = sorted.insert(value);
+                *reference.entry(value).or_default() += 1;
+                assert_eq!(sorted.as_slice()[index], value);
+                assert!(sorted.as_slice()[index + 1..]
+                    .iter()
+                    .all(|item| *item > value));
+            }
+
+            let probe = rng.below(200);
+            assert_eq!(sorted.contains(&probe), reference.contains_key(&probe));
+            let (a, b) = (rng.below(200), rng.below(200));
+            let (low, high) = (a.min(b), a.max(b));
+            let expected: usize = reference.range(low..high).map(|(_, count)| count).sum();
+            assert_eq!(sorted.range(low..high).len(), expected);
+            assert!(sorted
+                .range(low..high)
+                .iter()
+                .all(|item| (low..high).contains(item)));
+        }
+
+        let expected: Vec<u64> = reference
+            .iter()
+            .flat_map(|(value, count)| (0..*count).map(move |_| *value))
+            .collect();
+        assert_eq!(sorted.as_slice(), expected);
+        assert_eq!(sorted.len(), expected.len());
+    }
+
+    #[test]
+    fn duplicates_keep_insertion_order() {
+        let mut sorted = SortedVec::new();
+        for (key, tag) in [(2, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (2, 'e')] {
+            sorted.insert(Keyed(key, tag));
+        }
+        let tags: String = sorted.iter().map(|Keyed(_, tag)| *tag).collect();
+        assert_eq!(tags, "baced");
+
+        assert_eq!(sorted.binary_search(&2), Ok(1));
+        assert_eq!(sorted.remove(&2), Some(Keyed(2, 'a')));
+        assert_eq!(sorted.range(2..=2).len(), 2);
+    }
+
+    #[test]
+    fn binary_search_and_range_bounds() {
+        let sorted: SortedVec<i32> = vec![50, 10, 40, 20, 30].into_iter().collect();
+        assert_eq!(sorted.binary_search(&30), Ok(2));
+        assert_eq!(sorted.binary_search(&35), Err(3));
+        assert_eq!(sorted.binary_search(&0), Err(0));
+        assert_eq!(sorted.binary_search(&60), Err(5));
+
+        assert_eq!(sorted.range(..), [10, 20, 30, 40, 50]);
+        assert_eq!(sorted.range(20..=40), [20, 30, 40]);
+        assert_eq!(
+            sorted.range((Bound::Excluded(20), Bound::Excluded(40))),
+            [30]
+        );
+        assert_eq!(sorted.range(45..), [50]);
+        assert!(sorted
+            .range((Bound::Included(40), Bound::Excluded(20)))
+            .is_empty());
+        assert!(sorted.range(30..30).is_empty());
+        assert_eq!(Vec::from(sorted), [10, 20, 30, 40, 50]);
+    }
+
+    /// A value ordered by its first field only, to tell equal elements apart.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Keyed(i32, char);
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl Borrow<i32> for Keyed {
+        fn borrow(&self) -> &i32 {
+            &self.0
+        }
+    }
+}
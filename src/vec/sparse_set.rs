@@ -0,0 +1,226 @@
+use alloc::vec::Vec;
+use core::slice;
+
+/// A set of small integers with constant-time insertion, removal, lookup and clearing.
+///
+/// The members are stored contiguously in a dense array, in no particular order, which makes
+/// iterating over them as fast as iterating over a slice. A sparse array indexed by the keys
+/// records where each member sits in the dense one. It grows lazily to fit the largest key
+/// inserted so far, so memory use is proportional to that key rather than to the number of
+/// members.
+///
+/// An entry of the sparse array is only trusted if the dense array points back at it, so stale
+/// entries never need to be reset. This is what makes [`clear`](Self::clear) `O(1)`.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::sparse_set::SparseSet;
+///
+/// let mut alive = SparseSet::new();
+/// assert!(alive.insert(7));
+/// assert!(alive.insert(2));
+/// assert!(!alive.insert(7));
+///
+/// assert!(alive.contains(2));
+/// assert!(alive.remove(7));
+/// assert_eq!(alive.as_slice(), [2]);
+///
+/// alive.clear();
+/// assert!(!alive.contains(2));
+/// ```
+#[derive(Clone, Default)]
+pub struct SparseSet {
+    dense: Vec<usize>,
+    sparse: Vec<usize>,
+}
+
+impl SparseSet {
+    /// Constructs an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs an empty set that can hold the keys `0..universe` without growing, and up to
+    /// `universe` members before its dense array reallocates.
+    pub fn with_capacity(universe: usize) -> Self {
+        Self {
+            dense: Vec::with_capacity(universe),
+            sparse: alloc::vec![0; universe],
+        }
+    }
+
+    /// Returns the number of members in the set.
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns `true` if the set has no members.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Returns the members of the set, in the order of the dense array.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.dense
+    }
+
+    /// Returns an iterator over the members of the set, in the order of the dense array.
+    pub fn iter(&self) -> slice::Iter<'_, usize> {
+        self.dense.iter()
+    }
+
+    /// Returns the position of `key` in the dense array if it is a member.
+    fn position(&self, key: usize) -> Option<usize> {
+        let position = *self.sparse.get(key)?;
+        (self.dense.get(position) == Some(&key)).then_some(position)
+    }
+
+    /// Returns `true` if `key` is a member of the set.
+    pub fn contains(&self, key: usize) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Adds `key` to the set, returning `true` if it was not already a member.
+    pub fn insert(&mut self, key: usize) -> bool {
+        if self.contains(key) {
+            return false;
+        }
+        if key >= self.sparse.len() {
+            self.sparse.resize(key + 1, 0);
+        }
+        self.sparse[key] = self.dense.len();
+        self.dense.push(key);
+        true
+    }
+
+    /// Removes `key` from the set, returning `true` if it was a member.
+    ///
+    /// The last member of the dense array is moved into the gap, so the order of the members
+    /// changes.
+    pub fn remove(&mut self, key: usize) -> bool {
+        let position = match self.position(key) {
+            Some(position) => position,
+            None => return false,
+        };
+        self.dense.swap_remove(position);
+        if let Some(&moved) = self.dense.get(position) {
+            self.sparse[moved] = position;
+        }
+        true
+    }
+
+    /// Removes every member from the set in constant time.
+    pub fn clear(&mut self) {
+        self.dense.clear();
+    }
+}
+
+impl core::fmt::Debug for SparseSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for SparseSet {
+    /// Two sets are equal if they have the same members, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|&key| other.contains(key))
+    }
+}
+
+impl Eq for SparseSet {}
+
+impl Extend<usize> for SparseSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl FromIterator<usize> for SparseSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a> IntoIterator for &'a SparseSet {
+    type Item = &'a usize;
+    type IntoIter = slice::Iter<'a, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::collections::BTreeSet;
+
+    #[test]
+    fn remove_last_and_middle() {
+        let mut set: SparseSet = [4, 9, 1, 6].into_iter().collect();
+
+        // Removing the last member leaves the others where they are.
+        assert!(set.remove(6));
+        assert_eq!(set.as_slice(), [4, 9, 1]);
+
+        // Removing from the middle moves the last member into the gap.
+        assert!(set.remove(4));
+        assert_eq!(set.as_slice(), [1, 9]);
+        assert!(set.contains(1));
+        assert!(set.contains(9));
+        assert!(!set.contains(4));
+        assert!(!set.remove(4));
+
+        assert!(set.remove(1));
+        assert!(set.remove(9));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn reinsert_after_remove() {
+        let mut set = SparseSet::with_capacity(4);
+        assert!(set.insert(3));
+        assert!(set.insert(0));
+        assert!(set.remove(3));
+        assert!(set.insert(3));
+        assert_eq!(set.as_slice(), [0, 3]);
+
+        set.clear();
+        assert!(!set.contains(0) && !set.contains(3));
+        assert!(set.insert(3));
+        assert_eq!(set.len(), 1);
+        assert!(!set.contains(0));
+        assert!(set.insert(100));
+        assert_eq!(set, [100, 3].into_iter().collect());
+    }
+
+    #[test]
+    fn matches_btree_set() {
+        let mut rng = Rng::new(456);
+        let mut set = SparseSet::new();
+        let mut model = BTreeSet::new();
+        for _ in 0..5_000 {
+            let key = rng.below(200) as usize;
+            match rng.below(20) {
+                0 => {
+                    set.clear();
+                    model.clear();
+                }
+                1..=9 => assert_eq!(set.insert(key), model.insert(key)),
+                _ => assert_eq!(set.remove(key), model.remove(&key)),
+            }
+            assert_eq!(set.contains(key), model.contains(&key));
+            assert_eq!(set.len(), model.len());
+        }
+        let mut members = set.as_slice().to_vec();
+        members.sort_unstable();
+        assert!(members.iter().eq(&model));
+    }
+}
@@ -0,0 +1,460 @@
+use alloc::vec::Vec;
+use core::{iter::Enumerate, slice};
+
+/// A vector whose elements keep their indices when other elements are removed.
+///
+/// Removing an element leaves a hole in its slot instead of shifting the elements after it, so
+/// every index handed out by [`push`](Self::push) refers to the same element until that element
+/// is removed. Holes are reused by later pushes, most recently freed first, and
+/// [`compact`](Self::compact) closes all of them at once.
+///
+/// Unlike [`SlotMap`](crate::vec::slot_map::SlotMap), indices carry no generation, so an index
+/// whose element was removed refers to whatever is pushed into its slot next. That is up to the
+/// caller to rule out.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::stable_vec::StableVec;
+///
+/// let mut names = StableVec::new();
+/// let ada = names.push("Ada");
+/// let bob = names.push("Bob");
+/// let cy = names.push("Cy");
+///
+/// assert_eq!(names.remove(bob), Some("Bob"));
+/// assert_eq!(names[cy], "Cy");
+/// assert_eq!(names.get(bob), None);
+/// assert!(names.iter().eq([(ada, &"Ada"), (cy, &"Cy")]));
+///
+/// // The hole is filled by the next push.
+/// assert_eq!(names.push("Di"), bob);
+/// ```
+#[derive(Clone)]
+pub struct StableVec<T> {
+    slots: Vec<Slot<T>>,
+    /// The most recently freed slot, which links to the one freed before it.
+    free: Option<usize>,
+    len: usize,
+}
+
+#[derive(Clone)]
+enum Slot<T> {
+    Occupied(T),
+    Vacant { next_free: Option<usize> },
+}
+
+impl<T> StableVec<T> {
+    /// Constructs an empty vector.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Constructs an empty vector with room for `capacity` elements before it reallocates.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements in the vector, not counting holes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of slots in the vector, counting both elements and holes. Every index
+    /// handed out so far is below this.
+    pub fn capacity_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Adds an element to the vector and returns its index. The most recently freed hole is
+    /// filled if there is one, and otherwise the element goes at the end.
+    pub fn push(&mut self, value: T) -> usize {
+        self.len += 1;
+        match self.free {
+            Some(index) => {
+                let slot = core::mem::replace(&mut self.slots[index], Slot::Occupied(value));
+                if let Slot::Vacant { next_free } = slot {
+                    self.free = next_free;
+                }
+                index
+            }
+            None => {
+                self.slots.push(Slot::Occupied(value));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// Returns a reference to the element at `index`, or `None` if it is a hole or out of
+    /// bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match self.slots.get(index)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index`, or `None` if it is a hole or out
+    /// of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match self.slots.get_mut(index)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Returns `true` if there is an element at `index`.
+    pub fn contains_index(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Removes the element at `index` and returns it, leaving a hole in its place. Returns
+    /// `None` if there is no element there.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.get(index)?;
+        let hole = Slot::Vacant {
+            next_free: self.free,
+        };
+        self.free = Some(index);
+        self.len -= 1;
+        match core::mem::replace(&mut self.slots[index], hole) {
+            Slot::Occupied(value) => Some(value),
+            Slot::Vacant { .. } => None,
+        }
+    }
+
+    /// Removes every element and hole from the vector.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.free = None;
+        self.len = 0;
+    }
+
+    /// Closes every hole by moving the elements after it forwards, keeping them in the same
+    /// order. `remap` is called with the old and the new index of every element that moves, in
+    /// order, so that indices held elsewhere can be updated.
+    ///
+    /// Afterwards the elements occupy the indices `0..len`.
+    pub fn compact<F>(&mut self, mut remap: F)
+    where
+        F: FnMut(usize, usize),
+    {
+        let mut next = 0;
+        for index in 0..self.slots.len() {
+            if let Slot::Occupied(_) = self.slots[index] {
+                if index != next {
+                    self.slots.swap(index, next);
+                    remap(index, next);
+                }
+                next += 1;
+            }
+        }
+        self.slots.truncate(next);
+        self.free = None;
+    }
+
+    /// Returns an iterator over the indices and elements of the vector in order, skipping holes.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: self.slots.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over the indices and mutable references to the elements of the vector
+    /// in order, skipping holes.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for StableVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for StableVec<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> core::ops::Index<usize> for StableVec<T> {
+    type Output = T;
+
+    /// Returns a reference to the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no element at `index`.
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index)
+            .expect("Index does not hold an element in StableVec")
+    }
+}
+
+impl<T> core::ops::IndexMut<usize> for StableVec<T> {
+    /// Returns a mutable reference to the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no element at `index`.
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index)
+            .expect("Index does not hold an element in StableVec")
+    }
+}
+
+impl<T> Extend<T> for StableVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for StableVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<'a, T> IntoIterator for &'a StableVec<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut StableVec<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the indices and elements of a [`StableVec`], skipping holes.
+///
+/// This `struct` is created by [`StableVec::iter`].
+pub struct Iter<'a, T> {
+    slots: Enumerate<slice::Iter<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((index, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((index, slot)) = self.slots.next_back() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((index, value));
+            }
+        }
+        None
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Iter").field("len", &self.len).finish()
+    }
+}
+
+/// An iterator over the indices and mutable references to the elements of a [`StableVec`],
+/// skipping holes.
+///
+/// This `struct` is created by [`StableVec::iter_mut`].
+pub struct IterMut<'a, T> {
+    slots: Enumerate<slice::IterMut<'a, Slot<T>>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((index, value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((index, slot)) = self.slots.next_back() {
+            if let Slot::Occupied(value) = slot {
+                self.len -= 1;
+                return Some((index, value));
+            }
+        }
+        None
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> core::fmt::Debug for IterMut<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IterMut").field("len", &self.len).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn holes_are_reused_most_recent_first() {
+        let mut vec: StableVec<char> = "abcde".chars().collect();
+        assert_eq!(vec.remove(1), Some('b'));
+        assert_eq!(vec.remove(3), Some('d'));
+        assert_eq!(vec.remove(3), None);
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.capacity_slots(), 5);
+
+        assert_eq!(vec.push('x'), 3);
+        assert_eq!(vec.push('y'), 1);
+        assert_eq!(vec.push('z'), 5);
+        assert!(vec.iter().map(|(_, c)| *c).eq("aycxez".chars()));
+    }
+
+    #[test]
+    fn iteration_with_interleaved_holes() {
+        let mut vec: StableVec<usize> = (0..10).collect();
+        for index in [0, 2, 3, 7, 9] {
+            vec.remove(index);
+        }
+        assert!(vec.iter().eq([(1, &1), (4, &4), (5, &5), (6, &6), (8, &8)]));
+        assert!(vec.iter().rev().map(|(index, _)| index).eq([8, 6, 5, 4, 1]));
+        assert_eq!(vec.iter().len(), 5);
+
+        for (index, value) in &mut vec {
+            *value += index;
+        }
+        assert_eq!(vec[6], 12);
+        assert_eq!(vec.get(7), None);
+        assert_eq!(vec.get(10), None);
+    }
+
+    #[test]
+    fn compact_reports_remappings() {
+        let mut vec: StableVec<char> = "abcdef".chars().collect();
+        vec.remove(0);
+        vec.remove(3);
+        let mut moves = Vec::new();
+        vec.compact(|from, to| moves.push((from, to)));
+        assert_eq!(moves, [(1, 0), (2, 1), (4, 2), (5, 3)]);
+        assert!(vec.iter().eq([(0, &'b'), (1, &'c'), (2, &'e'), (3, &'f')]));
+        assert_eq!(vec.capacity_slots(), 4);
+        assert_eq!(vec.push('g'), 4);
+
+        let mut moves = Vec::new();
+        vec.compact(|from, to| moves.push((from, to)));
+        assert!(moves.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_hole() {
+        let mut vec: StableVec<i32> = (0..3).collect();
+        vec.remove(1);
+        let _ = vec[1];
+    }
+
+    #[test]
+    fn matches_model() {
+        let mut rng = Rng::new(455);
+        let mut vec = StableVec::new();
+        let mut model: Vec<Option<u64>> = Vec::new();
+        for step in 0..5_000 {
+            match rng.below(5) {
+                0 | 1 => {
+                    let index = rng.below(model.len() as u64 + 1) as usize;
+                    let expected = model.get_mut(index).and_then(Option::take);
+                    assert_eq!(vec.remove(index), expected);
+                }
+                2 if step % 100 == 0 => {
+                    vec.compact(|from, to| {
+                        assert!(to < from);
+                        model[to] = model[from].take();
+                    });
+                    model.truncate(vec.len());
+                    assert!(model.iter().all(Option::is_some));
+                }
+                _ => {
+                    let index = vec.push(step);
+                    if index == model.len() {
+                        assert!(model.iter().all(Option::is_some));
+                        model.push(Some(step));
+                    } else {
+                        assert_eq!(model[index], None);
+                        model[index] = Some(step);
+                    }
+                }
+            }
+            assert_eq!(vec.len(), model.iter().flatten().count());
+            assert_eq!(vec.capacity_slots(), model.len());
+        }
+        let expected = model
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| value.as_ref().map(|value| (index, value)));
+        assert!(vec.iter().eq(expected));
+    }
+}
@@ -0,0 +1,573 @@
+use alloc::collections::VecDeque;
+use core::{
+    mem::MaybeUninit,
+    ops::{Index, IndexMut},
+    ptr, slice,
+};
+
+/// A double-ended queue that stores up to `N` elements inline and moves to the heap once it
+/// grows past that.
+///
+/// While inline, the elements live in a ring buffer inside the deque itself, so short-lived
+/// queues that stay small never allocate. Pushing onto a full inline buffer moves every element
+/// into a heap-allocated [`VecDeque`] once, after which the deque behaves exactly like one. It
+/// does not move back inline when it shrinks again.
+///
+/// # Examples
+///
+/// ```
+/// use collect_me::vec::stack_deque::StackDeque;
+///
+/// let mut window: StackDeque<char, 4> = StackDeque::new();
+/// for c in "abc".chars() {
+///     window.push_back(c);
+/// }
+/// window.push_front('z');
+/// assert!(!window.spilled());
+///
+/// window.push_back('d');
+/// assert!(window.spilled());
+///
+/// assert_eq!(window.pop_front(), Some('z'));
+/// assert_eq!(window[0], 'a');
+/// assert!(window.iter().eq(&['a', 'b', 'c', 'd']));
+/// ```
+pub struct StackDeque<T, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+enum Storage<T, const N: usize> {
+    /// A ring buffer whose live elements start at `head` and wrap around the end of `buf`.
+    Inline {
+        buf: [MaybeUninit<T>; N],
+        head: usize,
+        len: usize,
+    },
+    Heap(VecDeque<T>),
+}
+
+impl<T, const N: usize> StackDeque<T, N> {
+    const UNINIT: MaybeUninit<T> = MaybeUninit::uninit();
+
+    /// Constructs an empty deque that does not allocate until it holds more than `N` elements.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::Inline {
+                buf: [Self::UNINIT; N],
+                head: 0,
+                len: 0,
+            },
+        }
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Inline { len, .. } => *len,
+            Storage::Heap(deque) => deque.len(),
+        }
+    }
+
+    /// Returns `true` if the deque is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of elements the deque can hold without allocating (again).
+    pub fn capacity(&self) -> usize {
+        match &self.storage {
+            Storage::Inline { .. } => N,
+            Storage::Heap(deque) => deque.capacity(),
+        }
+    }
+
+    /// Returns `true` if the elements have been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Appends an element to the back of the deque.
+    pub fn push_back(&mut self, value: T) {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } if *len < N => {
+                buf[wrap::<N>(*head + *len)].write(value);
+                *len += 1;
+            }
+            Storage::Inline { .. } => self.spill().push_back(value),
+            Storage::Heap(deque) => deque.push_back(value),
+        }
+    }
+
+    /// Prepends an element to the front of the deque.
+    pub fn push_front(&mut self, value: T) {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } if *len < N => {
+                *head = wrap::<N>(*head + N - 1);
+                buf[*head].write(value);
+                *len += 1;
+            }
+            Storage::Inline { .. } => self.spill().push_front(value),
+            Storage::Heap(deque) => deque.push_front(value),
+        }
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the deque is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } => {
+                if *len == 0 {
+                    return None;
+                }
+                *len -= 1;
+                // SAFETY: The slot of the last element is initialised. Decrementing `len` first
+                // marks it as vacant, so it is never read or dropped again.
+                Some(unsafe { buf[wrap::<N>(*head + *len)].assume_init_read() })
+            }
+            Storage::Heap(deque) => deque.pop_back(),
+        }
+    }
+
+    /// Removes the first element and returns it, or [`None`] if the deque is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } => {
+                if *len == 0 {
+                    return None;
+                }
+                let slot = *head;
+                *head = wrap::<N>(*head + 1);
+                *len -= 1;
+                // SAFETY: `slot` held the first element, which is initialised. Moving `head` past
+                // it first marks it as vacant, so it is never read or dropped again.
+                Some(unsafe { buf[slot].assume_init_read() })
+            }
+            Storage::Heap(deque) => deque.pop_front(),
+        }
+    }
+
+    /// Returns a reference to the element at `index`, counting from the front, or [`None`] if it
+    /// is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match &self.storage {
+            Storage::Inline { buf, head, len } => {
+                if index >= *len {
+                    return None;
+                }
+                // SAFETY: The first `len` slots starting at `head` are initialised.
+                Some(unsafe { buf[wrap::<N>(*head + index)].assume_init_ref() })
+            }
+            Storage::Heap(deque) => deque.get(index),
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index`, counting from the front, or
+    /// [`None`] if it is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } => {
+                if index >= *len {
+                    return None;
+                }
+                // SAFETY: The first `len` slots starting at `head` are initialised.
+                Some(unsafe { buf[wrap::<N>(*head + index)].assume_init_mut() })
+            }
+            Storage::Heap(deque) => deque.get_mut(index),
+        }
+    }
+
+    /// Returns the elements of the deque in order, as two slices that are joined end to end.
+    ///
+    /// The second slice is empty unless the elements wrap around the end of the buffer.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        match &self.storage {
+            Storage::Inline { buf, head, len } => {
+                let first = (*len).min(N - *head);
+                let (front, back) = (&buf[*head..*head + first], &buf[..*len - first]);
+                // SAFETY: Together the two ranges cover exactly the `len` initialised slots
+                // starting at `head`, and `MaybeUninit<T>` has the same layout as `T`.
+                unsafe { (slice_assume_init(front), slice_assume_init(back)) }
+            }
+            Storage::Heap(deque) => deque.as_slices(),
+        }
+    }
+
+    /// Returns the elements of the deque in order, as two mutable slices that are joined end to
+    /// end.
+    ///
+    /// The second slice is empty unless the elements wrap around the end of the buffer.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } => {
+                let first = (*len).min(N - *head);
+                let (wrapped, rest) = buf.split_at_mut(*head);
+                let (front, back) = (&mut rest[..first], &mut wrapped[..*len - first]);
+                // SAFETY: As in `as_slices`.
+                unsafe { (slice_assume_init_mut(front), slice_assume_init_mut(back)) }
+            }
+            Storage::Heap(deque) => deque.as_mut_slices(),
+        }
+    }
+
+    /// Rearranges the elements so that they are stored in one piece and returns them as a
+    /// mutable slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use collect_me::vec::stack_deque::StackDeque;
+    ///
+    /// let mut deque: StackDeque<i32, 4> = StackDeque::new();
+    /// deque.push_back(2);
+    /// deque.push_back(3);
+    /// deque.push_front(1);
+    ///
+    /// let contiguous = deque.make_contiguous();
+    /// contiguous.sort_by(|a, b| b.cmp(a));
+    /// assert_eq!(deque.as_slices(), (&[3, 2, 1][..], &[][..]));
+    /// ```
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        match &mut self.storage {
+            Storage::Inline { buf, head, len } => {
+                if *head + *len > N {
+                    buf.rotate_left(*head);
+                    *head = 0;
+                }
+                let live = &mut buf[*head..*head + *len];
+                // SAFETY: The elements no longer wrap around, so the `len` slots starting at
+                // `head` are exactly the initialised ones.
+                unsafe { &mut *(live as *mut [MaybeUninit<T>] as *mut [T]) }
+            }
+            Storage::Heap(deque) => deque.make_contiguous(),
+        }
+    }
+
+    /// Returns a front-to-back iterator over the elements of the deque.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    /// Removes every element from the deque. Any heap buffer is kept for reuse.
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Inline { .. } => while self.pop_back().is_some() {},
+            Storage::Heap(deque) => deque.clear(),
+        }
+    }
+
+    /// Moves the elements of a full inline buffer to the heap, making room for at least one more.
+    fn spill(&mut self) -> &mut VecDeque<T> {
+        if let Storage::Inline { buf, head, len } = &mut self.storage {
+            let mut deque = VecDeque::with_capacity((2 * N).max(4));
+            while *len > 0 {
+                let slot = *head;
+                *head = wrap::<N>(*head + 1);
+                *len -= 1;
+                // SAFETY: As in `pop_front`. The deque has room for every element, so pushing
+                // cannot panic while elements are half moved.
+                deque.push_back(unsafe { buf[slot].assume_init_read() });
+            }
+            self.storage = Storage::Heap(deque);
+        }
+
+        match &mut self.storage {
+            Storage::Heap(deque) => deque,
+            Storage::Inline { .. } => unreachable!("the deque was just moved to the heap"),
+        }
+    }
+}
+
+/// Maps an index into `0..2 * N` onto a slot of the inline ring buffer.
+fn wrap<const N: usize>(index: usize) -> usize {
+    if index >= N {
+        index - N
+    } else {
+        index
+    }
+}
+
+/// Reinterprets a slice of initialised slots as a slice of values.
+///
+/// # Safety
+///
+/// Every slot in `slots` must be initialised.
+unsafe fn slice_assume_init<T>(slots: &[MaybeUninit<T>]) -> &[T] {
+    // SAFETY: `MaybeUninit<T>` has the same layout as `T` and the caller guarantees that every
+    // slot is initialised.
+    unsafe { slice::from_raw_parts(slots.as_ptr().cast(), slots.len()) }
+}
+
+/// Reinterprets a mutable slice of initialised slots as a mutable slice of values.
+///
+/// # Safety
+///
+/// Every slot in `slots` must be initialised.
+unsafe fn slice_assume_init_mut<T>(slots: &mut [MaybeUninit<T>]) -> &mut [T] {
+    // SAFETY: As in `slice_assume_init`.
+    unsafe { slice::from_raw_parts_mut(slots.as_mut_ptr().cast(), slots.len()) }
+}
+
+impl<T, const N: usize> Drop for StackDeque<T, N> {
+    fn drop(&mut self) {
+        if let Storage::Inline { .. } = self.storage {
+            let (front, back) = self.as_mut_slices();
+            // SAFETY: The slices cover exactly the initialised slots of the inline buffer, which
+            // the deque owns and never touches again once it is dropped.
+            unsafe {
+                ptr::drop_in_place(front);
+                ptr::drop_in_place(back);
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> Default for StackDeque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for StackDeque<T, N> {
+    fn clone(&self) -> Self {
+        let mut clone = Self::new();
+        for value in self {
+            clone.push_back(value.clone());
+        }
+        clone
+    }
+}
+
+impl<T: core::fmt::Debug, const N: usize> core::fmt::Debug for StackDeque<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for StackDeque<T, N> {
+    /// Compares the elements of both deques in order, regardless of where they are stored.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for StackDeque<T, N> {}
+
+impl<T, const N: usize> Index<usize> for StackDeque<T, N> {
+    type Output = T;
+
+    /// Returns a reference to the element at `index`, counting from the front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("Index out of bounds in StackDeque")
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for StackDeque<T, N> {
+    /// Returns a mutable reference to the element at `index`, counting from the front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index)
+            .expect("Index out of bounds in StackDeque")
+    }
+}
+
+impl<T, const N: usize> Extend<T> for StackDeque<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for StackDeque<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a StackDeque<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A front-to-back iterator over the elements of a [`StackDeque`].
+///
+/// This `struct` is created by [`StackDeque::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Rng;
+    use alloc::{format, rc::Rc, vec::Vec};
+
+    fn check<T: PartialEq + core::fmt::Debug, const N: usize>(
+        deque: &StackDeque<T, N>,
+        reference: &VecDeque<T>,
+    ) {
+        assert_eq!(deque.len(), reference.len());
+        assert!(deque.capacity() >= deque.len());
+        if deque.len() > N {
+            assert!(deque.spilled());
+        }
+        for index in 0..=reference.len() {
+            assert_eq!(deque.get(index), reference.get(index));
+        }
+        assert!(deque.iter().eq(reference.iter()));
+        assert!(deque.iter().rev().eq(reference.iter().rev()));
+        assert_eq!(deque.iter().len(), reference.len());
+    }
+
+    fn randomized<const N: usize>(seed: u64) {
+        let mut rng = Rng::new(seed);
+        let mut deque: StackDeque<u64, N> = StackDeque::new();
+        let mut reference = VecDeque::new();
+
+        for step in 0..2_000 {
+            let value = rng.next_u64();
+            // Lean towards growing at first and towards shrinking later, so that the inline
+            // buffer wraps around a lot before the deque spills.
+            let grow = rng.below(10) < if step < 1_000 { 5 } else { 4 };
+            match (grow, rng.below(2) == 0) {
+                (true, true) => {
+                    deque.push_back(value);
+                    reference.push_back(value);
+                }
+                (true, false) => {
+                    deque.push_front(value);
+                    reference.push_front(value);
+                }
+                (false, true) => assert_eq!(deque.pop_back(), reference.pop_back()),
+                (false, false) => assert_eq!(deque.pop_front(), reference.pop_front()),
+            }
+
+            if !reference.is_empty() {
+                let index = rng.below(reference.len() as u64) as usize;
+                deque[index] ^= 1;
+                reference[index] ^= 1;
+                assert_eq!(deque[index], reference[index]);
+            }
+            if rng.below(50) == 0 {
+                assert_eq!(deque.make_contiguous(), reference.make_contiguous());
+            }
+            check(&deque, &reference);
+        }
+    }
+
+    #[test]
+    fn randomized_against_vec_deque() {
+        randomized::<0>(419);
+        randomized::<1>(420);
+        randomized::<4>(421);
+        randomized::<8>(422);
+        randomized::<64>(423);
+    }
+
+    #[test]
+    fn wraps_around_inline() {
+        let mut deque: StackDeque<i32, 4> = StackDeque::new();
+        deque.extend([1, 2, 3]);
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), Some(2));
+        deque.extend([4, 5, 6]);
+
+        assert!(!deque.spilled());
+        assert_eq!(deque.as_slices(), (&[3, 4][..], &[5, 6][..]));
+        assert_eq!(deque.make_contiguous(), [3, 4, 5, 6]);
+        assert_eq!(deque.as_slices(), (&[3, 4, 5, 6][..], &[][..]));
+
+        deque.push_front(2);
+        assert!(deque.spilled());
+        assert!(deque.iter().eq(&[2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn drops_every_element_once() {
+        let counter = Rc::new(());
+        {
+            let mut deque: StackDeque<Rc<()>, 4> = StackDeque::new();
+            deque.push_back(Rc::clone(&counter));
+            deque.push_front(Rc::clone(&counter));
+            deque.push_back(Rc::clone(&counter));
+            drop(deque.pop_front());
+            deque.push_back(Rc::clone(&counter));
+            deque.push_back(Rc::clone(&counter));
+            assert_eq!(Rc::strong_count(&counter), 5);
+            assert!(!deque.spilled());
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        {
+            let mut deque: StackDeque<Rc<()>, 2> = StackDeque::new();
+            deque.extend((0..5).map(|_| Rc::clone(&counter)));
+            assert!(deque.spilled());
+            let clone = deque.clone();
+            assert_eq!(Rc::strong_count(&counter), 11);
+            drop(clone);
+            deque.clear();
+            assert_eq!(Rc::strong_count(&counter), 1);
+        }
+    }
+
+    #[test]
+    fn equality_ignores_storage() {
+        let inline: StackDeque<i32, 8> = (0..5).collect();
+        let mut spilled: StackDeque<i32, 2> = (0..5).collect();
+        assert!(!inline.spilled());
+        assert!(spilled.spilled());
+        assert!(inline.iter().eq(spilled.iter()));
+        assert_eq!(spilled, (0..5).collect());
+
+        spilled.push_back(5);
+        assert_ne!(spilled, (0..5).collect());
+        assert_eq!(format!("{:?}", inline), "[0, 1, 2, 3, 4]");
+        assert_eq!(Vec::from_iter(inline.iter().copied()), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds in StackDeque")]
+    fn index_out_of_bounds() {
+        let deque: StackDeque<i32, 4> = (0..3).collect();
+        let _ = deque[3];
+    }
+}